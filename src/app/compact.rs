@@ -0,0 +1,55 @@
+use anyhow::Result;
+use chrono::{Duration, Local};
+
+use game_saver::{
+    archive::recompress_zstd,
+    config::{ArchiveFormat, Config},
+    files::{format_size, get_archive_files},
+};
+
+/// Recompress all autosaves that're older than `older_than_days` at the given zstd level.
+///
+/// Autosaves are created at zstd's fast default level, as they're created while playing.
+/// Old autosaves are rarely restored, so it's worth to spend more time on compressing them.
+pub fn compact_autosaves(config: &Config, older_than_days: u64, level: u8) -> Result<()> {
+    let older_than = Duration::try_days(older_than_days.try_into()?).unwrap_or(Duration::MAX);
+
+    let mut games: Vec<&String> = config.games.keys().collect();
+    games.sort();
+
+    let mut total_saved = 0;
+    for game in games {
+        let autosave_dir = config.autosave_dir(game);
+        if !config.games[game].has_autosaves() || !autosave_dir.exists() {
+            continue;
+        }
+
+        for save in get_archive_files(&autosave_dir)? {
+            if save.format != ArchiveFormat::TarZst
+                || Local::now() - save.last_modified < older_than
+            {
+                continue;
+            }
+
+            match recompress_zstd(&save.path, level) {
+                Ok((old_size, new_size)) if new_size < old_size => {
+                    let saved = old_size - new_size;
+                    total_saved += saved;
+                    println!(
+                        "{game}: {} {} -> {} (saved {})",
+                        save.file_name,
+                        format_size(old_size),
+                        format_size(new_size),
+                        format_size(saved)
+                    );
+                }
+                Ok(_) => (),
+                Err(err) => println!("{game}: Failed to compact {}: {err:#}", save.file_name),
+            }
+        }
+    }
+
+    println!("Saved {} in total", format_size(total_saved));
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread::sleep,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use crossbeam_channel::Receiver;
+use log::info;
+
+use game_saver::{config::Config, files::init_directories};
+
+use super::{
+    helper::event_output::EventOutput,
+    trash::purge_trash,
+    ui::state::AppState,
+    update::{handle_finished_jobs, handle_updates},
+    update_health_file,
+    worker::Job,
+};
+use crate::{cli::CliArguments, watcher::WatcherMessage};
+
+/// The interval in which the daemon checks for updates.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Run the autosave loop without the terminal ui, until `shutdown` is set.
+///
+/// All messages that would be shown in the event log are written to the log instead.
+/// Pending autosaves are created before returning.
+pub fn run_daemon(
+    config: Config,
+    receiver: Receiver<WatcherMessage>,
+    shutdown: &AtomicBool,
+    opt: &CliArguments,
+) -> Result<()> {
+    info!("Initializing directories");
+    init_directories(&config).context("Failed while initializing directories")?;
+    let purged = purge_trash(&config).context("Failed to remove old saves from the trash")?;
+    if purged > 0 {
+        info!("Removed {purged} old save(s) from the trash");
+    }
+
+    let mut state = AppState::new(&config)?;
+    state.event_output = EventOutput::from_cli(opt)?;
+    let health_file = opt.health_file.as_deref();
+    let mut last_health_update: Option<DateTime<Local>> = None;
+    info!("Daemon started, waiting for updates");
+    while !shutdown.load(Ordering::SeqCst) {
+        handle_updates(&mut state, &receiver)?;
+        forward_event_log(&mut state);
+        update_health_file(&state, health_file, &mut last_health_update, Local::now())?;
+        sleep(TICK);
+    }
+
+    info!("Shutting down, creating all pending autosaves");
+    flush_pending_saves(&mut state)?;
+    forward_event_log(&mut state);
+    info!("All pending autosaves have been created");
+
+    Ok(())
+}
+
+/// Create autosaves for all games with pending changes right away and wait for all jobs to
+/// finish. Neither the debounce nor the autosave timeout is respected, as we're about to exit.
+fn flush_pending_saves(state: &mut AppState) -> Result<()> {
+    while !state.engine.pending_games().is_empty() || !state.worker.is_idle() {
+        handle_finished_jobs(state)?;
+
+        for game in state.engine.pending_games() {
+            // Wait until any running save or restore for this game finished.
+            if state.worker.is_busy(&game) {
+                continue;
+            }
+
+            state.engine.discard_pending(&game);
+            if state.config.games.contains_key(&game) {
+                state.worker.submit(&state.config, Job::Autosave { game })?;
+            }
+        }
+
+        sleep(TICK);
+    }
+
+    Ok(())
+}
+
+/// There's no event log without the terminal ui, so its messages are moved to the log.
+fn forward_event_log(state: &mut AppState) {
+    for message in state.event_logs.items.drain(..) {
+        // The log has its own timestamps.
+        let message = message
+            .split_once(" - ")
+            .map_or(message.as_str(), |(_, message)| message);
+        info!("{message}");
+    }
+    state.event_logs.state.select(None);
+}
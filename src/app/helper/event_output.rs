@@ -0,0 +1,94 @@
+use std::{fs::File, io::Write, os::unix::net::UnixStream, path::Path, time::Duration};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde_derive::Serialize;
+
+use crate::cli::CliArguments;
+
+/// How long writing an event to the socket may block.
+/// A listener that stopped reading would otherwise stall game-saver, once the socket's buffer is
+/// full.
+const SOCKET_WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The kinds of events that're written to the event output.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    SaveCreated,
+    SaveRestored,
+    SaveDeleted,
+    Error,
+}
+
+/// A single line of the event output.
+#[derive(Serialize)]
+struct Event<'a> {
+    event: EventKind,
+    game: &'a str,
+    /// The affected save, if there's one.
+    path: Option<String>,
+    timestamp: DateTime<Local>,
+    /// The error message of `error` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+}
+
+/// Structured events for external monitoring, such as status bars.
+/// Each event is written as a single line of JSON.
+pub struct EventOutput {
+    writer: Box<dyn Write>,
+}
+
+impl EventOutput {
+    /// Open the event output that has been requested on the commandline, if any.
+    ///
+    /// Events are appended to `--events-file`.
+    /// `--events-socket` connects to a unix socket, which has to be created by the listener.
+    /// Writes to the socket fail after [SOCKET_WRITE_TIMEOUT], if the listener stopped reading.
+    pub fn from_cli(opt: &CliArguments) -> Result<Option<EventOutput>> {
+        let writer: Box<dyn Write> = if let Some(path) = &opt.events_file {
+            let file = File::options()
+                .create(true)
+                .append(true)
+                .open(path)
+                .context(format!("Failed to open events file {path:?}"))?;
+            Box::new(file)
+        } else if let Some(path) = &opt.events_socket {
+            let stream = UnixStream::connect(path)
+                .context(format!("Failed to connect to events socket {path:?}"))?;
+            stream
+                .set_write_timeout(Some(SOCKET_WRITE_TIMEOUT))
+                .context(format!("Failed to set write timeout of {path:?}"))?;
+            Box::new(stream)
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(EventOutput { writer }))
+    }
+
+    /// Write an event.
+    pub fn emit(
+        &mut self,
+        kind: EventKind,
+        game: &str,
+        path: Option<&Path>,
+        message: Option<&str>,
+    ) -> Result<()> {
+        let event = Event {
+            event: kind,
+            game,
+            path: path.map(|path| path.to_string_lossy().into_owned()),
+            timestamp: Local::now(),
+            message,
+        };
+        let mut line = serde_json::to_string(&event)?;
+        line.push('\n');
+
+        self.writer
+            .write_all(line.as_bytes())
+            .context("Failed to write event")?;
+        self.writer.flush().context("Failed to write event")
+    }
+}
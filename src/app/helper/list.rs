@@ -1,6 +1,9 @@
-use ratatui::widgets::ListState;
+use std::path::Path;
 
-use super::files::SaveFile;
+use chrono::{DateTime, Local};
+use ratatui::{layout::Rect, widgets::ListState};
+
+use game_saver::files::SaveFile;
 
 pub trait StatefulList {
     type Item;
@@ -51,7 +54,22 @@ impl StatefulList for StringList {
 /// It's a wrapper that manages a list of savegame file infos where items can be selected.
 pub struct SaveList {
     pub state: ListState,
+    /// The saves that match the [Self::filter]. These are the ones that're shown.
     pub items: Vec<SaveFile>,
+    /// All saves, regardless of the filter.
+    all_items: Vec<SaveFile>,
+    /// Only saves whose name contains this text are shown. Matching ignores the case.
+    pub filter: String,
+    /// The state of the rendered list, which additionally contains the date headers.
+    /// [Self::state] refers to the index in [Self::items], while this state refers to the row.
+    pub render_state: ListState,
+}
+
+/// A row of a rendered [SaveList].
+/// Saves are grouped under non-selectable headers by their age.
+pub enum SaveRow<'a> {
+    Header(&'static str),
+    Save(&'a SaveFile),
 }
 
 impl SaveList {
@@ -59,7 +77,10 @@ impl SaveList {
     pub fn with_items(items: Vec<SaveFile>) -> SaveList {
         let mut list = SaveList {
             state: ListState::default(),
-            items,
+            items: items.clone(),
+            all_items: items,
+            filter: String::new(),
+            render_state: ListState::default(),
         };
         list.autoselect_first();
 
@@ -71,6 +92,96 @@ impl SaveList {
         let selected = self.state.selected()?;
         self.items.get(selected).cloned()
     }
+
+    /// Select the save at `path`, if it's shown.
+    pub fn select_path(&mut self, path: &Path) {
+        if let Some(index) = self.items.iter().position(|save| save.path == path) {
+            self.state.select(Some(index));
+        }
+    }
+
+    /// Replace the items of the list, while trying to keep the current selection.
+    /// The current filter is applied to the new items.
+    pub fn set_items(&mut self, items: Vec<SaveFile>) {
+        self.all_items = items;
+        self.apply_filter();
+    }
+
+    /// Only show saves whose name contains `filter`, while trying to keep the current selection.
+    /// An empty filter shows all saves again.
+    pub fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.apply_filter();
+    }
+
+    /// Whether some saves are hidden by the filter.
+    pub fn is_filtered(&self) -> bool {
+        !self.filter.is_empty()
+    }
+
+    /// Update the shown items from all items and the current filter.
+    ///
+    /// If the selected save is still shown, it stays selected.
+    /// Otherwise, its nearest shown neighbor gets selected instead.
+    fn apply_filter(&mut self) {
+        let previous_index = self.state.selected();
+        let previous_path = self.get_selected().map(|save| save.path);
+        let filter = self.filter.to_lowercase();
+        self.items = self
+            .all_items
+            .iter()
+            .filter(|save| save.file_name.to_lowercase().contains(&filter))
+            .cloned()
+            .collect();
+
+        let Some(previous_index) = previous_index else {
+            return;
+        };
+        if self.items.is_empty() {
+            self.state.select(None);
+            return;
+        }
+
+        let index = previous_path
+            .and_then(|path| self.items.iter().position(|save| save.path == path))
+            .unwrap_or_else(|| previous_index.min(self.items.len() - 1));
+        self.state.select(Some(index));
+    }
+
+    /// Get all rows of the list, including the date headers.
+    /// Also returns the row of the currently selected save.
+    pub fn rows(&self, now: DateTime<Local>) -> (Vec<SaveRow<'_>>, Option<usize>) {
+        let mut rows = Vec::new();
+        let mut selected_row = None;
+        let mut current_group = None;
+
+        // The saves are sorted by date, so each group is contiguous.
+        for (index, save) in self.items.iter().enumerate() {
+            let group = date_group(save.last_modified, now);
+            if current_group != Some(group) {
+                rows.push(SaveRow::Header(group));
+                current_group = Some(group);
+            }
+
+            if self.state.selected() == Some(index) {
+                selected_row = Some(rows.len());
+            }
+            rows.push(SaveRow::Save(save));
+        }
+
+        (rows, selected_row)
+    }
+}
+
+/// Get the name of the group, a save with the given date is displayed in.
+fn date_group(date: DateTime<Local>, now: DateTime<Local>) -> &'static str {
+    let days = (now.date_naive() - date.date_naive()).num_days();
+    match days {
+        ..=0 => "Today",
+        1 => "Yesterday",
+        2..=6 => "This week",
+        _ => "Older",
+    }
 }
 
 impl StatefulList for SaveList {
@@ -99,6 +210,18 @@ pub trait Navigate {
     /// Select the previous item in the list.
     /// If there are no more items, we go to the the last item of the list.
     fn previous(&mut self);
+
+    /// Select the first item in the list, if there are any items.
+    fn first(&mut self);
+
+    /// Select the last item in the list, if there are any items.
+    fn last(&mut self);
+
+    /// Move the selection down by `rows` items, without wrapping around.
+    fn page_down(&mut self, rows: usize);
+
+    /// Move the selection up by `rows` items, without wrapping around.
+    fn page_up(&mut self, rows: usize);
 }
 
 impl<I, T> Navigate for T
@@ -151,4 +274,44 @@ where
         };
         self.get_state().select(Some(i));
     }
+
+    fn first(&mut self) {
+        self.autoselect_first();
+    }
+
+    fn last(&mut self) {
+        let len = self.get_items().len();
+        // Remove selection, if no elements exist.
+        self.get_state().select(len.checked_sub(1));
+    }
+
+    fn page_down(&mut self, rows: usize) {
+        let Some(last) = self.get_items().len().checked_sub(1) else {
+            self.get_state().select(None);
+            return;
+        };
+        let i = match self.get_state().selected() {
+            Some(i) => i.saturating_add(rows).min(last),
+            None => 0,
+        };
+        self.get_state().select(Some(i));
+    }
+
+    fn page_up(&mut self, rows: usize) {
+        let Some(last) = self.get_items().len().checked_sub(1) else {
+            self.get_state().select(None);
+            return;
+        };
+        let i = match self.get_state().selected() {
+            Some(i) => i.saturating_sub(rows).min(last),
+            None => 0,
+        };
+        self.get_state().select(Some(i));
+    }
+}
+
+/// The amount of rows that are visible in a list with borders, which has been rendered to `area`.
+/// This is used as the step size for page-wise navigation.
+pub fn page_size(area: Rect) -> usize {
+    usize::from(area.height.saturating_sub(2)).max(1)
 }
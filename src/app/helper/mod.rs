@@ -1,5 +1,8 @@
-pub mod files;
+pub mod event_output;
 pub mod list;
+pub mod notification;
+pub mod persistence;
+pub mod process;
 pub mod terminal;
 
 #[macro_export]
@@ -0,0 +1,21 @@
+use std::thread;
+
+use log::debug;
+use notify_rust::Notification;
+
+/// Show a desktop notification.
+///
+/// This is done in a separate thread, as talking to the notification daemon may take a while.
+/// Systems without a notification daemon simply don't show anything.
+pub fn notify(summary: String, body: String) {
+    thread::spawn(move || {
+        let result = Notification::new()
+            .appname("game-saver")
+            .summary(&summary)
+            .body(&body)
+            .show();
+        if let Err(err) = result {
+            debug!("Failed to show desktop notification '{summary}': {err}");
+        }
+    });
+}
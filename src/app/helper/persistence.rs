@@ -0,0 +1,79 @@
+use std::{
+    collections::BTreeMap,
+    fs::{create_dir_all, read_to_string, write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use log::warn;
+use serde_derive::{Deserialize, Serialize};
+
+use game_saver::config::Config;
+
+/// UI state that's remembered between sessions.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PersistedState {
+    /// The game that was selected when the app was closed.
+    pub selected_game: Option<String>,
+    /// The list that had the focus when the app was closed.
+    #[serde(default)]
+    pub focused_list: FocusedList,
+    /// The selected autosave of the selected game.
+    #[serde(default)]
+    pub selected_autosave: Option<PathBuf>,
+    /// The selected manual save of the selected game.
+    #[serde(default)]
+    pub selected_save: Option<PathBuf>,
+    /// The last `savegame_location` of each game that existed.
+    /// Used to detect locations that disappeared, e.g. after reinstalling a game on another drive.
+    #[serde(default)]
+    pub known_locations: BTreeMap<String, String>,
+    /// The save of each game that has been restored last, which is the one that's currently live.
+    /// Games are removed, once their savegame location diverged from that save.
+    #[serde(default)]
+    pub last_restored: BTreeMap<String, PathBuf>,
+}
+
+/// The lists of the main screen, which can have the focus.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusedList {
+    #[default]
+    Games,
+    Autosaves,
+    Saves,
+}
+
+impl PersistedState {
+    /// The path of the state file inside the configured data directory.
+    pub fn path(config: &Config) -> Result<PathBuf> {
+        Ok(config.data_directory()?.join("game_saver_state.toml"))
+    }
+
+    /// Load the state from the previous session.
+    /// A missing or broken state file isn't critical, so we fall back to the default state.
+    pub fn load(config: &Config) -> PersistedState {
+        let state = PersistedState::path(config).and_then(|path| {
+            if !path.exists() {
+                return Ok(PersistedState::default());
+            }
+            let content = read_to_string(&path).context(format!("Couldn't read {path:?}"))?;
+            toml::from_str(&content).context(format!("Couldn't parse {path:?}"))
+        });
+
+        state.unwrap_or_else(|err| {
+            warn!("Failed to load state of previous session: {err:?}");
+            PersistedState::default()
+        })
+    }
+
+    /// Write the state to disk, so it can be restored on the next start.
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let path = PersistedState::path(config)?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).context(format!("Failed to create directory {parent:?}"))?;
+        }
+        let content = toml::to_string(self).context("Failed to serialize state")?;
+        write(&path, content).context(format!("Failed to write state to {path:?}"))
+    }
+}
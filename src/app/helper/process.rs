@@ -0,0 +1,100 @@
+use std::{
+    fs::{read_dir, read_to_string},
+    os::unix::process::CommandExt,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+use anyhow::{Context, Result};
+
+/// Check whether a process with the given name is currently running.
+///
+/// The name is compared to the process name (`/proc/<pid>/comm`) and the file name of the
+/// executable of each process. This only works on systems that provide `/proc`.
+/// On all other systems, no process is ever considered to be running.
+pub fn is_process_running(name: &str) -> bool {
+    let Ok(entries) = read_dir("/proc") else {
+        return false;
+    };
+
+    for entry in entries.flatten() {
+        // Only the numeric directories belong to processes.
+        let file_name = entry.file_name();
+        if !file_name
+            .to_string_lossy()
+            .chars()
+            .all(|c| c.is_ascii_digit())
+        {
+            continue;
+        }
+
+        let path = entry.path();
+        if let Ok(comm) = read_to_string(path.join("comm")) {
+            // The kernel truncates process names to 15 characters.
+            let comm = comm.trim_end();
+            if comm == name || (comm.len() == 15 && name.starts_with(comm)) {
+                return true;
+            }
+        }
+
+        // The arguments are separated by null bytes, the first one is the executable.
+        if let Ok(cmdline) = read_to_string(path.join("cmdline")) {
+            let executable = cmdline.split('\0').next().unwrap_or_default();
+            if Path::new(executable)
+                .file_name()
+                .is_some_and(|file| file == name)
+            {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Open a directory in the platform's file manager.
+/// The file manager is spawned in the background, so it doesn't block the UI.
+pub fn open_in_file_manager(path: &Path) -> Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+
+    let mut child = Command::new(opener)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context(format!("Failed to run {opener}"))?;
+
+    // Reap the process, once it exits.
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
+
+/// Run a user provided command via `sh -c` in the background.
+///
+/// The command runs in its own process group, so it keeps running when game-saver exits or the
+/// terminal sends `CTRL+c`.
+pub fn spawn_detached(command: &str) -> Result<()> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .context(format!("Failed to spawn command: {command}"))?;
+
+    // Reap the process, once it exits.
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
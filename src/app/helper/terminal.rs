@@ -1,4 +1,11 @@
-use std::io::{self, Stdout};
+use std::{
+    io::{self, Stdout},
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Once,
+    },
+};
 
 use anyhow::{Context, Result};
 use crossterm::{
@@ -10,11 +17,20 @@ use ratatui::{backend::CrosstermBackend, Terminal as TuiTerminal};
 
 pub type Terminal = TuiTerminal<CrosstermBackend<Stdout>>;
 
+/// Whether the mouse has been captured by [init_terminal].
+/// The terminal is restored from places without access to the config, such as the panic hook.
+static MOUSE_CAPTURED: AtomicBool = AtomicBool::new(false);
+
 /// Initialize and return a crossterm terminal instance
-pub fn init_terminal() -> Result<Terminal> {
+/// Without `mouse_capture`, the terminal's own text selection keeps working.
+pub fn init_terminal(mouse_capture: bool) -> Result<Terminal> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if mouse_capture {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    MOUSE_CAPTURED.store(mouse_capture, Ordering::SeqCst);
     let backend = CrosstermBackend::new(stdout);
 
     Terminal::new(backend).context("Failed to initialize terminal")
@@ -23,12 +39,31 @@ pub fn init_terminal() -> Result<Terminal> {
 /// Restore the terminal to its previous "normal" state.
 pub fn restore_terminal(terminal: &mut Terminal) -> Result<()> {
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    if MOUSE_CAPTURED.swap(false, Ordering::SeqCst) {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
     terminal.show_cursor()?;
 
     Ok(())
 }
+
+/// Restore the terminal before the panic message is printed.
+///
+/// The previous panic hook (`better_panic`) is chained, so it still prints the panic.
+/// Without this, a panic leaves the user with a terminal that's still in raw mode.
+/// The hook is only installed once, no matter how often this is called.
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = disable_raw_mode();
+            let _ = execute!(io::stdout(), LeaveAlternateScreen);
+            if MOUSE_CAPTURED.load(Ordering::SeqCst) {
+                let _ = execute!(io::stdout(), DisableMouseCapture);
+            }
+            previous_hook(info);
+        }));
+    });
+}
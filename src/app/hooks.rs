@@ -0,0 +1,38 @@
+use std::{path::Path, process::Command};
+
+use anyhow::{bail, Context, Result};
+
+/// Run a user provided hook command via `sh -c`.
+///
+/// The placeholders `{game}` and `{archive}` are replaced by the shell-quoted name of the game
+/// and path of the archive. Both values are also available as the `GAME_SAVER_GAME` and
+/// `GAME_SAVER_ARCHIVE` environment variables.
+pub fn run_hook(command: &str, game: &str, archive: &Path) -> Result<()> {
+    let archive = archive.to_string_lossy();
+    let command = command
+        .replace("{game}", &shell_quote(game))
+        .replace("{archive}", &shell_quote(&archive));
+
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .env("GAME_SAVER_GAME", game)
+        .env("GAME_SAVER_ARCHIVE", archive.as_ref())
+        .output()
+        .context(format!("Failed to spawn hook command: {command}"))?;
+
+    if !output.status.success() {
+        bail!(
+            "Hook command '{command}' failed with {}:\nSTDERR:\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end(),
+        )
+    }
+
+    Ok(())
+}
+
+/// Wrap a value in single quotes, so it's passed to the shell as a single argument.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
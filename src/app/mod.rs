@@ -1,39 +1,91 @@
+use std::{path::Path, thread::sleep};
+
 use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
 use crossbeam_channel::Receiver;
 use log::info;
 
+mod compact;
+mod daemon;
 mod helper;
-mod saves;
+mod hooks;
+mod orphans;
+mod settings;
+#[cfg(test)]
+mod tests;
+mod trash;
 mod ui;
+mod undo;
 mod update;
+mod verify;
+mod wizard;
+mod worker;
+
+pub use self::{
+    compact::compact_autosaves,
+    daemon::run_daemon,
+    orphans::clean_orphaned_directories,
+    ui::state::has_listed_games,
+    verify::verify_backups,
+    wizard::{run_empty_config_screen, run_setup_wizard},
+};
+
+use game_saver::{
+    config::Config,
+    files::{init_directories, write_health_file},
+};
 
 use self::{
     helper::{
-        files::init_directories,
-        terminal::{restore_terminal, Terminal},
+        event_output::EventOutput,
+        terminal::{install_panic_hook, restore_terminal, Terminal},
     },
+    trash::purge_trash,
     ui::{
         draw::draw_ui,
         events::{handle_events, EventResult},
-        state::AppState,
+        state::{AppState, Input, InputType, UiState},
     },
-    update::handle_updates,
+    undo::clear_undo_dir,
+    update::{handle_finished_jobs, handle_updates},
 };
-use crate::{config::Config, watcher::Update};
+use crate::{cli::CliArguments, watcher::WatcherMessage};
+
+/// The interval in seconds in which the health file is updated.
+const HEALTH_FILE_INTERVAL: i64 = 10;
+
+/// How often the worker is checked for finished jobs while waiting to quit.
+const JOB_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
 
 /// Run the app.
 ///
 /// - Initialize directories
 /// - Initialize terminal
 /// - Enter the Event->Update->Draw loop
-pub fn run(config: Config, receiver: Receiver<Update>) -> Result<()> {
+pub fn run(config: Config, receiver: Receiver<WatcherMessage>, opt: &CliArguments) -> Result<()> {
     info!("Initializing directories");
     init_directories(&config).context("Failed while initializing directories")?;
+    let purged = purge_trash(&config).context("Failed to remove old saves from the trash")?;
     // Create a new app with some example state
     let mut state = AppState::new(&config)?;
+    state.debug_events = opt.debug_events;
+    state.event_output = EventOutput::from_cli(opt)?;
+    state.config_path = Some(Config::path(&opt.config)?);
+    // Ask for the new location of every game, whose savegame location disappeared.
+    for game in state.disappeared_locations.clone() {
+        state.push_state(UiState::Input(Input::new(
+            game,
+            String::new(),
+            InputType::Relocate,
+        )));
+    }
+    if purged > 0 {
+        state.log(&format!("Removed {purged} old save(s) from the trash"));
+    }
 
     info!("Initializing terminal");
-    let mut terminal = helper::terminal::init_terminal()?;
+    install_panic_hook();
+    let mut terminal = helper::terminal::init_terminal(config.mouse_capture)?;
 
     // One initial clear and draw
     // From now on, we only redraw, if there're actual changes.
@@ -42,11 +94,23 @@ pub fn run(config: Config, receiver: Receiver<Update>) -> Result<()> {
 
     // Restore the terminal in case any errors happen.
     // Otherwise the terminal won't be usable as it's still in AlternateScreen mode.
-    if let Err(error) = main_loop(&mut state, &mut terminal, receiver) {
+    if let Err(error) = main_loop(
+        &mut state,
+        &mut terminal,
+        receiver,
+        opt.health_file.as_deref(),
+    ) {
         restore_terminal(&mut terminal)?;
         return Err(error);
     }
 
+    finish_running_jobs(&mut state)?;
+
+    state
+        .persist()
+        .context("Failed to save state for the next session")?;
+    clear_undo_dir(&state.config).context("Failed to remove files of the undo history")?;
+
     Ok(())
 }
 
@@ -57,8 +121,10 @@ pub fn run(config: Config, receiver: Receiver<Update>) -> Result<()> {
 pub fn main_loop(
     state: &mut AppState,
     terminal: &mut Terminal,
-    receiver: Receiver<Update>,
+    receiver: Receiver<WatcherMessage>,
+    health_file: Option<&Path>,
 ) -> Result<()> {
+    let mut last_health_update: Option<DateTime<Local>> = None;
     loop {
         let mut draw_scheduled = false;
 
@@ -76,6 +142,52 @@ pub fn main_loop(
         if draw_scheduled {
             draw_ui(terminal, state)?;
         }
+
+        update_health_file(state, health_file, &mut last_health_update, Local::now())?;
+    }
+
+    Ok(())
+}
+
+/// Quitting while a save or restore is in flight would leave a half-written archive or savegame.
+/// Wait for the worker to finish all jobs, like the daemon does before shutting down.
+fn finish_running_jobs(state: &mut AppState) -> Result<()> {
+    if state.worker.is_idle() {
+        return Ok(());
+    }
+
+    println!("Waiting for running saves and restores to finish...");
+    while !state.worker.is_idle() {
+        handle_finished_jobs(state)?;
+        sleep(JOB_POLL_INTERVAL);
+    }
+
+    Ok(())
+}
+
+/// Signal that we're still alive, if a health file has been requested.
+///
+/// The file isn't updated anymore, once the watcher of any game stopped, as its changes aren't
+/// autosaved anymore. That way, supervisors notice and can restart game-saver.
+fn update_health_file(
+    state: &AppState,
+    health_file: Option<&Path>,
+    last_update: &mut Option<DateTime<Local>>,
+    now: DateTime<Local>,
+) -> Result<()> {
+    let Some(path) = health_file else {
+        return Ok(());
+    };
+    if !state.stopped_watchers.is_empty() {
+        return Ok(());
+    }
+
+    let update_due = last_update
+        .map(|last| now - last > Duration::seconds(HEALTH_FILE_INTERVAL))
+        .unwrap_or(true);
+    if update_due {
+        write_health_file(path)?;
+        *last_update = Some(now);
     }
 
     Ok(())
@@ -0,0 +1,58 @@
+use std::{
+    fs::remove_dir_all,
+    io::{stdin, stdout, Write},
+};
+
+use anyhow::{Context, Result};
+
+use game_saver::{
+    config::Config,
+    files::{directory_size, format_size, orphaned_backup_directories},
+};
+
+/// List all backup directories of games that are no longer configured and delete them,
+/// once the user confirmed it. With `yes`, they're deleted without asking.
+pub fn clean_orphaned_directories(config: &Config, yes: bool) -> Result<()> {
+    let orphans = orphaned_backup_directories(config)?;
+    if orphans.is_empty() {
+        println!("No orphaned backup directories found");
+        return Ok(());
+    }
+
+    let mut total = 0;
+    println!("Backup directories without a configured game:");
+    for orphan in &orphans {
+        let size = directory_size(orphan)?;
+        total += size;
+        println!("  {orphan:?} ({})", format_size(size));
+    }
+
+    if !yes && !confirm(&format!("Delete them and free {}?", format_size(total)))? {
+        println!("Nothing has been deleted");
+        return Ok(());
+    }
+
+    for orphan in &orphans {
+        remove_dir_all(orphan).context(format!("Failed to remove {orphan:?}"))?;
+    }
+    println!(
+        "Deleted {} directories and freed {}",
+        orphans.len(),
+        format_size(total)
+    );
+
+    Ok(())
+}
+
+/// Ask a yes/no question on the commandline. Anything but `y` or `yes` is a no.
+fn confirm(question: &str) -> Result<bool> {
+    print!("{question} [y/N] ");
+    stdout().flush().context("Failed to write to stdout")?;
+
+    let mut answer = String::new();
+    stdin()
+        .read_line(&mut answer)
+        .context("Failed to read answer")?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
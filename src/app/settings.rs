@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use ratatui::widgets::ListState;
+
+use game_saver::config::Config;
+
+use crate::app::helper::list::{Navigate, StatefulList};
+
+/// The options that can be changed on the settings screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    BackupDirectory,
+    Autosaves,
+    AutosaveTimeout,
+    IgnoredFiles,
+}
+
+impl Setting {
+    /// All settings in the order they're listed on the settings screen.
+    /// Global settings come first, followed by the ones of the selected game.
+    pub const ALL: [Setting; 4] = [
+        Setting::BackupDirectory,
+        Setting::Autosaves,
+        Setting::AutosaveTimeout,
+        Setting::IgnoredFiles,
+    ];
+
+    /// The key of the setting in the config file.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Setting::BackupDirectory => "backup_directory",
+            Setting::Autosaves => "autosaves",
+            Setting::AutosaveTimeout => "autosave_timeout",
+            Setting::IgnoredFiles => "ignored_files",
+        }
+    }
+
+    /// A human readable description of the setting.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Setting::BackupDirectory => "Backup directory",
+            Setting::Autosaves => "Autosaves to keep",
+            Setting::AutosaveTimeout => "Autosave cooldown in seconds",
+            Setting::IgnoredFiles => "Ignored files, comma separated",
+        }
+    }
+
+    /// Whether the setting applies to all games.
+    pub fn is_global(&self) -> bool {
+        matches!(self, Setting::BackupDirectory)
+    }
+
+    /// The current value of the setting, as it's shown and edited on the settings screen.
+    pub fn value(&self, config: &Config, game: &str) -> Result<String> {
+        let game_config = config.game(game)?;
+        Ok(match self {
+            Setting::BackupDirectory => config.backup_directory.clone(),
+            Setting::Autosaves => game_config.autosaves.to_string(),
+            Setting::AutosaveTimeout => game_config.autosave_timeout.to_string(),
+            Setting::IgnoredFiles => game_config.ignored_files.join(", "),
+        })
+    }
+
+    /// Parse the text that has been entered for this setting and apply it to the config.
+    ///
+    /// Autosaves cannot be disabled or enabled from here, as that also starts or stops the
+    /// watchers of a game. That's done in the config file.
+    pub fn apply(&self, config: &mut Config, game: &str, input: &str) -> Result<()> {
+        let input = input.trim();
+        let Some(game_config) = config.games.get_mut(game) else {
+            bail!("Couldn't find game {game} in config");
+        };
+        match self {
+            Setting::BackupDirectory => config.backup_directory = input.to_string(),
+            Setting::Autosaves => {
+                let autosaves: usize = input.parse().context("Has to be a number")?;
+                if !game_config.has_autosaves() {
+                    bail!("Autosaves are disabled, enable them in the config file");
+                }
+                if autosaves == 0 {
+                    bail!("Keeps at least one autosave, disable autosaves in the config file");
+                }
+                game_config.autosaves = autosaves;
+            }
+            Setting::AutosaveTimeout => {
+                game_config.autosave_timeout = input.parse().context("Has to be a number")?;
+            }
+            Setting::IgnoredFiles => game_config.ignored_files = split_globs(input),
+        }
+
+        Ok(())
+    }
+
+    /// Write the value of this setting from `config` to the config file at `path`.
+    pub fn save(&self, path: &Path, config: &Config, game: &str) -> Result<()> {
+        let game_config = config.game(game)?;
+        let value = match self {
+            Setting::BackupDirectory => toml_edit::value(&config.backup_directory),
+            Setting::Autosaves => {
+                let autosaves = i64::try_from(game_config.autosaves)?;
+                toml_edit::value(autosaves)
+            }
+            Setting::AutosaveTimeout => {
+                let timeout = i64::try_from(game_config.autosave_timeout)?;
+                toml_edit::value(timeout)
+            }
+            Setting::IgnoredFiles => toml_edit::value(
+                game_config
+                    .ignored_files
+                    .iter()
+                    .collect::<toml_edit::Array>(),
+            ),
+        };
+        let game = if self.is_global() { None } else { Some(game) };
+
+        Config::update_value(path, game, self.key(), value)
+    }
+}
+
+/// The list of all settings on the settings screen.
+pub struct SettingList {
+    pub state: ListState,
+    pub items: Vec<Setting>,
+}
+
+impl SettingList {
+    pub fn new() -> SettingList {
+        let mut list = SettingList {
+            state: ListState::default(),
+            items: Setting::ALL.to_vec(),
+        };
+        list.autoselect_first();
+
+        list
+    }
+
+    /// If something is selected, return the selected setting.
+    pub fn get_selected(&self) -> Option<Setting> {
+        let selected = self.state.selected()?;
+        self.items.get(selected).copied()
+    }
+}
+
+impl StatefulList for SettingList {
+    type Item = Setting;
+
+    fn get_state(&mut self) -> &mut ListState {
+        &mut self.state
+    }
+
+    fn get_items(&mut self) -> &Vec<Self::Item> {
+        &self.items
+    }
+}
+
+/// Split a comma separated list of globs.
+/// Commas inside of braces, such as in `*.{bak,tmp}`, are part of the glob.
+fn split_globs(input: &str) -> Vec<String> {
+    let mut globs = Vec::new();
+    let mut glob = String::new();
+    let mut depth = 0_usize;
+    for character in input.chars() {
+        match character {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                globs.push(std::mem::take(&mut glob));
+                continue;
+            }
+            _ => (),
+        }
+        glob.push(character);
+    }
+    globs.push(glob);
+
+    globs
+        .into_iter()
+        .map(|glob| glob.trim().to_string())
+        .filter(|glob| !glob.is_empty())
+        .collect()
+}
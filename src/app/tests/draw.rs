@@ -0,0 +1,64 @@
+use anyhow::Result;
+use ratatui::{backend::TestBackend, Terminal};
+
+use game_saver::{manually_save_game, GameConfig};
+
+use super::common::*;
+use crate::app::ui::{draw::draw_frame, state::AppState};
+
+/// Render the ui into a terminal of the given size and return its lines.
+fn render(state: &mut AppState, width: u16, height: u16) -> Result<Vec<String>> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|frame| draw_frame(frame, state))?;
+
+    let buffer = terminal.backend().buffer();
+    let lines = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+        })
+        .collect();
+    Ok(lines)
+}
+
+fn contains(lines: &[String], text: &str) -> bool {
+    lines.iter().any(|line| line.contains(text))
+}
+
+/// Terminals that are too small for the layout only show a hint with the current size.
+#[test]
+fn too_small() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let mut state = AppState::new(&fixture.config)?;
+
+    let lines = render(&mut state, 60, 10)?;
+    assert!(contains(&lines, "Terminal too small"));
+    assert!(contains(&lines, "60x10, needs at least 80x20"));
+    assert!(!contains(&lines, "Games"));
+
+    Ok(())
+}
+
+/// Large enough terminals show the game, its saves and the status line.
+#[test]
+fn layout() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 3,
+        ..Default::default()
+    });
+    manually_save_game(&fixture.config, GAME, "before boss", &no_progress)?;
+    let mut state = AppState::new(&fixture.config)?;
+
+    let lines = render(&mut state, 120, 30)?;
+    assert!(!contains(&lines, "Terminal too small"));
+    assert!(contains(&lines, "Games"));
+    assert!(contains(&lines, GAME));
+    assert!(contains(&lines, "Autosaves (keeps 3)"));
+    assert!(contains(&lines, "before boss"));
+    assert!(contains(&lines, "Event log"));
+    assert!(lines[29].starts_with(&format!(" {GAME} |")));
+    assert!(lines[29].contains("Free space: "));
+
+    Ok(())
+}
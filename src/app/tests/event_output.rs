@@ -0,0 +1,32 @@
+use std::{os::unix::net::UnixListener, time::Instant};
+
+use anyhow::Result;
+use clap::Parser;
+use tempfile::TempDir;
+
+use crate::{
+    app::helper::event_output::{EventKind, EventOutput},
+    cli::CliArguments,
+};
+
+/// A listener, which doesn't read from the socket, doesn't block the event output forever.
+/// Writes fail instead, once the socket's buffer is full.
+#[test]
+fn stalled_socket_listener() -> Result<()> {
+    let dir = TempDir::new()?;
+    let socket = dir.path().join("events.sock");
+    let _listener = UnixListener::bind(&socket)?;
+    let opt =
+        CliArguments::parse_from(["game-saver", "--events-socket", &socket.to_string_lossy()]);
+    let mut output = EventOutput::from_cli(&opt)?.expect("No event output has been opened");
+
+    let start = Instant::now();
+    let message = "x".repeat(64 * 1024);
+    let result = (0..1000)
+        .map(|_| output.emit(EventKind::Error, "game", None, Some(&message)))
+        .find(|result| result.is_err());
+    assert!(result.is_some(), "The socket never filled up");
+    assert!(start.elapsed().as_secs() < 10);
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::{thread::sleep, time::Duration};
+
+use anyhow::Result;
+use chrono::Local;
+use pretty_assertions::assert_eq;
+
+use game_saver::{files::get_archive_files, GameConfig};
+
+use super::common::*;
+use crate::app::{
+    ui::{events::autosave_now, state::AppState},
+    update::handle_finished_jobs,
+};
+
+/// Wait for the worker to finish all jobs and handle their results.
+fn finish_jobs(state: &mut AppState) -> Result<()> {
+    while !state.worker.is_idle() {
+        handle_finished_jobs(state)?;
+        sleep(Duration::from_millis(10));
+    }
+
+    Ok(())
+}
+
+/// Manual autosaves cover all pending changes and start the autosave timeout, just like
+/// autosaves that have been triggered by changes.
+#[test]
+fn autosave_now_starts_timeout() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 3,
+        autosave_timeout: 60,
+        ..Default::default()
+    });
+    let mut state = AppState::new(&fixture.config)?;
+    state.engine.change_detected(GAME, Local::now());
+
+    autosave_now(&mut state)?;
+    finish_jobs(&mut state)?;
+
+    let autosaves = get_archive_files(&fixture.config.autosave_dir(GAME))?;
+    assert_eq!(autosaves.len(), 1);
+    assert!(state.engine.pending_games().is_empty());
+    assert!(state.engine.timeout_remaining(GAME, Local::now()).is_some());
+
+    Ok(())
+}
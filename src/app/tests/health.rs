@@ -0,0 +1,54 @@
+use anyhow::Result;
+use chrono::{Duration, Local};
+
+use game_saver::GameConfig;
+
+use super::common::*;
+use crate::app::{ui::state::AppState, update_health_file, HEALTH_FILE_INTERVAL};
+
+/// The health file is refreshed once per interval, while the app is running.
+#[test]
+fn health_file_refreshed_per_interval() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let state = AppState::new(&fixture.config)?;
+    let path = fixture.dir.path().join("health");
+    let mut last_update = None;
+    let start = Local::now();
+
+    update_health_file(&state, Some(&path), &mut last_update, start)?;
+    assert!(path.exists());
+    assert_eq!(last_update, Some(start));
+
+    // Nothing is written, until the interval passed.
+    std::fs::remove_file(&path)?;
+    let within_interval = start + Duration::seconds(HEALTH_FILE_INTERVAL);
+    update_health_file(&state, Some(&path), &mut last_update, within_interval)?;
+    assert!(!path.exists());
+
+    let after_interval = start + Duration::seconds(HEALTH_FILE_INTERVAL + 1);
+    update_health_file(&state, Some(&path), &mut last_update, after_interval)?;
+    assert!(path.exists());
+    assert_eq!(last_update, Some(after_interval));
+
+    Ok(())
+}
+
+/// Once the watcher of any game stopped, the health file isn't refreshed anymore.
+#[test]
+fn health_file_stale_after_watcher_stopped() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let mut state = AppState::new(&fixture.config)?;
+    let path = fixture.dir.path().join("health");
+    let mut last_update = None;
+    let start = Local::now();
+    update_health_file(&state, Some(&path), &mut last_update, start)?;
+
+    std::fs::remove_file(&path)?;
+    state.stopped_watchers.insert(GAME.to_string());
+    let after_interval = start + Duration::seconds(HEALTH_FILE_INTERVAL + 1);
+    update_health_file(&state, Some(&path), &mut last_update, after_interval)?;
+    assert!(!path.exists());
+    assert_eq!(last_update, Some(start));
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+
+use game_saver::{manually_save_game, GameConfig};
+
+use super::common::*;
+use crate::app::{helper::list::Navigate, ui::state::AppState};
+
+/// Deleting the last save of a list moves the selection to the new last save.
+/// Once the list is empty, nothing is selected anymore.
+#[test]
+fn delete_last_save() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    for name in ["first", "second", "third"] {
+        manually_save_game(&fixture.config, GAME, name, &no_progress)?;
+    }
+
+    let mut state = AppState::new(&fixture.config)?;
+    state.update_manual_saves()?;
+    state.manual_saves.last();
+    assert_eq!(state.manual_saves.state.selected(), Some(2));
+
+    while let Some(save) = state.manual_saves.get_selected() {
+        let remaining = state.manual_saves.items.len() - 1;
+        std::fs::remove_file(&save.path)?;
+        state.update_manual_saves()?;
+        state.manual_saves.focus();
+
+        assert_eq!(state.manual_saves.items.len(), remaining);
+        assert_eq!(
+            state.manual_saves.state.selected(),
+            remaining.checked_sub(1)
+        );
+    }
+    assert!(state.manual_saves.items.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+//! Tests of the terminal ui, which isn't part of the library and therefore cannot be tested from
+//! the `tests` directory. They share its fixtures.
+#[path = "../../../tests/common/mod.rs"]
+mod common;
+mod draw;
+mod event_output;
+mod events;
+mod health;
+mod list;
+mod settings;
+mod state;
+mod trash;
+mod update;
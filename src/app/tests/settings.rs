@@ -0,0 +1,42 @@
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+
+use game_saver::GameConfig;
+
+use super::common::*;
+use crate::app::{
+    settings::Setting,
+    ui::{events::change_setting, state::AppState},
+};
+
+/// Create the state of a ui, whose config has been written to a config file.
+fn state_with_config_file(fixture: &Fixture) -> Result<AppState> {
+    let config_path = fixture.dir.path().join("game_saver.toml");
+    std::fs::write(&config_path, toml::to_string(&fixture.config)?)?;
+
+    let mut state = AppState::new(&fixture.config)?;
+    state.config_path = Some(config_path);
+    Ok(state)
+}
+
+/// A changed backup directory is only used after a restart, but the settings screen shows it
+/// right away.
+#[test]
+fn change_backup_directory() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let mut state = state_with_config_file(&fixture)?;
+    let new_directory = fixture.dir.path().join("new_backups");
+    let new_directory = new_directory.to_string_lossy().to_string();
+
+    change_setting(&mut state, Setting::BackupDirectory, &new_directory)?;
+    assert_eq!(
+        state.setting_value(Setting::BackupDirectory, GAME)?,
+        new_directory
+    );
+    assert_eq!(
+        state.config.backup_directory,
+        fixture.config.backup_directory
+    );
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+
+use game_saver::{
+    autosave_game, files::init_directories, manually_save_game, GameConfig, SaveFile,
+};
+
+use super::common::*;
+use crate::app::{
+    helper::{list::Navigate, persistence::PersistedState},
+    ui::state::{has_listed_games, AppState, UiState},
+};
+
+/// A game that has been selected during the previous session, but has been removed from the
+/// config since, falls back to the first game.
+#[test]
+fn persisted_game_missing_from_config() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let persisted = PersistedState {
+        selected_game: Some("removed_game".into()),
+        ..Default::default()
+    };
+    persisted.save(&fixture.config)?;
+
+    let state = AppState::new(&fixture.config)?;
+    assert_eq!(state.get_selected_game(), GAME);
+
+    Ok(())
+}
+
+/// The selected game may vanish from the config, which must not crash the ui.
+#[test]
+fn selected_game_missing_from_config() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let mut state = AppState::new(&fixture.config)?;
+    state.config.games.clear();
+
+    assert!(!state.selected_game_has_autosave());
+    assert!(!state.selected_game_shows_autosaves());
+    state.update_autosaves()?;
+    assert!(state.autosaves.items.is_empty());
+
+    Ok(())
+}
+
+/// A game, whose savegame location existed during the previous session but has been removed
+/// since, stays selectable, so the user can be asked for its new location.
+#[test]
+fn known_location_of_single_game_removed() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let config = &fixture.config;
+    manually_save_game(config, GAME, "manual", &no_progress)?;
+    AppState::new(config)?.persist()?;
+    std::fs::remove_dir_all(fixture.savegame_location())?;
+
+    assert!(has_listed_games(config));
+    let state = AppState::new(config)?;
+    assert_eq!(state.get_selected_game(), GAME);
+    assert_eq!(state.disappeared_locations, vec![GAME.to_string()]);
+    assert_eq!(state.manual_saves.items.len(), 1);
+
+    Ok(())
+}
+
+/// Games, whose savegame location never existed, aren't listed.
+/// If that's the case for all games, the empty state is shown instead of the main view.
+#[test]
+fn all_savegame_locations_missing() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let config = &fixture.config;
+    std::fs::remove_dir_all(fixture.savegame_location())?;
+
+    assert!(!has_listed_games(config));
+    assert!(AppState::new(config).is_err());
+
+    Ok(())
+}
+
+/// Add a game without autosaves, whose savegame location contains a single file.
+fn add_game_without_autosaves(fixture: &mut Fixture, game: &str) -> Result<()> {
+    let location = fixture.dir.path().join(game);
+    write_file(&location, "save.dat", b"another game");
+    fixture.config.games.insert(
+        game.to_string(),
+        GameConfig {
+            savegame_location: location.to_string_lossy().to_string(),
+            autosaves: 0,
+            ..Default::default()
+        },
+    );
+    init_directories(&fixture.config)
+}
+
+/// Move the selection of the game list to `game` and update the save lists, like the ui does.
+fn select_game(state: &mut AppState, game: &str) -> Result<()> {
+    for _ in 0..state.games.items.len() {
+        if state.get_selected_game() == game {
+            return state.update_saves();
+        }
+        state.games.next();
+    }
+    panic!("Game {game} isn't in the game list");
+}
+
+/// Switching between games with and without autosaves always shows the saves of the selected
+/// game. The autosaves of a previously selected game must not stick around.
+#[test]
+fn switch_games_with_different_autosave_settings() -> Result<()> {
+    let mut fixture = setup(GameConfig {
+        autosaves: 3,
+        ..Default::default()
+    });
+    add_game_without_autosaves(&mut fixture, "other_game")?;
+    let config = &fixture.config;
+    let autosave = autosave_game(config, GAME, &no_progress)?;
+    let manual_save = manually_save_game(config, "other_game", "manual", &no_progress)?;
+
+    let mut state = AppState::new(config)?;
+    for _ in 0..2 {
+        select_game(&mut state, GAME)?;
+        assert!(state.selected_game_shows_autosaves());
+        assert_eq!(paths(&state.autosaves.items), vec![autosave.clone()]);
+        assert!(state.manual_saves.items.is_empty());
+
+        select_game(&mut state, "other_game")?;
+        assert!(!state.selected_game_shows_autosaves());
+        assert!(state.autosaves.items.is_empty());
+        assert_eq!(paths(&state.manual_saves.items), vec![manual_save.clone()]);
+    }
+
+    Ok(())
+}
+
+fn paths(saves: &[SaveFile]) -> Vec<PathBuf> {
+    saves.iter().map(|save| save.path.clone()).collect()
+}
+
+/// The manual saves of a game without autosaves are loaded on startup.
+#[test]
+fn manual_saves_of_game_without_autosaves() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 0,
+        ..Default::default()
+    });
+    let config = &fixture.config;
+    let first = manually_save_game(config, GAME, "first", &no_progress)?;
+    let second = manually_save_game(config, GAME, "second", &no_progress)?;
+
+    let state = AppState::new(config)?;
+    assert!(!state.selected_game_shows_autosaves());
+    assert!(state.autosaves.items.is_empty());
+    let mut loaded = paths(&state.manual_saves.items);
+    loaded.sort();
+    assert_eq!(loaded, vec![first, second]);
+
+    Ok(())
+}
+
+/// The free space is checked on startup, so the status line doesn't have to check it on each draw.
+#[test]
+fn free_space_on_startup() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let state = AppState::new(&fixture.config)?;
+    assert!(state.free_space.contains_key(GAME));
+    assert!(state.last_space_check.is_some());
+
+    Ok(())
+}
+
+/// The focused list and the selected saves are restored on the next start, even if a prompt
+/// was open on exit.
+#[test]
+fn persist_focus_and_selected_saves() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 3,
+        ..Default::default()
+    });
+    let config = &fixture.config;
+    autosave_game(config, GAME, &no_progress)?;
+    for name in ["first", "second", "third"] {
+        manually_save_game(config, GAME, name, &no_progress)?;
+    }
+
+    let mut state = AppState::new(config)?;
+    state.state = UiState::ManualSave;
+    state.manual_saves.last();
+    let selected = state.manual_saves.get_selected().unwrap().path;
+    state.push_state(UiState::Settings);
+    state.persist()?;
+
+    let state = AppState::new(config)?;
+    assert!(matches!(state.state, UiState::ManualSave));
+    assert_eq!(state.manual_saves.get_selected().unwrap().path, selected);
+
+    // Removed saves fall back to the first one.
+    std::fs::remove_file(&selected)?;
+    let state = AppState::new(config)?;
+    assert_eq!(state.manual_saves.state.selected(), Some(0));
+
+    Ok(())
+}
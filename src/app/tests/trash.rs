@@ -0,0 +1,60 @@
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+
+use game_saver::{files::get_archive_files, manually_save_game, GameConfig};
+
+use super::common::*;
+use crate::app::trash::{get_trashed_saves, restore_trashed_save, trash_save};
+
+/// Saves with the same name, which are deleted right after each other, don't overwrite each
+/// other in the trash.
+#[test]
+fn trash_same_name_twice() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let config = &fixture.config;
+    let save_dir = config.save_dir(GAME);
+    for _ in 0..2 {
+        manually_save_game(config, GAME, "same", &no_progress)?;
+        let save = get_archive_files(&save_dir)?.remove(0);
+        trash_save(config, GAME, &save)?;
+    }
+
+    let trashed = get_trashed_saves(config, GAME)?;
+    assert_eq!(trashed.len(), 2);
+    assert!(trashed.iter().all(|save| save.file_name == "same"));
+
+    restore_trashed_save(config, GAME, &trashed[0].path)?;
+    let restored = get_archive_files(&save_dir)?;
+    assert_eq!(restored.len(), 1);
+    assert_eq!(restored[0].file_name, "same");
+
+    Ok(())
+}
+
+/// Saves that have been trashed by older versions only have a precision of seconds.
+#[test]
+fn legacy_trash_names() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let config = &fixture.config;
+    write_file(
+        &config.trash_dir(GAME),
+        "2024-01-02_03-04-05_old save.tar.zst",
+        b"save",
+    );
+
+    let trashed = get_trashed_saves(config, GAME)?;
+    assert_eq!(trashed.len(), 1);
+    assert_eq!(trashed[0].file_name, "old save");
+    assert_eq!(
+        trashed[0]
+            .last_modified
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        "2024-01-02 03:04:05"
+    );
+
+    restore_trashed_save(config, GAME, &trashed[0].path)?;
+    assert!(config.save_dir(GAME).join("old save.tar.zst").exists());
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::{thread::sleep, time::Duration};
+
+use anyhow::Result;
+use chrono::Local;
+use crossbeam_channel::unbounded;
+
+use game_saver::GameConfig;
+
+use super::common::*;
+use crate::app::{
+    ui::state::AppState,
+    update::{handle_finished_jobs, handle_updates, save_games_on_interval},
+};
+
+/// Idle redraw intervals, which are too large to be represented as a duration, never trigger.
+#[test]
+fn huge_idle_redraw_interval() -> Result<()> {
+    let mut fixture = setup(GameConfig::default());
+    fixture.config.idle_redraw_seconds = u64::MAX;
+    let mut state = AppState::new(&fixture.config)?;
+    let (_sender, receiver) = unbounded();
+
+    // The free space is checked during startup, so nothing else requires a redraw.
+    assert!(!handle_updates(&mut state, &receiver)?);
+
+    Ok(())
+}
+
+/// Interval saves cover all pending changes and start the autosave timeout.
+/// Otherwise, a change-triggered autosave would follow right away.
+#[test]
+fn interval_save_starts_timeout() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 3,
+        autosave_timeout: 60,
+        autosave_interval_minutes: Some(1),
+        ..Default::default()
+    });
+    let mut state = AppState::new(&fixture.config)?;
+    let last_save = Local::now() - chrono::Duration::minutes(2);
+    state.interval_saves.insert(GAME.to_string(), last_save);
+    state.engine.change_detected(GAME, Local::now());
+
+    assert!(save_games_on_interval(&mut state)?);
+    while !state.worker.is_idle() {
+        handle_finished_jobs(&mut state)?;
+        sleep(Duration::from_millis(10));
+    }
+
+    assert!(state.engine.pending_games().is_empty());
+    assert!(state.engine.timeout_remaining(GAME, Local::now()).is_some());
+
+    Ok(())
+}
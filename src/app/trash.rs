@@ -0,0 +1,176 @@
+use std::{
+    fs::{create_dir_all, read_dir, remove_file, rename},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Duration, Local, NaiveDateTime, TimeZone};
+
+use game_saver::{
+    config::{ArchiveFormat, Config},
+    files::SaveFile,
+};
+
+/// Trashed saves are prefixed with the time of their deletion.
+/// The microseconds keep saves with the same name apart, which are deleted in quick succession.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S-%6f";
+/// The length of a formatted [TIMESTAMP_FORMAT].
+const TIMESTAMP_LENGTH: usize = 26;
+/// The format of saves that have been trashed by older versions, which only used seconds.
+const LEGACY_TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+/// The length of a formatted [LEGACY_TIMESTAMP_FORMAT].
+const LEGACY_TIMESTAMP_LENGTH: usize = 19;
+
+/// Move a save into the trash of its game instead of deleting it.
+/// Returns the path of the trashed save.
+///
+/// The directory structure of the game's backup directory is kept, so autosaves end up in
+/// `<trash>/autosaves/` and scheduled autosaves in `<trash>/scheduled/`. That way, trashed saves
+/// can be moved back to their original location.
+pub fn trash_save(config: &Config, game: &str, save: &SaveFile) -> Result<PathBuf> {
+    if !save.path.is_file() {
+        bail!("Trying to delete non-existing file {:?}", &save.path);
+    }
+
+    let relative_path = save
+        .path
+        .strip_prefix(config.save_dir(game))
+        .context(format!("{:?} isn't a save of {game}", save.path))?;
+    let file_name = relative_path
+        .file_name()
+        .ok_or_else(|| anyhow!("Failed to get file name of {:?}", save.path))?;
+
+    let mut trash_dir = config.trash_dir(game);
+    if let Some(parent) = relative_path.parent() {
+        trash_dir = trash_dir.join(parent);
+    }
+    create_dir_all(&trash_dir).context(format!("Failed to create directory {trash_dir:?}"))?;
+
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT);
+    let trashed = trash_dir.join(format!("{timestamp}_{}", file_name.to_string_lossy()));
+    // Never overwrite another trashed save.
+    if trashed.exists() {
+        bail!("{trashed:?} already exists in the trash");
+    }
+    rename(&save.path, &trashed).context(format!("Failed to move {:?} to trash", save.path))?;
+
+    Ok(trashed)
+}
+
+/// Get all trashed saves of a game.
+/// The `last_modified` date of each save is the time it has been deleted.
+/// The saves are sorted by this date in descending order.
+pub fn get_trashed_saves(config: &Config, game: &str) -> Result<Vec<SaveFile>> {
+    let mut saves = Vec::new();
+
+    let trash_dir = config.trash_dir(game);
+    for dir in [
+        trash_dir.clone(),
+        trash_dir.join("autosaves"),
+        trash_dir.join("scheduled"),
+    ] {
+        if !dir.exists() {
+            continue;
+        }
+
+        let dir_files = read_dir(&dir).context(format!("Couldn't read directory {dir:?}"))?;
+        for dir_entry in dir_files {
+            let dir_entry = dir_entry.context(format!("Couldn't get dir entry in {dir:?}"))?;
+            let path = dir_entry.path();
+            if !path.is_file() {
+                continue;
+            }
+
+            let full_name = dir_entry.file_name().to_string_lossy().into_owned();
+            let Some((deleted_at, original_name)) = split_trashed_name(&full_name) else {
+                continue;
+            };
+            let Some((file_name, format)) = ArchiveFormat::split_file_name(original_name) else {
+                continue;
+            };
+            let last_modified = match Local.from_local_datetime(&deleted_at).earliest() {
+                Some(last_modified) => last_modified,
+                None => continue,
+            };
+
+            saves.push(SaveFile {
+                file_name: file_name.to_string(),
+                path,
+                last_modified,
+                format,
+            });
+        }
+    }
+
+    saves.sort_by_key(|save| std::cmp::Reverse(save.last_modified));
+
+    Ok(saves)
+}
+
+/// Move a trashed save back to its original location.
+pub fn restore_trashed_save(config: &Config, game: &str, trashed: &Path) -> Result<()> {
+    let relative_path = trashed
+        .strip_prefix(config.trash_dir(game))
+        .context(format!("{trashed:?} isn't in the trash of {game}"))?;
+    let full_name = relative_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("Failed to get file name of {trashed:?}"))?;
+    let (_, original_name) = split_trashed_name(&full_name)
+        .ok_or_else(|| anyhow!("Trashed save {trashed:?} has an unexpected name"))?;
+
+    let mut dest_dir = config.save_dir(game);
+    if let Some(parent) = relative_path.parent() {
+        dest_dir = dest_dir.join(parent);
+    }
+    let dest = dest_dir.join(original_name);
+    if dest.exists() {
+        bail!("Cannot restore {original_name}, a save with the same name already exists");
+    }
+
+    create_dir_all(&dest_dir).context(format!("Failed to create directory {dest_dir:?}"))?;
+    rename(trashed, &dest).context(format!("Failed to move {trashed:?} back to {dest:?}"))?;
+
+    Ok(())
+}
+
+/// Permanently remove all trashed saves, that have been deleted more than
+/// `trash_retention_days` ago. Returns the amount of removed saves.
+pub fn purge_trash(config: &Config) -> Result<usize> {
+    let Some(retention) = Duration::try_days(config.trash_retention_days.try_into()?) else {
+        return Ok(0);
+    };
+
+    let mut removed = 0;
+    for game in config.games.keys() {
+        for save in get_trashed_saves(config, game)? {
+            if Local::now() - save.last_modified < retention {
+                continue;
+            }
+
+            remove_file(&save.path).context(format!("Failed to remove {:?}", save.path))?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Split the name of a trashed save into the time it has been deleted and its original name.
+/// Names with the timestamps of older versions are supported as well.
+fn split_trashed_name(name: &str) -> Option<(NaiveDateTime, &str)> {
+    [
+        (TIMESTAMP_FORMAT, TIMESTAMP_LENGTH),
+        (LEGACY_TIMESTAMP_FORMAT, LEGACY_TIMESTAMP_LENGTH),
+    ]
+    .into_iter()
+    .find_map(|(format, length)| {
+        if !name.is_char_boundary(length) {
+            return None;
+        }
+        let (timestamp, original_name) = name.split_at(length);
+        let deleted_at = NaiveDateTime::parse_from_str(timestamp, format).ok()?;
+
+        Some((deleted_at, original_name.strip_prefix('_')?))
+    })
+}
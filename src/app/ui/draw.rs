@@ -1,124 +1,689 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
 use anyhow::Result;
+use chrono::{Duration, Local};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::Text,
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap},
     Frame as TuiFrame,
 };
 
-use super::state::{AppState, PromptType, UiState};
-use crate::app::helper::terminal::Terminal;
+use game_saver::{
+    config::DEFAULT_CATEGORY,
+    files::{format_size, SaveFile},
+    saves::SaveComparison,
+    Config,
+};
+
+use super::state::{AppState, Input, InputType, PromptType, UiState};
+use crate::app::{
+    helper::{
+        list::{SaveList, SaveRow},
+        terminal::Terminal,
+    },
+    worker::Job,
+};
 
 type Frame<'backend> = TuiFrame<'backend>;
 
+/// The smallest terminal size, in which the three-pane layout can be rendered.
+const MIN_WIDTH: u16 = 80;
+const MIN_HEIGHT: u16 = 20;
+
 /// Draw the terminal ui.
-/// This function doesn't change any state. Its sole purpose is to take the current state and
-/// render the terminal ui epending on the app state.
+/// Apart from remembering where the lists have been drawn, this function doesn't change any state.
+/// Its sole purpose is to take the current state and render the terminal ui epending on the app
+/// state.
 pub fn draw_ui(terminal: &mut Terminal, state: &mut AppState) -> Result<()> {
-    terminal.draw(|frame| {
-        // Create two horizontally split chunks with 1/3 to 2/3
-        // The left chunk will be the list of games
-        // The right chunk will be used to display save games
-        let main_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)].as_ref())
-            .split(frame.area());
-
-        // Draw the list of games
-        let game_list = build_list(state.games.items.clone(), "Games", true);
-        frame.render_stateful_widget(game_list, main_chunks[0], &mut state.games.state);
-
-        let game_config = state.config.games.get(&state.get_selected_game()).unwrap();
-
-        // Split the right side into either two or three chunks
-        // - Autosave list -> Dependant on whether the selected game has autosaves enabled
-        // - Normal save list
-        // - Block that's used as input field.
-        let (autosave_chunk, manual_chunk, event_log_chunk) = if game_config.autosaves != 0 {
-            let chunks = Layout::default()
-                .constraints(
-                    [
-                        Constraint::Ratio(1, 3),
-                        Constraint::Ratio(1, 3),
-                        Constraint::Ratio(1, 3),
-                    ]
-                    .as_ref(),
-                )
-                .split(main_chunks[1]);
-
-            (Some(chunks[0]), chunks[1], chunks[2])
-        } else {
-            let chunks = Layout::default()
-                .constraints([Constraint::Ratio(2, 3), Constraint::Ratio(1, 3)].as_ref())
-                .split(main_chunks[1]);
-            (None, chunks[0], chunks[1])
+    state.last_draw = Local::now();
+    terminal.draw(|frame| draw_frame(frame, state))?;
+
+    Ok(())
+}
+
+/// Render the terminal ui for the current state into a frame.
+pub fn draw_frame(frame: &mut Frame, state: &mut AppState) {
+    // Smaller terminals would result in panes without any space for their content.
+    // The layout is drawn again, once the terminal has been resized.
+    let area = frame.area();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        draw_too_small(frame, area);
+        return;
+    }
+
+    // Reserve the last line for the status line.
+    let vertical_chunks = Layout::default()
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(frame.area());
+    draw_status_line(frame, vertical_chunks[1], state);
+
+    // Create two horizontally split chunks with 1/3 to 2/3
+    // The left chunk will be the list of games
+    // The right chunk will be used to display save games
+    let main_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)].as_ref())
+        .split(vertical_chunks[0]);
+
+    draw_game_list(frame, main_chunks[0], state);
+    state.list_rects.games = main_chunks[0];
+
+    // Split the right side into either two or three chunks
+    // - Autosave list -> Dependant on whether the selected game has autosaves enabled
+    // - Normal save list
+    // - Block that's used as input field.
+    let layout = &state.config.layout;
+    let has_autosaves = state.selected_game_shows_autosaves();
+    let (autosave_chunk, manual_chunk, event_log_chunk) = if has_autosaves {
+        let total = layout.autosaves + layout.saves + layout.event_log;
+        let chunks = Layout::default()
+            .constraints(
+                [
+                    Constraint::Ratio(layout.autosaves, total),
+                    Constraint::Ratio(layout.saves, total),
+                    Constraint::Ratio(layout.event_log, total),
+                ]
+                .as_ref(),
+            )
+            .split(main_chunks[1]);
+
+        (Some(chunks[0]), chunks[1], chunks[2])
+    } else {
+        // The manual save list takes the space of the autosave list.
+        let saves = layout.autosaves + layout.saves;
+        let total = saves + layout.event_log;
+        let chunks = Layout::default()
+            .constraints(
+                [
+                    Constraint::Ratio(saves, total),
+                    Constraint::Ratio(layout.event_log, total),
+                ]
+                .as_ref(),
+            )
+            .split(main_chunks[1]);
+        (None, chunks[0], chunks[1])
+    };
+
+    // The filtered list stays highlighted, while the filter is being typed.
+    let filtered = match (&state.state, state.previous_states.last()) {
+        (UiState::Filter, Some(previous)) => Some(previous),
+        _ => None,
+    };
+    let game = state.get_selected_game();
+    let autosaves_filtered = matches!(filtered, Some(UiState::Autosave));
+    let manual_saves_filtered = matches!(filtered, Some(UiState::ManualSave));
+    if let Some(chunk) = autosave_chunk {
+        // Draw autosave list
+        let highlight = matches!(state.state, UiState::Autosave) || autosaves_filtered;
+        let title = match state.config.games.get(&game) {
+            Some(game_config) if game_config.autosave_schedule.is_some() => format!(
+                "Autosaves (keeps {}, {} scheduled)",
+                game_config.autosaves, game_config.scheduled_autosaves
+            ),
+            Some(game_config) => format!("Autosaves (keeps {})", game_config.autosaves),
+            None => "Autosaves".to_string(),
         };
+        let scheduled_dir = state.config.scheduled_dir(&game);
+        draw_save_list(
+            frame,
+            chunk,
+            &mut state.autosaves,
+            &title,
+            "No autosaves yet, they're created once the savegame changes",
+            highlight,
+            SaveMarkers {
+                marked: &state.marked_saves,
+                live: state.last_restored.get(&game),
+                scheduled: Some(&scheduled_dir),
+            },
+        );
+        state.list_rects.autosaves = chunk;
+    }
 
-        if let Some(chunk) = autosave_chunk {
-            // Draw autosave list
-            let autosave_list = build_list(
-                state
-                    .autosaves
-                    .items
-                    .iter()
-                    .map(|save| save.file_name.clone())
-                    .collect(),
-                "Autosaves",
-                matches!(state.state, UiState::Autosave),
-            );
-            frame.render_stateful_widget(autosave_list, chunk, &mut state.autosaves.state);
-        }
-
-        // Draw manual save list
-        let manual_list = build_list(
-            state
-                .manual_saves
-                .items
-                .iter()
-                .map(|save| save.file_name.clone())
-                .collect(),
-            "Saves",
-            matches!(state.state, UiState::ManualSave),
+    // Draw manual save list
+    let highlight = matches!(state.state, UiState::ManualSave) || manual_saves_filtered;
+    draw_save_list(
+        frame,
+        manual_chunk,
+        &mut state.manual_saves,
+        "Saves",
+        "No saves yet, press 'a' to create one",
+        highlight,
+        SaveMarkers {
+            marked: &state.marked_saves,
+            live: state.last_restored.get(&game),
+            scheduled: None,
+        },
+    );
+    state.list_rects.manual_saves = manual_chunk;
+
+    // The trash is shown on top of both save lists.
+    if state.is_trash_shown() {
+        let chunk = match autosave_chunk {
+            Some(autosave_chunk) => autosave_chunk.union(manual_chunk),
+            None => manual_chunk,
+        };
+        frame.render_widget(Clear, chunk);
+        let highlight = matches!(state.state, UiState::Trash);
+        draw_save_list(
+            frame,
+            chunk,
+            &mut state.trash,
+            "Trash",
+            "The trash is empty",
+            highlight,
+            SaveMarkers {
+                marked: &HashSet::new(),
+                live: None,
+                scheduled: None,
+            },
         );
-        frame.render_stateful_widget(manual_list, manual_chunk, &mut state.manual_saves.state);
+        state.list_rects.trash = chunk;
+    }
+
+    // The settings are shown on top of both save lists as well.
+    if state.is_settings_shown() {
+        let chunk = match autosave_chunk {
+            Some(autosave_chunk) => autosave_chunk.union(manual_chunk),
+            None => manual_chunk,
+        };
+        frame.render_widget(Clear, chunk);
+        draw_settings(frame, chunk, state, &game);
+        state.list_rects.settings = chunk;
+    }
+
+    // Draw event log
+    let event_log = build_list(state.event_logs.items.clone(), "Event log", false);
+    frame.render_stateful_widget(event_log, event_log_chunk, &mut state.event_logs.state);
+
+    // Draw the input field in the middle of the screen, if we're expecting input
+    if let UiState::Input(input) = &state.state {
+        let modal = get_modal(frame);
+
+        let title = match input.input_type {
+            InputType::Relocate => format!(
+                "New savegame location of {}",
+                state.config.display_name(&input.game)
+            ),
+            InputType::Setting(setting) => setting.label().to_string(),
+            _ => "Savefile Name".to_string(),
+        };
+        let mut block = Block::default().borders(Borders::ALL).title(title);
+        // Show validation errors at the bottom of the input field.
+        if let Some(error) = &input.error {
+            block = block
+                .title_bottom(Line::from(error.clone()).style(Style::default().fg(Color::Red)));
+        }
+
+        let paragraph =
+            Paragraph::new(input_line(input, modal.width.saturating_sub(2))).block(block);
+        frame.render_widget(paragraph, modal);
+    }
+
+    // Show the progress of a running save or restore, as long as nothing else is displayed.
+    if matches!(
+        state.state,
+        UiState::Games | UiState::Autosave | UiState::ManualSave
+    ) {
+        draw_progress(frame, state);
+    }
+
+    if let UiState::Prompt(prompt_type) = &state.state {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Are you sure?");
+
+        let text = get_prompt_text(prompt_type, &state.config, &state.get_selected_game());
+        let paragraph = Paragraph::new(text).block(block);
+
+        let modal = get_modal(frame);
+        frame.render_widget(paragraph, modal);
+    }
+
+    if let UiState::Comparison {
+        first,
+        second,
+        comparison,
+    } = &state.state
+    {
+        draw_comparison(frame, first, second, comparison);
+    }
+
+    if let UiState::Message { title, message } = &state.state {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title.clone())
+            .title_bottom("Press ESC to close");
+        let paragraph = Paragraph::new(message.clone()).block(block);
+
+        let modal = get_modal(frame);
+        frame.render_widget(paragraph, modal);
+    }
+}
+
+/// Render the text of an input field with a block cursor.
+/// Text that doesn't fit into `width` is scrolled, so the cursor is always visible.
+fn input_line(input: &Input, width: u16) -> Line<'static> {
+    let characters: Vec<char> = input.input.chars().collect();
+    let skipped = (input.cursor + 1).saturating_sub(width as usize);
+    let before: String = characters[skipped..input.cursor].iter().collect();
+    let cursor = characters.get(input.cursor).copied().unwrap_or(' ');
+    let after: String = characters.iter().skip(input.cursor + 1).collect();
+
+    Line::from(vec![
+        Span::raw(before),
+        Span::styled(
+            cursor.to_string(),
+            Style::default().add_modifier(Modifier::REVERSED),
+        ),
+        Span::raw(after),
+    ])
+}
+
+/// Draw all settings with their current values.
+/// Global settings are marked, as they don't only apply to the selected game.
+fn draw_settings(frame: &mut Frame, chunk: Rect, state: &mut AppState, game: &str) {
+    let items: Vec<ListItem> = state
+        .settings
+        .items
+        .iter()
+        .map(|setting| {
+            let mut label = setting.label().to_string();
+            if setting.is_global() {
+                label.push_str(" (all games)");
+            }
+            let value = state.setting_value(*setting, game).unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{label:<40}")),
+                Span::styled(value, Style::default().fg(Color::Yellow)),
+            ]))
+        })
+        .collect();
+
+    let title = format!(
+        "Settings of {} - ENTER to edit, ESC to close",
+        state.config.display_name(game)
+    );
+    let highlight = matches!(state.state, UiState::Settings);
+    let list = style_list(items, &title, highlight);
+    frame.render_stateful_widget(list, chunk, &mut state.settings.state);
+}
 
-        // Draw event log
-        let event_log = build_list(state.event_logs.items.clone(), "Event log", false);
-        frame.render_stateful_widget(event_log, event_log_chunk, &mut state.event_logs.state);
+/// Draw the list of games.
+/// Games that're currently being saved or restored in the background are marked.
+/// Games with pending changes are marked with a dot and show when they're going to be autosaved.
+/// Otherwise, the age of the last autosave is shown.
+/// The first nine games are numbered, as they can be selected with the number keys.
+///
+/// As soon as any game has a category, the games are grouped under headers of their categories.
+fn draw_game_list(frame: &mut Frame, chunk: Rect, state: &mut AppState) {
+    let now = Local::now();
+    let show_categories = state
+        .games
+        .items
+        .iter()
+        .any(|game| state.config.category(game) != DEFAULT_CATEGORY);
 
-        // Draw the input field in the middle of the screen, if we're expecting input
-        if let UiState::Input(input) = &state.state {
-            let modal = get_modal(frame);
+    let mut items = Vec::new();
+    let mut selected = None;
+    let mut previous_category = None;
+    for (index, game) in state.games.items.iter().enumerate() {
+        let category = state.config.category(game);
+        if show_categories && previous_category != Some(category) {
+            items.push(ListItem::new(Line::styled(
+                category,
+                Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            )));
+            previous_category = Some(category);
+        }
+        if state.games.state.selected() == Some(index) {
+            selected = Some(items.len());
+        }
 
-            let paragraph = Paragraph::new(Text::from(input.input.clone())).block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title("Savefile Name"),
-            );
-            frame.render_widget(paragraph, modal);
+        let name = state.config.display_name(game);
+        let countdown = state.shown_countdowns.get(game);
+        let text = match state.worker.current_job(game) {
+            Some(Job::Restore { .. }) => format!("{name} (restoring...)"),
+            Some(Job::Verify { .. }) => format!("{name} (verifying...)"),
+            Some(Job::RestoreSummary { .. }) => format!("{name} (reading save...)"),
+            Some(_) => format!("{name} (saving...)"),
+            None => match (countdown, state.last_autosaves.get(game)) {
+                (Some(countdown), _) => format!("{name} (autosave in {countdown}s)"),
+                (None, Some(time)) => format!("{name} (autosaved {})", format_age(now - *time)),
+                (None, None) => name.to_string(),
+            },
+        };
+        // The first nine games show the number, by which they can be selected.
+        let hint = if index < 9 {
+            format!("{} ", index + 1)
+        } else {
+            "  ".to_string()
+        };
+        let mut spans = vec![Span::styled(hint, Style::default().fg(Color::DarkGray))];
+        // Pending changes are marked, as long as the autosave hasn't been started yet.
+        if countdown.is_some() && state.worker.current_job(game).is_none() {
+            spans.push(Span::styled("● ", Style::default().fg(Color::Yellow)));
         }
+        spans.push(Span::raw(text));
+        items.push(ListItem::new(Line::from(spans)));
+    }
+
+    // Headers shift the rows of the games, so the selection is mapped onto the rendered rows.
+    // The scroll offset is only used for rendering, so it's kept in the games' list state.
+    let mut list_state = ListState::default()
+        .with_selected(selected)
+        .with_offset(state.games.state.offset());
+    let game_list = style_list(items, "Games", true);
+    frame.render_stateful_widget(game_list, chunk, &mut list_state);
+    *state.games.state.offset_mut() = list_state.offset();
+}
 
-        if let UiState::Prompt(prompt_type) = &state.state {
-            let block = Block::default()
+/// Tell the user to enlarge the terminal.
+fn draw_too_small(frame: &mut Frame, area: Rect) {
+    let text = format!(
+        "Terminal too small\n{}x{}, needs at least {MIN_WIDTH}x{MIN_HEIGHT}",
+        area.width, area.height
+    );
+    // Center the two lines vertically. Narrow terminals wrap them into the lines below.
+    let offset = area.height.saturating_sub(2) / 2;
+    let hint_area = Rect {
+        y: area.y + offset,
+        height: area.height - offset,
+        ..area
+    };
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, hint_area);
+}
+
+/// Draw a progress bar for the first game that's currently being saved or restored.
+fn draw_progress(frame: &mut Frame, state: &AppState) {
+    let mut games = state.games.items.iter();
+    let Some((game, job, (processed, total))) = games.find_map(|game| {
+        let job = state.worker.current_job(game)?;
+        let progress = state.worker.progress(game)?;
+        Some((game, job, progress))
+    }) else {
+        return;
+    };
+
+    let action = match job {
+        Job::Restore { .. } => "Restoring",
+        Job::Verify { .. } => "Verifying",
+        _ => "Saving",
+    };
+    let ratio = if total == 0 {
+        0.0
+    } else {
+        (processed as f64 / total as f64).min(1.0)
+    };
+
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
                 .borders(Borders::ALL)
-                .title("Are you sure?");
+                .title(format!("{action} {}", state.config.display_name(game))),
+        )
+        .gauge_style(Style::default().fg(Color::Green))
+        .ratio(ratio)
+        .label(format!(
+            "{} / {}",
+            format_size(processed),
+            format_size(total)
+        ));
+
+    let modal = get_modal(frame);
+    frame.render_widget(gauge, modal);
+}
 
-            let text = get_prompt_text(prompt_type, state.get_selected_game());
-            let paragraph = Paragraph::new(text).block(block);
+/// Show the differences between two saves in a large modal.
+fn draw_comparison(
+    frame: &mut Frame,
+    first: &SaveFile,
+    second: &SaveFile,
+    comparison: &SaveComparison,
+) {
+    let mut lines = vec![
+        Line::from(format!(
+            "First:  {} - {} files ({})",
+            first.file_name,
+            comparison.first_files,
+            format_size(comparison.first_bytes)
+        )),
+        Line::from(format!(
+            "Second: {} - {} files ({})",
+            second.file_name,
+            comparison.second_files,
+            format_size(comparison.second_bytes)
+        )),
+    ];
 
-            let modal = get_modal(frame);
-            frame.render_widget(paragraph, modal);
+    let sections = [
+        ("Added", &comparison.added, Color::Green),
+        ("Removed", &comparison.removed, Color::Red),
+        ("Changed", &comparison.changed, Color::Yellow),
+    ];
+    for (title, files, color) in sections {
+        if files.is_empty() {
+            continue;
         }
-    })?;
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            format!("{title} ({}):", files.len()),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        for file in files {
+            lines.push(Line::styled(
+                format!("  {file}"),
+                Style::default().fg(color),
+            ));
+        }
+    }
+    if comparison.added.is_empty() && comparison.removed.is_empty() && comparison.changed.is_empty()
+    {
+        lines.push(Line::from(""));
+        lines.push(Line::from(
+            "Both saves contain the same files with the same sizes.",
+        ));
+    }
 
-    Ok(())
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Comparison")
+        .title_bottom("Press ESC to close");
+    let paragraph = Paragraph::new(lines).block(block);
+
+    // Use most of the screen, as the list of files can get long.
+    let area = frame.area();
+    let modal = Rect {
+        x: area.x + area.width / 8,
+        y: area.y + area.height / 8,
+        width: area.width * 3 / 4,
+        height: area.height * 3 / 4,
+    };
+    frame.render_widget(Clear, modal);
+    frame.render_widget(paragraph, modal);
 }
 
-fn build_list(items: Vec<String>, title: &str, highlight: bool) -> List {
+/// Show the selected game, the focused pane, the autosave state and the free space on the
+/// backup volume.
+/// The free space is highlighted, once it drops below the configured minimum and autosaves are
+/// skipped.
+fn draw_status_line(frame: &mut Frame, chunk: Rect, state: &AppState) {
+    let game = state.get_selected_game();
+    let filter_pane;
+    let pane = match &state.state {
+        UiState::Games => "Games",
+        UiState::Autosave => "Autosaves",
+        UiState::ManualSave => "Saves",
+        UiState::Trash => "Trash",
+        UiState::Settings => "Settings",
+        UiState::Input(_) => "Input",
+        UiState::Prompt(_) => "Prompt",
+        UiState::Comparison { .. } => "Comparison",
+        UiState::Message { .. } => "Message",
+        UiState::Filter => {
+            // Show the filter that's being typed with a cursor.
+            let filter = match state.previous_states.last() {
+                Some(UiState::Autosave) => &state.autosaves.filter,
+                _ => &state.manual_saves.filter,
+            };
+            filter_pane = format!("Filter: {filter}_");
+            &filter_pane
+        }
+    };
+
+    let autosave = if !state.selected_game_has_autosave() {
+        "Autosaves disabled".to_string()
+    } else if state.stopped_watchers.contains(&game) {
+        "File watcher stopped, restart game-saver".to_string()
+    } else if state.engine.is_ignoring(&game)
+        || matches!(state.worker.current_job(&game), Some(Job::Restore { .. }))
+    {
+        "Autosaves paused while restoring".to_string()
+    } else if let Some(countdown) = state.engine.countdown(&game, Local::now()) {
+        format!("Autosave in {countdown}s")
+    } else if let Some(remaining) = state.engine.timeout_remaining(&game, Local::now()) {
+        format!("Autosaves paused for {remaining}s")
+    } else {
+        "Watching for changes".to_string()
+    };
+
+    let free_space = match state.free_space.get(&game).copied() {
+        Some(available) if available < state.config.min_free_space() => Span::styled(
+            format!(
+                "Free space: {} - Autosaves are disabled, free up some space!",
+                format_size(available)
+            ),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Some(available) => Span::raw(format!("Free space: {}", format_size(available))),
+        None => Span::raw("Free space: unknown"),
+    };
+
+    let line = Line::from(vec![
+        Span::raw(format!(
+            " {} | {pane} | {autosave} | ",
+            state.config.display_name(&game)
+        )),
+        free_space,
+    ]);
+    frame.render_widget(Paragraph::new(line), chunk);
+}
+
+/// Format the time that passed since an event in a short, human readable way.
+fn format_age(age: Duration) -> String {
+    let minutes = age.num_minutes();
+    match minutes {
+        ..=0 => "just now".to_string(),
+        1..=59 => format!("{minutes}m ago"),
+        60..=1439 => format!("{}h ago", minutes / 60),
+        _ => format!("{}d ago", minutes / 1440),
+    }
+}
+
+/// Saves that're highlighted in a save list.
+struct SaveMarkers<'a> {
+    /// Saves that have been marked for batch operations.
+    marked: &'a HashSet<PathBuf>,
+    /// The save that has been restored last.
+    live: Option<&'a PathBuf>,
+    /// The directory of scheduled autosaves, which are tagged as such.
+    scheduled: Option<&'a Path>,
+}
+
+/// Draw a list of saves, which are grouped under headers by their age.
+/// Saves that have been marked for batch operations are prefixed with `[x]`, the live save is
+/// marked as `current`.
+/// Empty lists show `empty_hint` in their center instead.
+/// An active filter is shown in the title.
+fn draw_save_list(
+    frame: &mut Frame,
+    chunk: Rect,
+    list: &mut SaveList,
+    title: &str,
+    empty_hint: &str,
+    highlight: bool,
+    markers: SaveMarkers,
+) {
+    let (rows, selected_row) = list.rows(Local::now());
+    let items = rows
+        .into_iter()
+        .map(|row| match row {
+            SaveRow::Header(header) => ListItem::new(header).style(
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            ),
+            SaveRow::Save(save) => {
+                let mut spans = Vec::new();
+                if markers.marked.contains(&save.path) {
+                    spans.push(Span::raw("[x] "));
+                }
+                spans.push(Span::raw(save.file_name.clone()));
+                if markers
+                    .scheduled
+                    .is_some_and(|dir| save.path.starts_with(dir))
+                {
+                    spans.push(Span::styled(
+                        " (scheduled)",
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+                // The save that has been restored last is the one that's currently played.
+                if markers.live == Some(&save.path) {
+                    spans.push(Span::styled(
+                        " ◀ current",
+                        Style::default().fg(Color::Green),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            }
+        })
+        .collect();
+
+    let title = if list.is_filtered() {
+        format!("{title} (filter: {})", list.filter)
+    } else {
+        title.to_string()
+    };
+    list.render_state.select(selected_row);
+    let save_list = style_list(items, &title, highlight);
+    frame.render_stateful_widget(save_list, chunk, &mut list.render_state);
+
+    if list.items.is_empty() {
+        // Place the hint in the vertical middle of the list's inner area.
+        let inner = Block::default().borders(Borders::ALL).inner(chunk);
+        let hint_area = Rect {
+            y: inner.y + inner.height / 2,
+            height: inner.height.min(1),
+            ..inner
+        };
+        let empty_hint = if list.is_filtered() {
+            "No saves match the filter, press Esc to clear it"
+        } else {
+            empty_hint
+        };
+        let hint = Paragraph::new(empty_hint)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(hint, hint_area);
+    }
+}
+
+fn build_list(items: Vec<String>, title: &str, highlight: bool) -> List<'_> {
     // Create the game selection.
     let items: Vec<ListItem> = items.into_iter().map(ListItem::new).collect();
+    style_list(items, title, highlight)
+}
 
+fn style_list<'a>(items: Vec<ListItem<'a>>, title: &'a str, highlight: bool) -> List<'a> {
     // Create a List from all list items and highlight the currently selected one
     let mut list = List::new(items)
         .block(Block::default().borders(Borders::ALL).title(title))
@@ -173,12 +738,13 @@ fn get_modal(frame: &mut Frame) -> Rect {
     overlay_horizontal[1]
 }
 
-fn get_prompt_text(prompt_type: &PromptType, game: String) -> Text {
+fn get_prompt_text(prompt_type: &PromptType, config: &Config, game: &str) -> Text<'static> {
     let message = match prompt_type {
         PromptType::Delete { save } => {
             format!(
                 "Delete the savefile '{}' for game {}",
-                &save.file_name, game
+                &save.file_name,
+                config.display_name(game)
             )
         }
         PromptType::Rename { save, new_name } => {
@@ -193,6 +759,54 @@ fn get_prompt_text(prompt_type: &PromptType, game: String) -> Text {
         PromptType::CreateOverwrite { new_name, .. } => {
             format!("Do you really want to overwrite save '{}'", &new_name)
         }
+        PromptType::DeleteMarked { saves } => {
+            format!("Move {} marked saves to the trash", saves.len())
+        }
+        PromptType::PermanentDelete { save } => {
+            return Text::from(format!(
+                "Permanently delete '{}'? This cannot be undone! (y/N)",
+                &save.file_name
+            ));
+        }
+        PromptType::Restore {
+            game,
+            save,
+            summary,
+            launch,
+        } => {
+            let launch = if *launch {
+                format!(" and launch {}", config.display_name(game))
+            } else {
+                String::new()
+            };
+            match summary {
+                Some(summary) => format!("Restore '{}'{launch}? This {summary}", &save.file_name),
+                None => format!("Restore '{}'{launch}", &save.file_name),
+            }
+        }
+        PromptType::RestoreRunning {
+            game,
+            save,
+            summary,
+        } => {
+            // Restoring is blocked by default, as this may corrupt the save.
+            let summary = summary
+                .map(|summary| format!(" This {summary}."))
+                .unwrap_or_default();
+            return Text::from(format!(
+                "{} appears to be running. Restore '{}' anyway?{summary} (y/N)",
+                config.display_name(game),
+                &save.file_name
+            ));
+        }
+        PromptType::DeleteSurplus { game, saves } => {
+            let names: Vec<&str> = saves.iter().map(|save| save.file_name.as_str()).collect();
+            format!(
+                "Maximum amount of saves reached for {}. Delete the oldest: '{}'",
+                config.display_name(game),
+                names.join("', '")
+            )
+        }
     };
 
     Text::from(format!("{message} (Y/n)"))
@@ -1,16 +1,38 @@
-use std::time::Duration;
+use std::{
+    collections::HashSet,
+    mem::discriminant,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use anyhow::{bail, Result};
+use arboard::Clipboard;
 use chrono::Local;
 use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers};
+use log::debug;
+use ratatui::layout::Rect;
+
+use game_saver::{
+    config::Config,
+    files::{get_archive_files, SaveFile},
+    saves::{
+        compare_saves, delete_save, rename_save, sanitize_save_name, surplus_autosaves,
+        surplus_manual_saves, RestoreSummary,
+    },
+};
 
 use super::state::{AppState, Input, InputType, PromptType, UiState};
 use crate::app::{
     helper::{
-        list::Navigate,
+        event_output::EventKind,
+        list::{page_size, Navigate, SaveList},
+        process::{is_process_running, open_in_file_manager},
         terminal::{restore_terminal, Terminal},
     },
-    saves::{delete_save, manually_save_game, rename_save, restore_save},
+    settings::Setting,
+    trash::{restore_trashed_save, trash_save},
+    undo::{snapshot_path, UndoAction},
+    worker::Job,
 };
 
 /// This enum signals the parent function, which actions should be taken.
@@ -36,13 +58,65 @@ pub fn handle_events(terminal: &mut Terminal, state: &mut AppState) -> Result<Ev
         return Ok(EventResult::NotHandled);
     }
 
-    match read()? {
-        Event::Key(event) => handle_key(&event, terminal, state),
+    let event = read()?;
+    let result = handle_event(&event, terminal, state)?;
+    if !is_navigation_key(&event, state) {
+        return Ok(result);
+    }
+
+    // Held keys queue up repeats faster than the ui can be drawn. Applying all of them would move
+    // the selection past the intended entry, once the key is released.
+    // All queued repeats are therefore taken at once and only applied up to `key_repeat_limit`.
+    let limit = state.config.key_repeat_limit;
+    let mut repeats = 1;
+    while poll(Duration::ZERO)? {
+        let next = read()?;
+        if next != event {
+            // Any other event ends the repeats and is handled as usual.
+            return match handle_event(&next, terminal, state)? {
+                EventResult::Quit => Ok(EventResult::Quit),
+                _ => Ok(EventResult::Redraw),
+            };
+        }
+        if limit == 0 || repeats < limit {
+            handle_event(&next, terminal, state)?;
+            repeats += 1;
+        }
+    }
+
+    Ok(result)
+}
+
+fn handle_event(
+    event: &Event,
+    terminal: &mut Terminal,
+    state: &mut AppState,
+) -> Result<EventResult> {
+    match event {
+        Event::Key(event) => handle_key(event, terminal, state),
         Event::Resize(_, _) => Ok(EventResult::Redraw),
         _ => Ok(EventResult::NotHandled),
     }
 }
 
+/// Whether an event moves the selection of a list.
+/// Keys are text while typing, so they're never considered to be navigation.
+fn is_navigation_key(event: &Event, state: &AppState) -> bool {
+    if matches!(state.state, UiState::Input(_) | UiState::Filter) {
+        return false;
+    }
+
+    match event {
+        Event::Key(event) => match event.code {
+            KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown => true,
+            KeyCode::Char('j' | 'k') => true,
+            KeyCode::Char('d' | 'u') => event.modifiers == KeyModifiers::CONTROL,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 /// Handle all kinds of key events
 fn handle_key(
     event: &KeyEvent,
@@ -51,13 +125,78 @@ fn handle_key(
 ) -> Result<EventResult> {
     let current_ui_state = state.get_state();
 
+    // CTRL+C only quits from the main views.
+    // During inputs and prompts, it cancels them just like Esc, so no typed text is lost.
+    if let (
+        KeyEvent {
+            code: KeyCode::Char('c'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        },
+        UiState::Input(_)
+        | UiState::Prompt(_)
+        | UiState::Comparison { .. }
+        | UiState::Message { .. },
+    ) = (event, &current_ui_state)
+    {
+        state.pop_state()?;
+        return Ok(EventResult::Redraw);
+    }
+
+    // CTRL+r restores the latest autosave from all main views.
+    // It's handled first, as the save lists would treat it as `r` for renaming.
+    if let (
+        KeyEvent {
+            code: KeyCode::Char('r'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        },
+        UiState::Games | UiState::Autosave | UiState::ManualSave,
+    ) = (event, &current_ui_state)
+    {
+        quick_restore(state)?;
+        return Ok(EventResult::Redraw);
+    }
+
+    let result = handle_state_key(event, terminal, state, &current_ui_state)?;
+
+    // Marks only apply to the focused save list. Clear them, once the focus moves elsewhere.
+    let is_main_view = |ui_state: &UiState| {
+        matches!(
+            ui_state,
+            UiState::Games | UiState::Autosave | UiState::ManualSave
+        )
+    };
+    if is_main_view(&current_ui_state)
+        && is_main_view(&state.state)
+        && discriminant(&current_ui_state) != discriminant(&state.state)
+    {
+        state.marked_saves.clear();
+    }
+
+    Ok(result)
+}
+
+/// Dispatch a key event to the handlers of the current UI state.
+fn handle_state_key(
+    event: &KeyEvent,
+    terminal: &mut Terminal,
+    state: &mut AppState,
+    current_ui_state: &UiState,
+) -> Result<EventResult> {
     // Run through strictly state-specific handlers.
-    let mut result = match current_ui_state {
+    let mut result = match current_ui_state.clone() {
         UiState::Input(input) => return handle_input(event, state, input),
         UiState::Prompt(prompt_type) => return handle_prompt(event, state, prompt_type),
         UiState::Games => handle_game_list(event, state)?,
         UiState::Autosave => handle_autosave_list(event, state)?,
         UiState::ManualSave => handle_manual_save_list(event, state)?,
+        UiState::Trash => handle_trash(event, state)?,
+        UiState::Settings => handle_settings(event, state)?,
+        UiState::Comparison { .. } | UiState::Message { .. } => {
+            return handle_comparison(event, state)
+        }
+        UiState::Filter => return handle_filter(event, state),
     };
 
     // Return the result, if it has been handled by one of the specific handlers
@@ -82,6 +221,17 @@ fn handle_key(
 
 /// Handle input during
 fn handle_input(event: &KeyEvent, state: &mut AppState, mut input: Input) -> Result<EventResult> {
+    if let KeyEvent {
+        code: KeyCode::Char('v'),
+        modifiers: KeyModifiers::CONTROL,
+        ..
+    } = event
+    {
+        paste_into_input(state, &mut input);
+        state.state = UiState::Input(input);
+        return Ok(EventResult::Redraw);
+    }
+
     match event.code {
         KeyCode::Esc => {
             // Abort the savegame cration process
@@ -89,16 +239,71 @@ fn handle_input(event: &KeyEvent, state: &mut AppState, mut input: Input) -> Res
             return Ok(EventResult::Redraw);
         }
         KeyCode::Enter => {
+            // The new savegame location of a game isn't a save name.
+            if matches!(input.input_type, InputType::Relocate) {
+                if let Err(error) = relocate_game(state, &input) {
+                    input.error = Some(format!("{error:#}"));
+                    state.state = UiState::Input(input);
+                    return Ok(EventResult::Redraw);
+                }
+                state.pop_state()?;
+                return Ok(EventResult::Redraw);
+            }
+
+            if let InputType::Setting(setting) = input.input_type {
+                if let Err(error) = change_setting(state, setting, &input.input) {
+                    input.error = Some(format!("{error:#}"));
+                    state.state = UiState::Input(input);
+                    return Ok(EventResult::Redraw);
+                }
+                state.pop_state()?;
+                return Ok(EventResult::Redraw);
+            }
+
+            // Make sure the name can be used as a file name and show an error otherwise.
+            if let Err(error) = sanitize_save_name(&input.input) {
+                input.error = Some(error.to_string());
+                state.state = UiState::Input(input);
+                return Ok(EventResult::Redraw);
+            }
+
+            // Don't touch the saves of a game, while it's being saved or restored.
+            if state.worker.is_busy(&input.game) {
+                input.error = Some(format!(
+                    "{} is currently being saved or restored",
+                    state.config.display_name(&input.game)
+                ));
+                state.state = UiState::Input(input);
+                return Ok(EventResult::Redraw);
+            }
+
             // Create a new save.
             match input.input_type {
                 InputType::Create => {
-                    manually_save_game(&state.config, &input.game, &input.input)?;
-                    state.log(&format!(
-                        "New manual save for {} with name '{}'",
-                        &input.game, &input.input
-                    ));
+                    // Check if a save with this name already exists.
+                    // If it does, ask whether the user wants to overwrite the existing file.
+                    let format = state.config.game(&input.game)?.archive_format;
+                    if state
+                        .config
+                        .save_dir(&input.game)
+                        .join(format!("{}{}", &input.input, format.extension()))
+                        .exists()
+                    {
+                        state.push_state(UiState::Prompt(PromptType::CreateOverwrite {
+                            new_name: input.input.clone(),
+                            game: input.game.clone(),
+                        }));
+                        return Ok(EventResult::Redraw);
+                    }
+
+                    state.worker.submit(
+                        &state.config,
+                        Job::ManualSave {
+                            game: input.game.clone(),
+                            name: input.input.clone(),
+                        },
+                    )?;
                     state.pop_state()?;
-                    state.update_manual_saves()?;
                     return Ok(EventResult::Redraw);
                 }
                 InputType::Rename(save) => {
@@ -109,7 +314,7 @@ fn handle_input(event: &KeyEvent, state: &mut AppState, mut input: Input) -> Res
                         .parent()
                         .expect("Saves shouldn't be the root folder.");
                     if parent_directory
-                        .join(format!("{}.tar.zst", &input.input))
+                        .join(format!("{}{}", &input.input, save.format.extension()))
                         .exists()
                     {
                         state.push_state(UiState::Prompt(PromptType::RenameOverwrite {
@@ -126,17 +331,39 @@ fn handle_input(event: &KeyEvent, state: &mut AppState, mut input: Input) -> Res
                     }));
                     return Ok(EventResult::Redraw);
                 }
+                InputType::Relocate | InputType::Setting(_) => {
+                    unreachable!("Relocations and settings are handled above")
+                }
             }
         }
         KeyCode::Backspace => {
-            // Remove a character from the name
-            input.input.pop();
+            // Remove the character in front of the cursor
+            input.delete_backward();
+            input.error = None;
+            state.state = UiState::Input(input);
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Delete => {
+            input.delete_forward();
+            input.error = None;
             state.state = UiState::Input(input);
             return Ok(EventResult::Redraw);
         }
         KeyCode::Char(character) => {
-            // Add the character to the name
-            input.input.push(character);
+            // Add the character at the cursor
+            input.insert(character);
+            input.error = None;
+            state.state = UiState::Input(input);
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Left | KeyCode::Right | KeyCode::Home | KeyCode::End => {
+            match event.code {
+                KeyCode::Left => input.move_left(),
+                KeyCode::Right => input.move_right(),
+                KeyCode::Home => input.move_to_start(),
+                KeyCode::End => input.move_to_end(),
+                _ => (),
+            }
             state.state = UiState::Input(input);
             return Ok(EventResult::Redraw);
         }
@@ -146,6 +373,99 @@ fn handle_input(event: &KeyEvent, state: &mut AppState, mut input: Input) -> Res
     Ok(EventResult::Ignore)
 }
 
+/// Point a game, whose savegame location disappeared, to its new location.
+///
+/// The new location is written to the config file, the backups of the game are kept.
+/// The file watcher of the game is only started with the next launch of game-saver.
+fn relocate_game(state: &mut AppState, input: &Input) -> Result<()> {
+    let location = input.input.trim();
+    let mut game_config = state.config.game(&input.game)?.clone();
+    game_config.savegame_location = location.to_string();
+    let savegame_location = game_config.savegame_location();
+    if !savegame_location.exists() {
+        bail!("{savegame_location:?} doesn't exist");
+    }
+
+    let Some(config_path) = &state.config_path else {
+        bail!("The config file is unknown");
+    };
+    Config::update_savegame_location(config_path, &input.game, location)?;
+
+    state.config.games.insert(input.game.clone(), game_config);
+    state
+        .known_locations
+        .insert(input.game.clone(), location.to_string());
+    state
+        .disappeared_locations
+        .retain(|game| game != &input.game);
+    state.add_game(&input.game);
+    state.log(&format!(
+        "Changed the savegame location of {} to {savegame_location:?}. \
+        Restart game-saver to watch the new location.",
+        state.config.display_name(&input.game)
+    ));
+
+    Ok(())
+}
+
+/// Narrow down the filtered save list, while the user is typing.
+/// Enter keeps the filter, Esc removes it again.
+fn handle_filter(event: &KeyEvent, state: &mut AppState) -> Result<EventResult> {
+    let Some(list) = state.filtered_list() else {
+        state.pop_state()?;
+        return Ok(EventResult::Redraw);
+    };
+
+    match event {
+        KeyEvent {
+            code: KeyCode::Enter,
+            ..
+        } => {
+            state.pop_state()?;
+        }
+        KeyEvent {
+            code: KeyCode::Esc, ..
+        }
+        | KeyEvent {
+            code: KeyCode::Char('c'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        } => {
+            list.set_filter(String::new());
+            list.focus();
+            state.pop_state()?;
+        }
+        KeyEvent {
+            code: KeyCode::Down,
+            ..
+        } => list.next(),
+        KeyEvent {
+            code: KeyCode::Up, ..
+        } => list.previous(),
+        KeyEvent {
+            code: KeyCode::Backspace,
+            ..
+        } => {
+            let mut filter = list.filter.clone();
+            filter.pop();
+            list.set_filter(filter);
+            list.focus();
+        }
+        KeyEvent {
+            code: KeyCode::Char(character),
+            ..
+        } => {
+            let mut filter = list.filter.clone();
+            filter.push(*character);
+            list.set_filter(filter);
+            list.focus();
+        }
+        _ => return Ok(EventResult::Ignore),
+    }
+
+    Ok(EventResult::Redraw)
+}
+
 /// Handle y/n prompts and do the appropriate action, depending on the prompt type.
 fn handle_prompt(
     event: &KeyEvent,
@@ -161,7 +481,13 @@ fn handle_prompt(
         KeyCode::Char('y' | 'Y') => match prompt_type {
             PromptType::RenameOverwrite { save, new_name }
             | PromptType::Rename { save, new_name } => {
-                rename_save(&save, &new_name)?;
+                let new_path = rename_save(&save, &new_name)?;
+                // The marker of the live save follows the save.
+                for path in state.last_restored.values_mut() {
+                    if *path == save.path {
+                        *path = new_path.clone();
+                    }
+                }
                 state.update_saves()?;
                 // Double pop the state, as we had to have an input beforehand.
                 state.pop_state()?;
@@ -171,19 +497,76 @@ fn handle_prompt(
                 return Ok(EventResult::Redraw);
             }
             PromptType::CreateOverwrite { new_name, game } => {
-                manually_save_game(&state.config, &game, &new_name)?;
-                state.log(&format!(
-                    "New manual save for {} with name '{}'",
-                    &game, &new_name
-                ));
+                state.worker.submit(
+                    &state.config,
+                    Job::ManualSave {
+                        game,
+                        name: new_name,
+                    },
+                )?;
                 state.pop_state()?;
                 state.pop_state()?;
-                state.update_manual_saves()?;
                 return Ok(EventResult::Redraw);
             }
             PromptType::Delete { save } => {
+                let game = state.get_selected_game();
+                let trashed = trash_save(&state.config, &game, &save)?;
+                state.emit(EventKind::SaveDeleted, &game, Some(&save.path), None);
+                state.push_undo(UndoAction::Delete {
+                    game,
+                    trashed: vec![trashed],
+                });
+                state.log(&format!("Moved save '{}' to the trash", &save.file_name));
+                state.pop_state()?;
+                match state.state {
+                    UiState::Autosave => {
+                        state.update_autosaves()?;
+                        state.autosaves.focus();
+                    }
+                    UiState::ManualSave => {
+                        state.update_manual_saves()?;
+                        state.manual_saves.focus();
+                    }
+                    _ => bail!("Trying to delete when focus wasn't on a SaveList."),
+                }
+                return Ok(EventResult::Redraw);
+            }
+            PromptType::Restore {
+                game, save, launch, ..
+            } => {
+                state.pop_state()?;
+                if launch && !state.worker.is_busy(&game) {
+                    state.pending_launches.insert(game.clone());
+                }
+                submit_restore(state, game, save, true)?;
+                return Ok(EventResult::Redraw);
+            }
+            PromptType::RestoreRunning { game, save, .. } => {
+                state.pop_state()?;
+                submit_restore(state, game, save, true)?;
+                return Ok(EventResult::Redraw);
+            }
+            PromptType::DeleteMarked { saves } => {
+                let game = state.get_selected_game();
+                let mut trashed = Vec::new();
+                for save in saves.iter() {
+                    trashed.push(trash_save(&state.config, &game, save)?);
+                    state.emit(EventKind::SaveDeleted, &game, Some(&save.path), None);
+                }
+                state.log(&format!("Moved {} saves to the trash", saves.len()));
+                state.push_undo(UndoAction::Delete { game, trashed });
+                state.marked_saves.clear();
+                state.pop_state()?;
+                state.update_saves()?;
+                state.autosaves.focus();
+                state.manual_saves.focus();
+                return Ok(EventResult::Redraw);
+            }
+            PromptType::PermanentDelete { save } => {
                 delete_save(&save)?;
-                state.log(&format!("Deleted save '{}'", &save.file_name));
+                state.log(&format!("Permanently deleted save '{}'", &save.file_name));
+                let game = state.get_selected_game();
+                state.emit(EventKind::SaveDeleted, &game, Some(&save.path), None);
                 state.pop_state()?;
                 match state.state {
                     UiState::Autosave => {
@@ -194,10 +577,27 @@ fn handle_prompt(
                         state.update_manual_saves()?;
                         state.manual_saves.focus();
                     }
+                    UiState::Trash => {
+                        state.update_trash()?;
+                        state.trash.focus();
+                    }
                     _ => bail!("Trying to delete when focus wasn't on a SaveList."),
                 }
                 return Ok(EventResult::Redraw);
             }
+            PromptType::DeleteSurplus { game, saves } => {
+                let mut trashed = Vec::new();
+                for save in saves.iter() {
+                    trashed.push(trash_save(&state.config, &game, save)?);
+                    state.log(&format!("Moved save '{}' to the trash", &save.file_name));
+                    state.emit(EventKind::SaveDeleted, &game, Some(&save.path), None);
+                }
+                state.push_undo(UndoAction::Delete { game, trashed });
+                state.pop_state()?;
+                state.update_manual_saves()?;
+                state.manual_saves.focus();
+                return Ok(EventResult::Redraw);
+            }
         },
         _ => {}
     }
@@ -205,8 +605,455 @@ fn handle_prompt(
     Ok(EventResult::Ignore)
 }
 
+/// Mark or unmark a save for batch operations.
+fn toggle_mark(state: &mut AppState, path: PathBuf) {
+    if !state.marked_saves.remove(&path) {
+        state.marked_saves.insert(path);
+    }
+}
+
+/// Get the prompt to delete either all marked saves of a list or its selected save.
+fn delete_prompt(list: &SaveList, marked: &HashSet<PathBuf>) -> Option<PromptType> {
+    let saves: Vec<SaveFile> = list
+        .items
+        .iter()
+        .filter(|save| marked.contains(&save.path))
+        .cloned()
+        .collect();
+    if !saves.is_empty() {
+        return Some(PromptType::DeleteMarked { saves });
+    }
+
+    list.get_selected().map(|save| PromptType::Delete { save })
+}
+
+/// Ask whether a save of the currently selected game should be restored.
+/// The prompt shows how many files the restore writes and removes. As listing the save may take
+/// a while, the summary is computed by the worker and the prompt is shown once it's done.
+///
+/// If `launch` is set, the game is launched after a successful restore.
+/// Games that are already running are never launched, so this only applies to [PromptType::Restore].
+fn start_restore(state: &mut AppState, save: SaveFile, launch: bool) -> Result<()> {
+    let game = state.get_selected_game();
+    state
+        .worker
+        .submit(&state.config, Job::RestoreSummary { game, save, launch })
+}
+
+/// Show the confirmation prompt for the restore of a save, once its summary is known.
+/// The summary is missing, if the archive couldn't be listed.
+pub fn show_restore_prompt(
+    state: &mut AppState,
+    game: String,
+    save: SaveFile,
+    summary: Option<RestoreSummary>,
+    launch: bool,
+) {
+    // Restoring while the game has its save files open may corrupt the save.
+    let process_name = state
+        .config
+        .games
+        .get(&game)
+        .and_then(|game_config| game_config.process_name.as_deref());
+    let prompt = if process_name.is_some_and(is_process_running) {
+        PromptType::RestoreRunning {
+            game,
+            save,
+            summary,
+        }
+    } else {
+        PromptType::Restore {
+            game,
+            save,
+            summary,
+            launch,
+        }
+    };
+    state.push_state(UiState::Prompt(prompt));
+}
+
+/// Ask whether a save should be restored, after which the game is launched with its
+/// `launch_command`.
+fn start_restore_and_launch(state: &mut AppState, save: SaveFile) -> Result<()> {
+    let game = state.get_selected_game();
+    if state.config.game(&game)?.launch_command.is_none() {
+        let name = state.config.display_name(&game);
+        state.log(&format!("{name} doesn't have a launch_command"));
+        return Ok(());
+    }
+
+    start_restore(state, save, true)
+}
+
+/// Schedule the restore of a save, without checking whether the game is running.
+///
+/// If `take_snapshot` is set, the current savegame location is archived before the restore,
+/// so the restore can be undone.
+fn submit_restore(
+    state: &mut AppState,
+    game: String,
+    save: SaveFile,
+    take_snapshot: bool,
+) -> Result<()> {
+    if state.worker.is_busy(&game) {
+        let name = state.config.display_name(&game);
+        state.log(&format!("{name} is currently being saved or restored"));
+        return Ok(());
+    }
+
+    let snapshot = if take_snapshot {
+        let format = state.config.game(&game)?.archive_format;
+        let path = snapshot_path(&state.config, &game, format)?;
+        Some(SaveFile {
+            file_name: "snapshot before restore".into(),
+            path,
+            last_modified: Local::now(),
+            format,
+        })
+    } else {
+        None
+    };
+
+    // Ignore all changes in the savegame location, while the restore is running.
+    state.engine.ignore_changes(&game, Local::now());
+    state.worker.submit(
+        &state.config,
+        Job::Restore {
+            game,
+            save,
+            snapshot,
+        },
+    )
+}
+
+/// Undo the most recent destructive action.
+fn undo(state: &mut AppState) -> Result<()> {
+    let Some(action) = state.undo_stack.pop() else {
+        state.log("Nothing to undo");
+        return Ok(());
+    };
+
+    match action {
+        UndoAction::Delete { game, trashed } => {
+            for path in trashed.iter() {
+                if let Err(err) = restore_trashed_save(&state.config, &game, path) {
+                    state.log(&format!("Failed to undo delete: {err}"));
+                    return Ok(());
+                }
+            }
+            state.log(&format!("Restored {} deleted save(s)", trashed.len()));
+            state.update_saves()?;
+        }
+        UndoAction::Restore { game, snapshot } => {
+            let name = state.config.display_name(&game).to_string();
+            if state.worker.is_busy(&game) {
+                state.log(&format!("{name} is currently being saved or restored"));
+                state
+                    .undo_stack
+                    .push(UndoAction::Restore { game, snapshot });
+                return Ok(());
+            }
+            state.log(&format!("Undoing the last restore of {name}"));
+            submit_restore(state, game, snapshot, false)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Check all saves of the currently selected game for corruption in the background.
+fn verify_now(state: &mut AppState) -> Result<()> {
+    let game = state.get_selected_game();
+    let name = state.config.display_name(&game).to_string();
+    if state.worker.is_busy(&game) {
+        state.log(&format!("{name} is currently being saved or restored"));
+        return Ok(());
+    }
+
+    state.log(&format!("Verifying saves of {name}"));
+    state.worker.submit(&state.config, Job::Verify { game })
+}
+
+/// Change the amount of autosaves that're kept for the currently selected game by one.
+fn change_autosave_count(state: &mut AppState, increase: bool) -> Result<()> {
+    let game = state.get_selected_game();
+    let autosaves = state.config.game(&game)?.autosaves;
+    let autosaves = if increase {
+        autosaves.saturating_add(1)
+    } else {
+        autosaves.saturating_sub(1)
+    };
+
+    if let Err(err) = change_setting(state, Setting::Autosaves, &autosaves.to_string()) {
+        let name = state.config.display_name(&game).to_string();
+        state.log(&format!("{name}: {err:#}"));
+    }
+
+    Ok(())
+}
+
+/// Apply a new value of a setting to the selected game and write it to the config file.
+///
+/// Surplus autosaves are moved to the trash right away, unless the game is currently being saved
+/// or restored. They're removed with its next autosave then.
+pub fn change_setting(state: &mut AppState, setting: Setting, input: &str) -> Result<()> {
+    let game = state.get_selected_game();
+    let name = state.config.display_name(&game).to_string();
+    let mut config = state.config.clone();
+    setting.apply(&mut config, &game, input)?;
+
+    // Only reject the value, if it causes any new problems.
+    let known_errors = state.config.errors();
+    if let Some(error) = config
+        .errors()
+        .into_iter()
+        .find(|error| !known_errors.contains(error))
+    {
+        bail!(error);
+    }
+
+    let Some(config_path) = &state.config_path else {
+        bail!("The config file is unknown");
+    };
+    setting.save(config_path, &config, &game)?;
+    let value = setting.value(&config, &game)?;
+
+    match setting {
+        // The saves of all games would suddenly be missing, so the current directory is kept.
+        Setting::BackupDirectory => {
+            state.log(&format!(
+                "Changed the backup directory to {value:?}. Restart game-saver to use it, \
+                existing saves aren't moved."
+            ));
+            state.new_backup_directory = Some(value);
+            return Ok(());
+        }
+        Setting::IgnoredFiles => state.log(&format!(
+            "Changed the ignored files of {name} to '{value}'. \
+            Restart game-saver to apply them to the file watcher as well."
+        )),
+        _ => state.log(&format!("Changed {} of {name} to {value}", setting.key())),
+    }
+
+    let previous_autosaves = state.config.game(&game)?.autosaves;
+    state.config = config;
+    if state.config.game(&game)?.autosaves >= previous_autosaves {
+        return Ok(());
+    }
+    if state.worker.is_busy(&game) {
+        state.log(&format!(
+            "{name} is currently being saved or restored, surplus autosaves are removed with \
+            its next autosave"
+        ));
+        return Ok(());
+    }
+    // Lowering the count by accident shouldn't lose any saves, so they can be restored from the
+    // trash.
+    let surplus = surplus_autosaves(&state.config, &game)?;
+    for save in surplus.iter() {
+        trash_save(&state.config, &game, save)?;
+    }
+    if !surplus.is_empty() {
+        state.log(&format!(
+            "Moved {} surplus autosaves of {name} to the trash",
+            surplus.len()
+        ));
+        state.update_saves()?;
+    }
+
+    Ok(())
+}
+
+/// Create an autosave of the currently selected game, without waiting for any file changes.
+pub fn autosave_now(state: &mut AppState) -> Result<()> {
+    let game = state.get_selected_game();
+    let name = state.config.display_name(&game).to_string();
+    if !state.selected_game_has_autosave() {
+        state.log(&format!("Autosaves are disabled for {name}"));
+        return Ok(());
+    }
+    if state.worker.is_busy(&game) {
+        state.log(&format!("{name} is currently being saved or restored"));
+        return Ok(());
+    }
+
+    // This save covers all pending changes and starts the autosave timeout, just like any other
+    // autosave.
+    let autosave_timeout = state.config.game(&game)?.autosave_timeout;
+    state
+        .engine
+        .autosave_created(&game, autosave_timeout, Local::now());
+    state.log(&format!("Creating autosave for {name}"));
+    state.worker.submit(&state.config, Job::Autosave { game })
+}
+
+/// Restore the newest autosave of the currently selected game.
+/// The usual prompt is skipped, if `quick_restore_without_prompt` is set for the game.
+fn quick_restore(state: &mut AppState) -> Result<()> {
+    let game = state.get_selected_game();
+    let name = state.config.display_name(&game).to_string();
+    if !state.selected_game_has_autosave() {
+        state.log(&format!("Autosaves are disabled for {name}"));
+        return Ok(());
+    }
+
+    // The autosave list might be filtered, so the saves are read from disk.
+    // They're sorted by date, the newest save comes first.
+    let Some(save) = get_archive_files(&state.config.autosave_dir(&game))?
+        .into_iter()
+        .next()
+    else {
+        state.log(&format!("{name} doesn't have any autosaves yet"));
+        return Ok(());
+    };
+
+    if state.config.game(&game)?.quick_restore_without_prompt {
+        state.log(&format!(
+            "Restoring the latest autosave '{}'",
+            save.file_name
+        ));
+        return submit_restore(state, game, save, true);
+    }
+
+    start_restore(state, save, false)
+}
+
+/// Insert the text of the clipboard at the cursor of an input field.
+///
+/// The pasted text is put onto a single line. Save names additionally can't contain any path
+/// separators, which are removed as well.
+/// If there's no clipboard or it doesn't contain any text, this is shown as an input error.
+fn paste_into_input(state: &mut AppState, input: &mut Input) {
+    let text = match open_clipboard(state).map(|clipboard| clipboard.get_text()) {
+        Some(Ok(text)) => text,
+        Some(Err(err)) => {
+            debug!("Couldn't paste from clipboard: {err}");
+            input.error = Some("The clipboard doesn't contain any text".into());
+            return;
+        }
+        None => {
+            input.error = Some("No clipboard available".into());
+            return;
+        }
+    };
+
+    let is_path = matches!(
+        input.input_type,
+        InputType::Relocate | InputType::Setting(_)
+    );
+    let text: String = text
+        .split(['\n', '\r'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .chars()
+        .filter(|character| !character.is_control())
+        .filter(|character| is_path || *character != '/')
+        .collect();
+    input.insert_str(&text);
+    input.error = None;
+}
+
+/// Get the system clipboard, which is opened on first use.
+/// Returns `None` on systems without a clipboard, e.g. headless ones.
+fn open_clipboard(state: &mut AppState) -> Option<&mut Clipboard> {
+    if state.clipboard.is_none() {
+        state.clipboard = Clipboard::new()
+            .map_err(|err| debug!("Couldn't open clipboard: {err}"))
+            .ok();
+    }
+
+    state.clipboard.as_mut()
+}
+
+/// Copy the path of a save to the clipboard.
+/// Systems without a clipboard, e.g. headless ones, show the path in a modal instead.
+fn copy_path(state: &mut AppState, path: &Path) {
+    let text = path.to_string_lossy().into_owned();
+    let copied =
+        open_clipboard(state).is_some_and(|clipboard| match clipboard.set_text(text.clone()) {
+            Ok(()) => true,
+            Err(err) => {
+                debug!("Couldn't copy to clipboard: {err}");
+                false
+            }
+        });
+    if copied {
+        state.log("Copied path to clipboard");
+    } else {
+        state.push_state(UiState::Message {
+            title: "Path of the save".into(),
+            message: text,
+        });
+    }
+}
+
+/// Check whether a game exceeds its maximum amount of manual saves after a new save.
+/// The oldest saves are either deleted right away or the user is asked to delete them.
+pub fn handle_manual_save_limit(state: &mut AppState, game: &str) -> Result<()> {
+    let saves = surplus_manual_saves(&state.config, game)?;
+    if saves.is_empty() {
+        return Ok(());
+    }
+
+    if !state.config.game(game)?.auto_delete_manual_saves {
+        state.push_state(UiState::Prompt(PromptType::DeleteSurplus {
+            game: game.to_string(),
+            saves,
+        }));
+        return Ok(());
+    }
+
+    for save in saves.iter() {
+        trash_save(&state.config, game, save)?;
+        state.log(&format!(
+            "Moved old save '{}' to the trash",
+            &save.file_name
+        ));
+        state.emit(EventKind::SaveDeleted, game, Some(&save.path), None);
+    }
+    state.update_manual_saves()?;
+    state.manual_saves.focus();
+
+    Ok(())
+}
+
+/// Move the selection of a list by a page, if the event is one of the page navigation keys.
+/// Returns whether the event has been handled.
+fn handle_page_navigation(event: &KeyEvent, list: &mut impl Navigate, area: Rect) -> bool {
+    match event {
+        KeyEvent {
+            code: KeyCode::PageDown,
+            ..
+        }
+        | KeyEvent {
+            modifiers: KeyModifiers::CONTROL,
+            code: KeyCode::Char('d'),
+            ..
+        } => list.page_down(page_size(area)),
+        KeyEvent {
+            code: KeyCode::PageUp,
+            ..
+        }
+        | KeyEvent {
+            modifiers: KeyModifiers::CONTROL,
+            code: KeyCode::Char('u'),
+            ..
+        } => list.page_up(page_size(area)),
+        _ => return false,
+    }
+
+    true
+}
+
 /// Actions that are only possible when the game list is focused.
 fn handle_game_list(event: &KeyEvent, state: &mut AppState) -> Result<EventResult> {
+    if handle_page_navigation(event, &mut state.games, state.list_rects.games) {
+        state.update_saves()?;
+        return Ok(EventResult::Redraw);
+    }
+
     match event.code {
         KeyCode::Down | KeyCode::Char('j') => {
             state.games.next();
@@ -218,6 +1065,33 @@ fn handle_game_list(event: &KeyEvent, state: &mut AppState) -> Result<EventResul
             state.update_saves()?;
             return Ok(EventResult::Redraw);
         }
+        KeyCode::Home | KeyCode::Char('g') => {
+            state.games.first();
+            state.update_saves()?;
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            state.games.last();
+            state.update_saves()?;
+            return Ok(EventResult::Redraw);
+        }
+        // The first nine games can be selected by their number.
+        KeyCode::Char(digit @ '1'..='9') => {
+            let index = digit as usize - '1' as usize;
+            if index < state.games.items.len() {
+                state.games.state.select(Some(index));
+                state.update_saves()?;
+            }
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char('+') => {
+            change_autosave_count(state, true)?;
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char('-') => {
+            change_autosave_count(state, false)?;
+            return Ok(EventResult::Redraw);
+        }
         _ => {}
     }
 
@@ -229,7 +1103,7 @@ fn handle_game_list(event: &KeyEvent, state: &mut AppState) -> Result<EventResul
     {
         // Moving to the right moves focus to the save lists.
         // If autosaves are enabled we focus it, otherwise we fallback to manual saves.
-        if state.selected_game_has_autosave() {
+        if state.selected_game_shows_autosaves() {
             state.state = UiState::Autosave;
             state.autosaves.focus();
         } else {
@@ -242,8 +1116,161 @@ fn handle_game_list(event: &KeyEvent, state: &mut AppState) -> Result<EventResul
     Ok(EventResult::NotHandled)
 }
 
+/// Comparisons and messages are closed with any of the usual keys. All other keys are ignored.
+fn handle_comparison(event: &KeyEvent, state: &mut AppState) -> Result<EventResult> {
+    match event.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+            state.pop_state()?;
+            Ok(EventResult::Redraw)
+        }
+        _ => Ok(EventResult::Ignore),
+    }
+}
+
+/// Pick the first save of a comparison or compare the second save with the first one.
+fn handle_compare_keys(
+    event: &KeyEvent,
+    state: &mut AppState,
+    save: Option<SaveFile>,
+) -> Result<EventResult> {
+    // CTRL+c quits the program.
+    if event.modifiers.contains(KeyModifiers::CONTROL) {
+        return Ok(EventResult::NotHandled);
+    }
+    let Some(save) = save else {
+        return Ok(EventResult::NotHandled);
+    };
+    let game = state.get_selected_game();
+
+    match event.code {
+        KeyCode::Char('c') => {
+            state.log(&format!(
+                "Picked '{}' for comparison. Press 'C' on another save to compare them",
+                save.file_name
+            ));
+            state.compare_base = Some((game, save));
+        }
+        KeyCode::Char('C') => {
+            let first = match &state.compare_base {
+                Some((base_game, first)) if base_game == &game => first.clone(),
+                Some(_) => {
+                    state.log("Only saves of the same game can be compared");
+                    return Ok(EventResult::Redraw);
+                }
+                None => {
+                    state.log("Press 'c' on a save to pick it for comparison first");
+                    return Ok(EventResult::Redraw);
+                }
+            };
+            match compare_saves(&first, &save) {
+                Ok(comparison) => state.push_state(UiState::Comparison {
+                    first,
+                    second: save,
+                    comparison,
+                }),
+                Err(err) => state.log(&format!("Failed to compare saves: {err}")),
+            }
+        }
+        _ => return Ok(EventResult::NotHandled),
+    }
+
+    Ok(EventResult::Redraw)
+}
+
+/// Actions that are only possible while the trash is shown.
+fn handle_trash(event: &KeyEvent, state: &mut AppState) -> Result<EventResult> {
+    if handle_page_navigation(event, &mut state.trash, state.list_rects.trash) {
+        return Ok(EventResult::Redraw);
+    }
+
+    match event.code {
+        KeyCode::Esc | KeyCode::Char('t') => {
+            state.pop_state()?;
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            state.trash.next();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            state.trash.previous();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            state.trash.first();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            state.trash.last();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Delete | KeyCode::Char('D') => {
+            if let Some(save) = state.trash.get_selected() {
+                state.push_state(UiState::Prompt(PromptType::PermanentDelete { save }));
+                return Ok(EventResult::Redraw);
+            }
+        }
+        KeyCode::Enter => {
+            // Move the trashed save back to the save lists.
+            if let Some(save) = state.trash.get_selected() {
+                let game = state.get_selected_game();
+                match restore_trashed_save(&state.config, &game, &save.path) {
+                    Ok(()) => state.log(&format!("Restored '{}' from the trash", save.file_name)),
+                    Err(err) => state.log(&format!("Failed to restore from trash: {err}")),
+                }
+                state.update_trash()?;
+                state.update_saves()?;
+                return Ok(EventResult::Redraw);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(EventResult::NotHandled)
+}
+
+/// Actions that are only possible while the settings are shown.
+fn handle_settings(event: &KeyEvent, state: &mut AppState) -> Result<EventResult> {
+    if handle_page_navigation(event, &mut state.settings, state.list_rects.settings) {
+        return Ok(EventResult::Redraw);
+    }
+
+    match event.code {
+        KeyCode::Esc | KeyCode::Char('s') => state.pop_state()?,
+        KeyCode::Down | KeyCode::Char('j') => state.settings.next(),
+        KeyCode::Up | KeyCode::Char('k') => state.settings.previous(),
+        KeyCode::Home | KeyCode::Char('g') => state.settings.first(),
+        KeyCode::End | KeyCode::Char('G') => state.settings.last(),
+        KeyCode::Enter => {
+            // Edit the selected setting, starting with its current value.
+            let Some(setting) = state.settings.get_selected() else {
+                return Ok(EventResult::Ignore);
+            };
+            let game = state.get_selected_game();
+            let input = state.setting_value(setting, &game)?;
+            state.push_state(UiState::Input(Input::new(
+                game,
+                input,
+                InputType::Setting(setting),
+            )));
+        }
+        _ => return Ok(EventResult::NotHandled),
+    }
+
+    Ok(EventResult::Redraw)
+}
+
 /// Actions that are only possible when the autosave list is focused.
 fn handle_autosave_list(event: &KeyEvent, state: &mut AppState) -> Result<EventResult> {
+    if handle_page_navigation(event, &mut state.autosaves, state.list_rects.autosaves) {
+        return Ok(EventResult::Redraw);
+    }
+    let selected = state.autosaves.get_selected();
+    let result = handle_compare_keys(event, state, selected)?;
+    if !matches!(result, EventResult::NotHandled) {
+        return Ok(result);
+    }
+
     match event {
         KeyEvent {
             modifiers: KeyModifiers::CONTROL,
@@ -264,6 +1291,21 @@ fn handle_autosave_list(event: &KeyEvent, state: &mut AppState) -> Result<EventR
             state.state = UiState::Games;
             return Ok(EventResult::Redraw);
         }
+        KeyEvent {
+            code: KeyCode::Char('D'),
+            ..
+        }
+        | KeyEvent {
+            modifiers: KeyModifiers::SHIFT,
+            code: KeyCode::Delete,
+            ..
+        } => {
+            // Permanently delete a save, without moving it to the trash.
+            if let Some(save) = state.autosaves.get_selected() {
+                state.push_state(UiState::Prompt(PromptType::PermanentDelete { save }));
+                return Ok(EventResult::Redraw);
+            }
+        }
         _ => (),
     }
 
@@ -276,34 +1318,66 @@ fn handle_autosave_list(event: &KeyEvent, state: &mut AppState) -> Result<EventR
             state.autosaves.previous();
             return Ok(EventResult::Redraw);
         }
+        KeyCode::Home | KeyCode::Char('g') => {
+            state.autosaves.first();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            state.autosaves.last();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char('/') => {
+            state.push_state(UiState::Filter);
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Esc if state.autosaves.is_filtered() => {
+            state.autosaves.set_filter(String::new());
+            state.autosaves.focus();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char(' ') => {
+            // Mark a save for batch operations.
+            if let Some(save) = state.autosaves.get_selected() {
+                toggle_mark(state, save.path);
+                return Ok(EventResult::Redraw);
+            }
+        }
         KeyCode::Delete | KeyCode::Char('d') => {
-            // Delete a autosave
+            // Move all marked saves or the selected save to the trash.
+            if let Some(prompt) = delete_prompt(&state.autosaves, &state.marked_saves) {
+                state.push_state(UiState::Prompt(prompt));
+                return Ok(EventResult::Redraw);
+            }
+        }
+        KeyCode::Char('y') => {
+            // Copy the path of the save, e.g. to copy the backup manually.
             if let Some(save) = state.autosaves.get_selected() {
-                state.push_state(UiState::Prompt(PromptType::Delete { save }));
+                copy_path(state, &save.path);
                 return Ok(EventResult::Redraw);
             }
         }
         KeyCode::Char('r') => {
             // Rename a autosave
             if let Some(save) = state.autosaves.get_selected() {
-                state.push_state(UiState::Input(Input {
-                    game: state.get_selected_game(),
-                    input: save.file_name.clone(),
-                    input_type: InputType::Rename(save),
-                }));
+                state.push_state(UiState::Input(Input::new(
+                    state.get_selected_game(),
+                    save.file_name.clone(),
+                    InputType::Rename(save),
+                )));
                 return Ok(EventResult::Redraw);
             }
         }
         KeyCode::Enter => {
             // Restore a autosave game.
             if let Some(save) = state.autosaves.get_selected() {
-                let game = state.get_selected_game();
-                restore_save(&state.config, &game, &save)?;
-                state.ignore_changes.insert(game.clone(), Local::now());
-                state.log(&format!(
-                    "Restored savefile {} for {}",
-                    save.file_name, &game
-                ));
+                start_restore(state, save, false)?;
+                return Ok(EventResult::Redraw);
+            }
+        }
+        KeyCode::Char('L') => {
+            // Restore the save and launch the game afterwards.
+            if let Some(save) = state.autosaves.get_selected() {
+                start_restore_and_launch(state, save)?;
                 return Ok(EventResult::Redraw);
             }
         }
@@ -314,19 +1388,30 @@ fn handle_autosave_list(event: &KeyEvent, state: &mut AppState) -> Result<EventR
 
 /// Actions that are only possible when the manual save list is focused.
 fn handle_manual_save_list(event: &KeyEvent, state: &mut AppState) -> Result<EventResult> {
+    if handle_page_navigation(
+        event,
+        &mut state.manual_saves,
+        state.list_rects.manual_saves,
+    ) {
+        return Ok(EventResult::Redraw);
+    }
+    let selected = state.manual_saves.get_selected();
+    let result = handle_compare_keys(event, state, selected)?;
+    if !matches!(result, EventResult::NotHandled) {
+        return Ok(result);
+    }
+
     match event {
         KeyEvent {
             modifiers: KeyModifiers::CONTROL,
             code: KeyCode::Down | KeyCode::Up | KeyCode::Char('j' | 'k'),
             ..
-        } => {
+        } if state.selected_game_shows_autosaves() => {
             // Moving up down while focus is on the manual save list should switch focus
             // to the autosave list. Only do this if autosaves are enabled.
-            if state.selected_game_has_autosave() {
-                state.state = UiState::Autosave;
-                state.autosaves.focus();
-                return Ok(EventResult::Redraw);
-            }
+            state.state = UiState::Autosave;
+            state.autosaves.focus();
+            return Ok(EventResult::Redraw);
         }
         KeyEvent {
             modifiers: KeyModifiers::CONTROL,
@@ -336,6 +1421,21 @@ fn handle_manual_save_list(event: &KeyEvent, state: &mut AppState) -> Result<Eve
             state.state = UiState::Games;
             return Ok(EventResult::Redraw);
         }
+        KeyEvent {
+            code: KeyCode::Char('D'),
+            ..
+        }
+        | KeyEvent {
+            modifiers: KeyModifiers::SHIFT,
+            code: KeyCode::Delete,
+            ..
+        } => {
+            // Permanently delete a save, without moving it to the trash.
+            if let Some(save) = state.manual_saves.get_selected() {
+                state.push_state(UiState::Prompt(PromptType::PermanentDelete { save }));
+                return Ok(EventResult::Redraw);
+            }
+        }
         _ => (),
     }
 
@@ -348,34 +1448,66 @@ fn handle_manual_save_list(event: &KeyEvent, state: &mut AppState) -> Result<Eve
             state.manual_saves.previous();
             return Ok(EventResult::Redraw);
         }
+        KeyCode::Home | KeyCode::Char('g') => {
+            state.manual_saves.first();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            state.manual_saves.last();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char('/') => {
+            state.push_state(UiState::Filter);
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Esc if state.manual_saves.is_filtered() => {
+            state.manual_saves.set_filter(String::new());
+            state.manual_saves.focus();
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char(' ') => {
+            // Mark a save for batch operations.
+            if let Some(save) = state.manual_saves.get_selected() {
+                toggle_mark(state, save.path);
+                return Ok(EventResult::Redraw);
+            }
+        }
         KeyCode::Delete | KeyCode::Char('d') => {
-            // Delete a autosave
+            // Move all marked saves or the selected save to the trash.
+            if let Some(prompt) = delete_prompt(&state.manual_saves, &state.marked_saves) {
+                state.push_state(UiState::Prompt(prompt));
+                return Ok(EventResult::Redraw);
+            }
+        }
+        KeyCode::Char('y') => {
+            // Copy the path of the save, e.g. to copy the backup manually.
             if let Some(save) = state.manual_saves.get_selected() {
-                state.push_state(UiState::Prompt(PromptType::Delete { save }));
+                copy_path(state, &save.path);
                 return Ok(EventResult::Redraw);
             }
         }
         KeyCode::Char('r') => {
             // Rename a autosave
             if let Some(save) = state.manual_saves.get_selected() {
-                state.push_state(UiState::Input(Input {
-                    game: state.get_selected_game(),
-                    input: save.file_name.clone(),
-                    input_type: InputType::Rename(save),
-                }));
+                state.push_state(UiState::Input(Input::new(
+                    state.get_selected_game(),
+                    save.file_name.clone(),
+                    InputType::Rename(save),
+                )));
                 return Ok(EventResult::Redraw);
             }
         }
         KeyCode::Enter => {
             // Restore a autosave game.
             if let Some(save) = state.manual_saves.get_selected() {
-                let game = state.get_selected_game();
-                restore_save(&state.config, &game, &save)?;
-                state.ignore_changes.insert(game.clone(), Local::now());
-                state.log(&format!(
-                    "Restored savefile '{}' for {}",
-                    save.file_name, game
-                ));
+                start_restore(state, save, false)?;
+                return Ok(EventResult::Redraw);
+            }
+        }
+        KeyCode::Char('L') => {
+            // Restore the save and launch the game afterwards.
+            if let Some(save) = state.manual_saves.get_selected() {
+                start_restore_and_launch(state, save)?;
                 return Ok(EventResult::Redraw);
             }
         }
@@ -393,21 +1525,61 @@ fn handle_main_view(
     terminal: &mut Terminal,
     state: &mut AppState,
 ) -> Result<EventResult> {
-    //handle_global(event, terminal, state, current_ui_state);
+    if let KeyEvent {
+        code: KeyCode::Char('s'),
+        modifiers: KeyModifiers::CONTROL,
+        ..
+    } = event
+    {
+        // Create an autosave for the current game right away, regardless of any changes.
+        autosave_now(state)?;
+        return Ok(EventResult::Redraw);
+    }
+
     match event.code {
         KeyCode::Char('q') => {
-            // 'q' instantly exits the program.
+            // 'q' exits the program, running jobs are finished beforehand.
             restore_terminal(terminal)?;
             return Ok(EventResult::Quit);
         }
+        KeyCode::Char('u') => {
+            undo(state)?;
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char('o') => {
+            // Open the backup directory of the current game.
+            let save_dir = state.config.save_dir(&state.get_selected_game());
+            match open_in_file_manager(&save_dir) {
+                Ok(()) => state.log(&format!("Opened {save_dir:?}")),
+                Err(err) => state.log(&format!("Couldn't open {save_dir:?}: {err:#}")),
+            }
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char('v') => {
+            verify_now(state)?;
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char('s') => {
+            // Show the settings of the current game.
+            state.settings.focus();
+            state.push_state(UiState::Settings);
+            return Ok(EventResult::Redraw);
+        }
+        KeyCode::Char('t') => {
+            // Show the trashed saves of the current game.
+            state.update_trash()?;
+            state.trash.focus();
+            state.push_state(UiState::Trash);
+            return Ok(EventResult::Redraw);
+        }
         KeyCode::Char('a') => {
             let game = state.get_selected_game();
             // Create a new savegame for the current game.
-            state.push_state(UiState::Input(Input {
+            state.push_state(UiState::Input(Input::new(
                 game,
-                input: String::new(),
-                input_type: InputType::Create,
-            }));
+                String::new(),
+                InputType::Create,
+            )));
             return Ok(EventResult::Redraw);
         }
         _ => {}
@@ -424,7 +1596,7 @@ fn handle_exits(event: &KeyEvent, terminal: &mut Terminal) -> Result<EventResult
             modifiers: KeyModifiers::CONTROL,
             ..
         } => {
-            // Classict CTRL+C should kill the program
+            // Classic CTRL+C should kill the program, as long as we're in one of the main views.
             restore_terminal(terminal)?;
             return Ok(EventResult::Quit);
         }
@@ -433,7 +1605,7 @@ fn handle_exits(event: &KeyEvent, terminal: &mut Terminal) -> Result<EventResult
             modifiers: KeyModifiers::NONE,
             ..
         } => {
-            // 'q' instantly exits the program.
+            // 'q' exits the program, running jobs are finished beforehand.
             restore_terminal(terminal)?;
             return Ok(EventResult::Quit);
         }
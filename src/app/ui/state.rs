@@ -1,14 +1,32 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 use anyhow::{anyhow, bail, Context, Result};
+use arboard::Clipboard;
 use chrono::{DateTime, Local};
+use log::{error, info};
+use ratatui::layout::Rect;
+
+use game_saver::{
+    config::{Config, DEFAULT_CATEGORY},
+    engine::AutosaveEngine,
+    files::{available_space, get_archive_files, SaveFile},
+    saves::{RestoreSummary, SaveComparison},
+};
 
-use crate::{
-    app::helper::{
-        files::{get_archive_files, SaveFile},
+use crate::app::{
+    helper::{
+        event_output::{EventKind, EventOutput},
         list::{Navigate, SaveList, StringList},
+        notification::notify,
+        persistence::{FocusedList, PersistedState},
     },
-    config::Config,
+    settings::{Setting, SettingList},
+    trash::get_trashed_saves,
+    undo::{UndoAction, UNDO_LIMIT},
+    worker::Worker,
 };
 
 /// This indicates the current focused part of the UI.
@@ -21,13 +39,103 @@ pub enum UiState {
     Input(Input),
     /// The user is in the middle of writing something into the input field.
     Prompt(PromptType),
+    /// The user is browsing the trashed saves of the selected game.
+    Trash,
+    /// The user is browsing the settings of the selected game.
+    Settings,
+    /// The user is typing a filter for the save list of the previous state.
+    Filter,
+    /// The differences between two saves are shown.
+    Comparison {
+        first: SaveFile,
+        second: SaveFile,
+        comparison: SaveComparison,
+    },
+    /// An important message, which has to be closed by the user.
+    Message {
+        title: String,
+        message: String,
+    },
 }
 
 #[derive(Clone, Debug)]
 pub struct Input {
     pub game: String,
     pub input: String,
+    /// The position of the cursor in characters. New characters are inserted in front of it.
+    pub cursor: usize,
     pub input_type: InputType,
+    /// A validation error for the current input, which is displayed to the user.
+    pub error: Option<String>,
+}
+
+impl Input {
+    /// Create an input field, which is prefilled with `input`.
+    /// The cursor is placed behind the prefilled text.
+    pub fn new(game: String, input: String, input_type: InputType) -> Input {
+        Input {
+            game,
+            cursor: input.chars().count(),
+            input,
+            input_type,
+            error: None,
+        }
+    }
+
+    /// Insert a character at the cursor.
+    pub fn insert(&mut self, character: char) {
+        let index = self.byte_index(self.cursor);
+        self.input.insert(index, character);
+        self.cursor += 1;
+    }
+
+    /// Insert a text at the cursor and move the cursor behind it.
+    pub fn insert_str(&mut self, text: &str) {
+        let index = self.byte_index(self.cursor);
+        self.input.insert_str(index, text);
+        self.cursor += text.chars().count();
+    }
+
+    /// Remove the character in front of the cursor.
+    pub fn delete_backward(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        self.delete_forward();
+    }
+
+    /// Remove the character behind the cursor.
+    pub fn delete_forward(&mut self) {
+        let index = self.byte_index(self.cursor);
+        if index < self.input.len() {
+            self.input.remove(index);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.input.chars().count());
+    }
+
+    pub fn move_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.cursor = self.input.chars().count();
+    }
+
+    /// Get the byte index of the character at `cursor`.
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.input
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.input.len(), |(index, _)| index)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -36,10 +144,13 @@ pub enum InputType {
     Create,
     /// Rename an existing save file.
     Rename(SaveFile),
+    /// Enter the new savegame location of a game, whose location disappeared.
+    Relocate,
+    /// Enter a new value on the settings screen.
+    Setting(Setting),
 }
 
 #[derive(Clone, Debug)]
-#[allow(dead_code)]
 pub enum PromptType {
     Rename {
         save: SaveFile,
@@ -57,6 +168,46 @@ pub enum PromptType {
     Delete {
         save: SaveFile,
     },
+    /// Should all marked saves be moved to the trash?
+    DeleteMarked {
+        saves: Vec<SaveFile>,
+    },
+    /// Should a save be deleted permanently, without moving it to the trash?
+    PermanentDelete {
+        save: SaveFile,
+    },
+    /// Should the save be restored?
+    /// The summary is missing, if the archive couldn't be listed.
+    Restore {
+        game: String,
+        save: SaveFile,
+        summary: Option<RestoreSummary>,
+        /// Launch the game after a successful restore.
+        launch: bool,
+    },
+    /// The game seems to be running. Should the save be restored anyway?
+    RestoreRunning {
+        game: String,
+        save: SaveFile,
+        summary: Option<RestoreSummary>,
+    },
+    /// The maximum amount of manual saves has been reached.
+    /// Should the oldest saves be deleted?
+    DeleteSurplus {
+        game: String,
+        saves: Vec<SaveFile>,
+    },
+}
+
+/// The areas the lists have been rendered to during the last draw.
+/// The key handlers need them to know how many items fit on a page.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ListRects {
+    pub games: Rect,
+    pub autosaves: Rect,
+    pub manual_saves: Rect,
+    pub trash: Rect,
+    pub settings: Rect,
 }
 
 /// This struct holds the state for the tui-rs interface.
@@ -69,6 +220,10 @@ pub struct AppState {
     pub games: StringList,
     pub autosaves: SaveList,
     pub manual_saves: SaveList,
+    /// The trashed saves of the selected game. Only filled while the trash is shown.
+    pub trash: SaveList,
+    /// The settings of the selected game. Only shown on the settings screen.
+    pub settings: SettingList,
     /// This is a non-persisted event log, which is used to show the user performed actions.
     pub event_logs: StringList,
 
@@ -81,18 +236,72 @@ pub struct AppState {
     /// still want to get back to the correct starting state.
     pub previous_states: Vec<UiState>,
 
-    /// This map is used to store games that recently changed on disk.
-    /// We perform changes once there haven't been any changes for some time.
-    /// That's why we have to cache this state for a little while.
-    pub changes_detected: HashMap<String, DateTime<Local>>,
-    /// This map is used to temporarily ignore changes on the filesystem.
-    /// This is needed so we don't trigger saves when restoring saves.
-    /// (As the restore is a change in the filesystem that get's detected).
-    pub ignore_changes: HashMap<String, DateTime<Local>>,
-    /// This map is used to remember the last save of a game.
-    ///
-    /// We need to handle data to apply the autosave-timeout.
-    pub autosave_timeouts: HashMap<String, DateTime<Local>>,
+    /// Decides when autosaves for detected file changes are due.
+    pub engine: AutosaveEngine,
+    /// This map is used to remember the last interval autosave of a game.
+    pub interval_saves: HashMap<String, DateTime<Local>>,
+    /// The time of the next scheduled autosave of each game with an `autosave_schedule`.
+    pub scheduled_saves: HashMap<String, DateTime<Local>>,
+    /// The background worker, which creates and restores saves.
+    pub worker: Worker,
+    /// Log all file change events and how they've been handled.
+    pub debug_events: bool,
+    /// Whether the process of a game was running during the last check.
+    /// Only contains games that create autosaves on launch or exit.
+    pub running_games: HashMap<String, bool>,
+    /// The last time the game processes have been checked.
+    pub last_process_poll: Option<DateTime<Local>>,
+    /// Saves that have been marked for batch operations in the currently focused save list.
+    pub marked_saves: HashSet<PathBuf>,
+    /// Games that are launched, once their currently running restore succeeded.
+    pub pending_launches: HashSet<String>,
+    /// Destructive actions that can be undone, the most recent one being the last.
+    pub undo_stack: Vec<UndoAction>,
+    /// The autosave countdowns of all games with pending changes, as they're currently shown in
+    /// the game list and the status line.
+    pub shown_countdowns: HashMap<String, i64>,
+    /// The areas of all lists during the last draw.
+    pub list_rects: ListRects,
+    /// The time of the newest autosave of each game, which shows that autosaving works.
+    /// Games without any autosaves aren't included.
+    pub last_autosaves: HashMap<String, DateTime<Local>>,
+    /// The time of the last draw of the terminal ui.
+    pub last_draw: DateTime<Local>,
+    /// The free space on the backup volume of each game, as it's shown in the status line.
+    /// Games whose free space couldn't be determined aren't included.
+    pub free_space: HashMap<String, u64>,
+    /// The last time the free space has been checked.
+    pub last_space_check: Option<DateTime<Local>>,
+    /// The game and the save that has been picked as the first save of a comparison.
+    pub compare_base: Option<(String, SaveFile)>,
+    /// The path of the config file, which is updated when the user changes a savegame location.
+    pub config_path: Option<PathBuf>,
+    /// The backup directory that has been changed on the settings screen.
+    /// It's only used after a restart, so [Self::config] still contains the current one.
+    pub new_backup_directory: Option<String>,
+    /// The last `savegame_location` of each game that existed.
+    pub known_locations: BTreeMap<String, String>,
+    /// The save of each game that has been restored last and is therefore currently live.
+    pub last_restored: BTreeMap<String, PathBuf>,
+    /// Games whose file watcher stopped, so their changes aren't detected anymore.
+    pub stopped_watchers: HashSet<String>,
+    /// Games whose savegame location existed during a previous session, but doesn't anymore.
+    pub disappeared_locations: Vec<String>,
+    /// Structured events for external monitoring, if requested on the commandline.
+    pub event_output: Option<EventOutput>,
+    /// The system clipboard, which is opened on first use.
+    /// It's kept open, as X11 clipboards lose their content once they're closed.
+    pub clipboard: Option<Clipboard>,
+}
+
+/// Return whether at least one game of the config is shown in the game list.
+/// Games are shown, if their savegame location exists or existed during a previous session.
+pub fn has_listed_games(config: &Config) -> bool {
+    let known_locations = PersistedState::load(config).known_locations;
+    config.games.iter().any(|(name, game_config)| {
+        game_config.savegame_location().exists()
+            || known_locations.get(name) == Some(&game_config.savegame_location)
+    })
 }
 
 impl AppState {
@@ -109,21 +318,45 @@ impl AppState {
         // Get a list of all games in the config
         let mut items = Vec::new();
         let mut event_logs = Vec::new();
+        let persisted = PersistedState::load(config);
+        let mut known_locations = persisted.known_locations;
+        let mut disappeared_locations = Vec::new();
+        let mut engine = AutosaveEngine::new();
+
+        for (name, game_config) in config.games.iter() {
+            engine.set_debounce(name, game_config.autosave_debounce);
 
-        for (name, config) in config.games.iter() {
             // Make sure all savegame locations do exist.
             // Ignore all games, where this isn't the case.
-            let savegame_location = config.savegame_location();
+            let savegame_location = game_config.savegame_location();
             if !savegame_location.exists() {
-                event_logs.push(format!(
-                    "Cannot find savegame location for game {name}: {savegame_location:?}"
-                ));
+                // Warn loudly about locations that used to exist, as the game's watcher is dead.
+                // The game stays in the list, so its saves can still be browsed until the
+                // location has been updated.
+                if known_locations.get(name) == Some(&game_config.savegame_location) {
+                    event_logs.push(format!(
+                        "WARNING: The savegame location of {} disappeared: \
+                        {savegame_location:?}. Autosaves are disabled until it's updated.",
+                        config.display_name(name)
+                    ));
+                    disappeared_locations.push(name.clone());
+                    items.push(name.clone());
+                } else {
+                    event_logs.push(format!(
+                        "Cannot find savegame location for game {name}: {savegame_location:?}"
+                    ));
+                }
                 continue;
             }
 
+            known_locations.insert(name.clone(), game_config.savegame_location.clone());
             items.push(name.clone());
         }
-        items.sort();
+        if items.is_empty() {
+            bail!("None of the savegame locations in your config exists.");
+        }
+        sort_games(config, &mut items);
+        disappeared_locations.sort();
 
         let mut state = AppState {
             config: config.clone(),
@@ -132,20 +365,117 @@ impl AppState {
             games: StringList::with_items(items),
             autosaves: SaveList::with_items(Vec::new()),
             manual_saves: SaveList::with_items(Vec::new()),
+            trash: SaveList::with_items(Vec::new()),
+            settings: SettingList::new(),
             event_logs: StringList::with_items(event_logs),
-            changes_detected: HashMap::new(),
-            ignore_changes: HashMap::new(),
-            autosave_timeouts: HashMap::new(),
+            engine,
+            interval_saves: HashMap::new(),
+            scheduled_saves: HashMap::new(),
+            worker: Worker::new(),
+            debug_events: false,
+            running_games: HashMap::new(),
+            last_process_poll: None,
+            marked_saves: HashSet::new(),
+            pending_launches: HashSet::new(),
+            undo_stack: Vec::new(),
+            shown_countdowns: HashMap::new(),
+            list_rects: ListRects::default(),
+            last_autosaves: HashMap::new(),
+            last_draw: Local::now(),
+            free_space: HashMap::new(),
+            last_space_check: None,
+            compare_base: None,
+            config_path: None,
+            new_backup_directory: None,
+            known_locations,
+            last_restored: persisted.last_restored,
+            stopped_watchers: HashSet::new(),
+            disappeared_locations,
+            event_output: None,
+            clipboard: None,
         };
+        for game in state.games.items.clone() {
+            state.update_last_autosave(&game)?;
+        }
+        state.update_free_space();
+        // Select the game from the previous session, if it still exists.
+        if let Some(game) = persisted.selected_game {
+            if let Some(index) = state.games.items.iter().position(|name| name == &game) {
+                state.games.state.select(Some(index));
+            }
+        }
+
         // Load the list of saves if we selected a game.
         state.update_saves()?;
         state.update_autosaves()?;
         state.autosaves.autoselect_first();
         state.manual_saves.autoselect_first();
 
+        // Restore the selected saves and the focus of the previous session.
+        // Saves that have been removed in the meantime are ignored.
+        if let Some(path) = &persisted.selected_autosave {
+            state.autosaves.select_path(path);
+        }
+        if let Some(path) = &persisted.selected_save {
+            state.manual_saves.select_path(path);
+        }
+        state.state = match persisted.focused_list {
+            FocusedList::Autosaves if state.selected_game_shows_autosaves() => UiState::Autosave,
+            FocusedList::Autosaves | FocusedList::Saves => UiState::ManualSave,
+            FocusedList::Games => UiState::Games,
+        };
+
         Ok(state)
     }
 
+    /// Remember the current UI state for the next session.
+    pub fn persist(&self) -> Result<()> {
+        // Prompts and other screens are shown on top of the list, that had the focus before.
+        let focused_list = std::iter::once(&self.state)
+            .chain(self.previous_states.iter().rev())
+            .find_map(|state| match state {
+                UiState::Games => Some(FocusedList::Games),
+                UiState::Autosave => Some(FocusedList::Autosaves),
+                UiState::ManualSave => Some(FocusedList::Saves),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let persisted = PersistedState {
+            selected_game: self.games.get_selected(),
+            focused_list,
+            selected_autosave: self.autosaves.get_selected().map(|save| save.path),
+            selected_save: self.manual_saves.get_selected().map(|save| save.path),
+            known_locations: self.known_locations.clone(),
+            last_restored: self.last_restored.clone(),
+        };
+        persisted.save(&self.config)
+    }
+
+    /// Show a game, whose savegame location has been missing so far, in the game list.
+    /// The currently selected game stays selected.
+    pub fn add_game(&mut self, game: &str) {
+        let selected = self.games.get_selected();
+        if !self.games.items.iter().any(|name| name == game) {
+            self.games.items.push(game.to_string());
+            sort_games(&self.config, &mut self.games.items);
+        }
+        let index = selected
+            .and_then(|selected| self.games.items.iter().position(|name| name == &selected));
+        self.games.state.select(index.or(Some(0)));
+    }
+
+    /// Remember a destructive action, so it can be undone.
+    /// The oldest action is discarded, once the [UNDO_LIMIT] is exceeded.
+    pub fn push_undo(&mut self, action: UndoAction) {
+        self.undo_stack.push(action);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            let oldest = self.undo_stack.remove(0);
+            if let Err(err) = oldest.discard() {
+                error!("Failed to discard old undo action:\n{err:?}");
+            }
+        }
+    }
+
     /// We enter the next UI state nesting level.
     /// This is most likely some kind of prompt.
     pub fn push_state(&mut self, state: UiState) {
@@ -180,8 +510,24 @@ impl AppState {
     /// Return whether we have to handle autosave or not.
     pub fn selected_game_has_autosave(&self) -> bool {
         let game_name = self.get_selected_game();
-        let game_config = self.config.games.get(&game_name).unwrap();
-        game_config.has_autosaves()
+        self.config
+            .games
+            .get(&game_name)
+            .map(|game_config| game_config.has_autosaves())
+            .unwrap_or(false)
+    }
+
+    /// Whether the autosave list is shown for the selected game.
+    /// That's the case, if it has autosaves or an `autosave_schedule`, as scheduled autosaves are
+    /// listed there as well.
+    pub fn selected_game_shows_autosaves(&self) -> bool {
+        let game_name = self.get_selected_game();
+        self.config
+            .games
+            .get(&game_name)
+            .is_some_and(|game_config| {
+                game_config.has_autosaves() || game_config.autosave_schedule.is_some()
+            })
     }
 
     pub fn log(&mut self, message: &str) {
@@ -192,6 +538,41 @@ impl AppState {
             .select(Some(self.event_logs.items.len() - 1));
     }
 
+    /// Write a structured event to the event output, if there's one.
+    /// A broken event output is dropped, so the error is only logged once.
+    pub fn emit(
+        &mut self,
+        kind: EventKind,
+        game: &str,
+        path: Option<&Path>,
+        message: Option<&str>,
+    ) {
+        let Some(output) = &mut self.event_output else {
+            return;
+        };
+        if let Err(err) = output.emit(kind, game, path, message) {
+            error!("Disabled the event output:\n{err:?}");
+            self.event_output = None;
+            self.log(&format!("Disabled the event output: {err:#}"));
+        }
+    }
+
+    /// Show a desktop notification, if they're enabled.
+    pub fn notify(&self, summary: &str, body: &str) {
+        if self.config.desktop_notifications {
+            notify(summary.to_string(), body.to_string());
+        }
+    }
+
+    /// Log a message about file change events to the event log and the logger.
+    /// This only does something, if `--debug-events` is set.
+    pub fn log_event(&mut self, message: &str) {
+        if self.debug_events {
+            info!("{message}");
+            self.log(message);
+        }
+    }
+
     /// Convenience wrapper, which calls [self.update_saves] and [self.update_autosaves].
     pub fn update_saves(&mut self) -> Result<()> {
         self.update_autosaves()
@@ -200,22 +581,123 @@ impl AppState {
             .context("Failed while updating manual")
     }
 
+    /// The value of a setting of a game, as it's shown on the settings screen.
+    /// A changed backup directory is shown, even though it's only used after a restart.
+    pub fn setting_value(&self, setting: Setting, game: &str) -> Result<String> {
+        match (setting, &self.new_backup_directory) {
+            (Setting::BackupDirectory, Some(directory)) => Ok(directory.clone()),
+            _ => setting.value(&self.config, game),
+        }
+    }
+
+    /// Check the free space on the backup volume of all games.
+    /// Returns whether it changed for any game.
+    pub fn update_free_space(&mut self) -> bool {
+        let free_space: HashMap<String, u64> = self
+            .games
+            .items
+            .iter()
+            .filter_map(|game| {
+                let available = available_space(&self.config.game_backup_directory(game)).ok()?;
+                Some((game.clone(), available))
+            })
+            .collect();
+        self.last_space_check = Some(Local::now());
+        if free_space == self.free_space {
+            return false;
+        }
+        self.free_space = free_space;
+
+        true
+    }
+
     /// Update the list of saves that're currently in the autosave folder of the selected game.
+    /// Scheduled autosaves are listed together with the other autosaves.
     pub fn update_autosaves(&mut self) -> Result<()> {
         let name = self.get_selected_game();
 
-        // Return early, if autosaves are disabled for the currently selected game.
-        if !self.selected_game_has_autosave() {
+        // Clear the list, if autosaves are disabled for the currently selected game.
+        // Otherwise, the autosaves of the previously selected game would still be shown.
+        if !self.selected_game_shows_autosaves() {
+            self.autosaves.set_items(Vec::new());
+            return Ok(());
+        }
+
+        let mut saves = Vec::new();
+        for dir in [
+            self.config.autosave_dir(&name),
+            self.config.scheduled_dir(&name),
+        ] {
+            if dir.exists() {
+                saves.extend(get_archive_files(&dir)?);
+            }
+        }
+        saves.sort_by_key(|save| std::cmp::Reverse(save.last_modified));
+
+        self.autosaves.set_items(saves);
+        self.update_last_autosave(&name)
+    }
+
+    /// Remember the time of the newest autosave of a game.
+    pub fn update_last_autosave(&mut self, game: &str) -> Result<()> {
+        let has_autosaves = self
+            .config
+            .games
+            .get(game)
+            .is_some_and(|game_config| game_config.has_autosaves());
+        if !has_autosaves {
+            self.last_autosaves.remove(game);
             return Ok(());
         }
 
-        let autosave_dir = self.config.autosave_dir(&name);
-        let saves = get_archive_files(&autosave_dir)?;
+        // Saves are sorted by their modification time, the newest one comes first.
+        let saves = get_archive_files(&self.config.autosave_dir(game))?;
+        match saves.first() {
+            Some(save) => self
+                .last_autosaves
+                .insert(game.to_string(), save.last_modified),
+            None => self.last_autosaves.remove(game),
+        };
 
-        self.autosaves.items = saves;
         Ok(())
     }
 
+    /// Update the list of trashed saves of the selected game.
+    pub fn update_trash(&mut self) -> Result<()> {
+        let name = self.get_selected_game();
+        let saves = get_trashed_saves(&self.config, &name)?;
+
+        self.trash.set_items(saves);
+        Ok(())
+    }
+
+    /// The save list that's filtered, while [UiState::Filter] is active.
+    pub fn filtered_list(&mut self) -> Option<&mut SaveList> {
+        match self.previous_states.last() {
+            Some(UiState::Autosave) => Some(&mut self.autosaves),
+            Some(UiState::ManualSave) => Some(&mut self.manual_saves),
+            _ => None,
+        }
+    }
+
+    /// Whether the trash is currently shown, possibly with a prompt on top of it.
+    pub fn is_trash_shown(&self) -> bool {
+        matches!(self.state, UiState::Trash)
+            || matches!(
+                (&self.state, self.previous_states.last()),
+                (UiState::Prompt(_), Some(UiState::Trash))
+            )
+    }
+
+    /// Whether the settings are currently shown, possibly with an input on top of them.
+    pub fn is_settings_shown(&self) -> bool {
+        matches!(self.state, UiState::Settings)
+            || matches!(
+                (&self.state, self.previous_states.last()),
+                (UiState::Input(_), Some(UiState::Settings))
+            )
+    }
+
     /// Update the list of saves that're currently in the savegame folder of the selected game.
     pub fn update_manual_saves(&mut self) -> Result<()> {
         let name = self.get_selected_game();
@@ -223,7 +705,20 @@ impl AppState {
         let save_dir = self.config.save_dir(&name);
         let saves = get_archive_files(&save_dir)?;
 
-        self.manual_saves.items = saves;
+        self.manual_saves.set_items(saves);
         Ok(())
     }
 }
+
+/// Sort games by their category and name, so the games of each category are listed together.
+/// The default category comes first.
+fn sort_games(config: &Config, games: &mut [String]) {
+    games.sort_by_cached_key(|game| {
+        let category = config.category(game);
+        (
+            category != DEFAULT_CATEGORY,
+            category.to_string(),
+            game.clone(),
+        )
+    });
+}
@@ -0,0 +1,71 @@
+use std::{
+    fs::{create_dir_all, remove_dir_all, remove_file},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+
+use game_saver::{
+    config::{ArchiveFormat, Config},
+    files::SaveFile,
+};
+
+/// The maximum amount of actions that can be undone.
+/// The files of older actions are removed, once this limit is exceeded.
+pub const UNDO_LIMIT: usize = 10;
+
+/// A destructive action, that can be undone.
+#[derive(Clone, Debug)]
+pub enum UndoAction {
+    /// Saves have been moved to the trash.
+    /// Contains the paths of the trashed saves.
+    Delete { game: String, trashed: Vec<PathBuf> },
+    /// A save has been restored.
+    /// Contains the snapshot of the savegame location, that has been taken right before the
+    /// restore.
+    Restore { game: String, snapshot: SaveFile },
+}
+
+impl UndoAction {
+    /// Remove all temporary files of this action, once it can no longer be undone.
+    /// Trashed saves are kept, as they can still be restored from the trash.
+    pub fn discard(&self) -> Result<()> {
+        match self {
+            UndoAction::Delete { .. } => Ok(()),
+            UndoAction::Restore { snapshot, .. } => remove_if_exists(&snapshot.path),
+        }
+    }
+}
+
+/// Get a new path for the safety snapshot, that's taken right before restoring a save.
+pub fn snapshot_path(config: &Config, game: &str, format: ArchiveFormat) -> Result<PathBuf> {
+    let snapshot_dir = config.snapshot_dir(game);
+    create_dir_all(&snapshot_dir)
+        .context(format!("Failed to create directory {snapshot_dir:?}"))?;
+
+    let file_name = Local::now()
+        .format("before_restore_%Y-%m-%d_%H-%M-%S%.f")
+        .to_string();
+
+    Ok(snapshot_dir.join(format!("{file_name}{}", format.extension())))
+}
+
+/// Remove all files that were kept to undo actions.
+/// This is done on a clean exit, as the undo stack isn't persisted.
+pub fn clear_undo_dir(config: &Config) -> Result<()> {
+    let undo_dir = config.undo_dir();
+    if undo_dir.exists() {
+        remove_dir_all(&undo_dir).context(format!("Failed to remove {undo_dir:?}"))?;
+    }
+
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    if path.exists() {
+        remove_file(path).context(format!("Failed to remove {path:?}"))?;
+    }
+
+    Ok(())
+}
@@ -1,121 +1,667 @@
-use std::convert::TryInto;
+use std::{collections::HashMap, convert::TryInto};
 
 use anyhow::Result;
-use chrono::{Duration, Local};
+use chrono::{DateTime, Duration, Local};
 use crossbeam_channel::Receiver;
-use log::debug;
+use log::{debug, error};
 
-use super::{saves::autosave_game, ui::state::AppState};
-use crate::watcher::Update;
+use game_saver::{
+    engine::ChangeOutcome,
+    files::get_archive_files,
+    saves::{EmptySavegameLocation, LowDiskSpace},
+    InsufficientSpace,
+};
+
+use super::{
+    helper::{
+        event_output::EventKind,
+        list::Navigate,
+        process::{is_process_running, spawn_detached},
+    },
+    ui::{
+        events::{handle_manual_save_limit, show_restore_prompt},
+        state::{AppState, UiState},
+    },
+    undo::UndoAction,
+    worker::{Job, JobResult},
+};
+use crate::watcher::WatcherMessage;
+
+/// The interval in seconds in which we check whether game processes are running.
+const PROCESS_POLL_INTERVAL: i64 = 2;
+
+/// The interval in seconds in which the free space on the backup volumes is checked.
+/// It's additionally checked after each finished job.
+const SPACE_POLL_INTERVAL: i64 = 10;
 
 /// Process updates (filesystem changes) according to the current app state.
 ///
 /// If enabled, filesystem changes will trigger autosaves.
 /// Updates will be ignored during save restoration.
-pub fn handle_updates(state: &mut AppState, receiver: &Receiver<Update>) -> Result<bool> {
+pub fn handle_updates(state: &mut AppState, receiver: &Receiver<WatcherMessage>) -> Result<bool> {
     let mut draw_scheduled = false;
 
-    receive_updates(state, receiver);
+    if handle_finished_jobs(state)? {
+        draw_scheduled = true;
+        // Saves and restores change the free space.
+        state.last_space_check = None;
+    }
+
+    if receive_updates(state, receiver) {
+        draw_scheduled = true;
+    }
 
     if save_games(state)? {
         draw_scheduled = true;
     }
 
-    remove_ignored_changes(state);
-    remove_autosave_timeouts(state);
+    if save_games_on_interval(state)? {
+        draw_scheduled = true;
+    }
+
+    if save_games_on_schedule(state)? {
+        draw_scheduled = true;
+    }
+
+    if poll_game_processes(state)? {
+        draw_scheduled = true;
+    }
+
+    if poll_free_space(state) {
+        draw_scheduled = true;
+    }
+
+    state.engine.sweep(Local::now());
+
+    // Keep the countdowns in the game list and the status line ticking.
+    let now = Local::now();
+    let countdowns: HashMap<String, i64> = state
+        .engine
+        .pending_games()
+        .into_iter()
+        .filter_map(|game| {
+            let countdown = state.engine.countdown(&game, now)?;
+            Some((game, countdown))
+        })
+        .collect();
+    if countdowns != state.shown_countdowns {
+        state.shown_countdowns = countdowns;
+        draw_scheduled = true;
+    }
+
+    // Keep relative times, such as the ages of the last autosaves, up to date.
+    // This is only checked once per loop, so it doesn't cause any additional wakeups.
+    // Intervals that are too large to be represented are never reached.
+    let interval = i64::try_from(state.config.idle_redraw_seconds)
+        .ok()
+        .and_then(Duration::try_seconds);
+    if let Some(interval) = interval {
+        if !interval.is_zero() && Local::now() - state.last_draw >= interval {
+            draw_scheduled = true;
+        }
+    }
 
     Ok(draw_scheduled)
 }
 
 /// Go through all updates for changed files.
 /// If autosaves are enabled and no autosave-timeout is active schedule a save for the given game.
-pub fn receive_updates(state: &mut AppState, receiver: &Receiver<Update>) {
-    while let Ok(update) = receiver.try_recv() {
+///
+/// Returns whether a redraw is needed, as a file watcher reported an error.
+pub fn receive_updates(state: &mut AppState, receiver: &Receiver<WatcherMessage>) -> bool {
+    let mut draw_scheduled = false;
+    while let Ok(message) = receiver.try_recv() {
+        let update = match message {
+            WatcherMessage::Update(update) => update,
+            WatcherMessage::Error {
+                game_name,
+                error,
+                stopped,
+            } => {
+                handle_watcher_error(state, &game_name, &error, stopped);
+                draw_scheduled = true;
+                continue;
+            }
+        };
         debug!("Received update:\n{update:#?}");
-        let game_config = state.config.games.get(&update.game_name).unwrap();
+        let game = &update.game_name;
+        let name = state.config.display_name(game).to_string();
+        state.log_event(&format!(
+            "{name}: {} for {:?}",
+            update.kinds.join(", "),
+            update.locations
+        ));
+
+        let game_config = match state.config.games.get(game) {
+            Some(game_config) => game_config,
+            None => {
+                error!("Received update for unknown game {}", game);
+                continue;
+            }
+        };
         if !game_config.has_autosaves() {
+            state.log_event(&format!("{name}: Ignored, autosaves are disabled"));
             continue;
         }
 
-        // Don't schedule a autosave, if we just restored a save for that game.
-        if state.ignore_changes.contains_key(&update.game_name) {
+        // Don't schedule a autosave, while a save is being restored.
+        if let Some(Job::Restore { .. }) = state.worker.current_job(game) {
+            state.log_event(&format!("{name}: Ignored, a save is being restored"));
             continue;
         }
 
-        state
-            .changes_detected
-            .insert(update.game_name.clone(), update.time);
+        let message = match state.engine.change_detected(game, update.time) {
+            ChangeOutcome::Ignored => "Ignored, a save has just been restored",
+            ChangeOutcome::Postponed => "Autosave postponed, waiting for changes to settle",
+            ChangeOutcome::AfterTimeout => "Autosave scheduled after the autosave timeout",
+            ChangeOutcome::Scheduled => "Autosave scheduled",
+        };
+        state.log_event(&format!("{name}: {message}"));
     }
+
+    draw_scheduled
+}
+
+/// Show errors of the file watchers in the event log, where they're visible in the terminal ui.
+/// A watcher that has been given up on is a lot more serious, as the changes of its game aren't
+/// autosaved anymore.
+fn handle_watcher_error(state: &mut AppState, game: &str, error: &str, stopped: bool) {
+    let name = state.config.display_name(game).to_string();
+    if !stopped {
+        error!("Error in file watcher for game {game}: {error}");
+        state.log(&format!("Error in the file watcher of {name}: {error}"));
+        return;
+    }
+
+    let message = format!("The file watcher of {name} stopped");
+    state.log(&format!(
+        "WARNING: {message}: {error}. Changes aren't autosaved until game-saver is restarted."
+    ));
+    state.emit(
+        EventKind::Error,
+        game,
+        None,
+        Some(&format!("{message}: {error}")),
+    );
+    state.notify(&message, error);
+    state.stopped_watchers.insert(game.to_string());
 }
 
 /// Save all games whose save directory hasn't been touched for a few seconds.
 pub fn save_games(state: &mut AppState) -> Result<bool> {
     let mut draw_scheduled = false;
-    let watched_changes: Vec<String> = state.changes_detected.keys().cloned().collect();
 
-    for game in watched_changes.iter() {
-        // Make sure there weren't any changes for a few seconds.
-        // Otherwise we might create a backup, while the game is still writing files.
-        let time = state.changes_detected.get(game).unwrap();
-        if (Local::now() - Duration::seconds(5)).lt(time) {
+    // Only games whose changes settled and whose autosave timeout passed are due.
+    for game in state.engine.due_games(Local::now()) {
+        // Wait until any running save or restore for this game finished.
+        if state.worker.is_busy(&game) {
             continue;
         }
 
-        // Don't save, if the autosave timeout didn't finish yet.
-        if state.autosave_timeouts.contains_key(game) {
+        // Drop changes of games that no longer exist in the config.
+        let autosave_timeout = match state.config.games.get(&game) {
+            Some(game_config) => game_config.autosave_timeout,
+            None => {
+                state.log(&format!("Skipped autosave for unknown game {game}"));
+                state.engine.discard_pending(&game);
+                continue;
+            }
+        };
+
+        // We can create the autosave.
+        state.worker.submit(
+            &state.config,
+            Job::Autosave {
+                game: game.to_string(),
+            },
+        )?;
+
+        // This starts the autosave timeout and removes that update from our watchlist.
+        state
+            .engine
+            .autosave_created(&game, autosave_timeout, Local::now());
+        draw_scheduled = true;
+    }
+
+    Ok(draw_scheduled)
+}
+
+/// Create autosaves for all games, whose `autosave_interval_minutes` passed since their last
+/// interval save. This happens regardless of any file changes.
+pub fn save_games_on_interval(state: &mut AppState) -> Result<bool> {
+    let mut draw_scheduled = false;
+    let mut games: Vec<(String, u64, usize)> = state
+        .config
+        .games
+        .iter()
+        .filter(|(_, game_config)| game_config.has_autosaves())
+        .filter_map(|(game, game_config)| {
+            game_config
+                .autosave_interval_minutes
+                .map(|interval| (game.clone(), interval, game_config.autosave_timeout))
+        })
+        .collect();
+    games.sort();
+
+    for (game, interval, autosave_timeout) in games {
+        // The interval starts, once we see the game for the first time.
+        let last_save = match state.interval_saves.get(&game) {
+            Some(last_save) => *last_save,
+            None => {
+                state.interval_saves.insert(game, Local::now());
+                continue;
+            }
+        };
+
+        // Absurdly large intervals simply never pass.
+        let Some(interval) = interval.try_into().ok().and_then(Duration::try_minutes) else {
+            continue;
+        };
+        if (Local::now() - interval).lt(&last_save) {
             continue;
         }
 
-        // We can create the autosave.
-        autosave_game(&state.config, game)?;
-        state.log(&format!("Autosave created for {game}"));
-        state.update_autosaves()?;
-
-        // Set a autosave timeout, if it is specified for the current game.
-        let game_config = state.config.games.get(game).unwrap();
-        if game_config.autosave_timeout > 0 {
-            state.autosave_timeouts.insert(game.clone(), Local::now());
+        // Wait until any running save or restore for this game finished.
+        if state.worker.is_busy(&game) {
+            continue;
         }
 
-        // Schedule a redraw and remove that update from our watchlist.
-        state.changes_detected.remove(game);
+        state.worker.submit(
+            &state.config,
+            Job::Autosave {
+                game: game.to_string(),
+            },
+        )?;
+        // The interval save covers all pending changes, just like any other autosave.
+        state
+            .engine
+            .autosave_created(&game, autosave_timeout, Local::now());
+        let name = state.config.display_name(&game).to_string();
+        state.log_event(&format!("{name}: Interval autosave scheduled"));
+        state.interval_saves.insert(game, Local::now());
         draw_scheduled = true;
     }
 
     Ok(draw_scheduled)
 }
 
-/// Changes will be ignored for a short time after restoring a save file.
-/// Remove the ignore rule for file changes after a few seconds.
-/// We only have to lock this for a short amount of time, after the restore.
-pub fn remove_ignored_changes(state: &mut AppState) {
-    let ignored_duration = Duration::seconds(5);
+/// Create scheduled autosaves for all games, whose next `autosave_schedule` time has passed.
+/// This happens regardless of any file changes.
+pub fn save_games_on_schedule(state: &mut AppState) -> Result<bool> {
+    let mut draw_scheduled = false;
+    let mut games: Vec<String> = state
+        .config
+        .games
+        .iter()
+        .filter(|(_, game_config)| game_config.autosave_schedule.is_some())
+        .map(|(game, _)| game.clone())
+        .collect();
+    games.sort();
 
-    let games: Vec<String> = state.ignore_changes.keys().cloned().collect();
+    let now = Local::now();
+    for game in games {
+        // The schedule starts, once we see the game for the first time.
+        // Times that have been missed while game-saver wasn't running aren't caught up on.
+        let Some(next_save) = state.scheduled_saves.get(&game).copied() else {
+            if let Some(next_save) = next_scheduled_save(state, &game, now)? {
+                state.scheduled_saves.insert(game, next_save);
+            }
+            continue;
+        };
+        if now < next_save {
+            continue;
+        }
 
-    for game in games.iter() {
-        let time = state.ignore_changes.get(game).unwrap();
-        if (Local::now() - ignored_duration).gt(time) {
-            state.ignore_changes.remove(game);
+        // Wait until any running save or restore for this game finished.
+        if state.worker.is_busy(&game) {
+            continue;
         }
+
+        state.worker.submit(
+            &state.config,
+            Job::ScheduledAutosave {
+                game: game.to_string(),
+            },
+        )?;
+        let name = state.config.display_name(&game).to_string();
+        state.log_event(&format!("{name}: Scheduled autosave started"));
+        match next_scheduled_save(state, &game, now)? {
+            Some(next_save) => state.scheduled_saves.insert(game, next_save),
+            None => state.scheduled_saves.remove(&game),
+        };
+        draw_scheduled = true;
     }
+
+    Ok(draw_scheduled)
+}
+
+/// Get the first time of a game's `autosave_schedule` after `now`.
+/// Schedules, which never occur again, don't have a next time.
+fn next_scheduled_save(
+    state: &AppState,
+    game: &str,
+    now: DateTime<Local>,
+) -> Result<Option<DateTime<Local>>> {
+    let schedule = state.config.game(game)?.schedule()?;
+    Ok(schedule.and_then(|schedule| schedule.after(&now).next()))
 }
 
-/// If an autosave timeout is specified, we won't save the game until the specified timeout
-/// finished.
-/// Remove the ignore rule for file changes after a few seconds.
-/// We only have to lock this for a short amount of time, after the restore.
-pub fn remove_autosave_timeouts(state: &mut AppState) {
-    let games: Vec<String> = state.autosave_timeouts.keys().cloned().collect();
-
-    for game in games.iter() {
-        let game_config = state.config.games.get(game).unwrap();
-        let timeout = game_config.autosave_timeout;
-        let timeout_duration = Duration::seconds(timeout.try_into().unwrap_or(i64::MAX));
-
-        let last_save = state.autosave_timeouts.get(game).unwrap();
-        if (Local::now() - timeout_duration).gt(last_save) {
-            state.autosave_timeouts.remove(game);
+/// Check the free space on the backup volumes, if it hasn't been checked for a while.
+/// The status line only shows the cached value, as checking it on every draw is too expensive.
+///
+/// Returns whether the free space changed.
+fn poll_free_space(state: &mut AppState) -> bool {
+    let poll_due = state
+        .last_space_check
+        .map(|last| Local::now() - last > Duration::seconds(SPACE_POLL_INTERVAL))
+        .unwrap_or(true);
+    if !poll_due {
+        return false;
+    }
+
+    state.update_free_space()
+}
+
+/// Check whether game processes started or exited and create autosaves accordingly.
+///
+/// A started game is saved right away, so its saves are captured before anything is played.
+/// Exited games are treated like a file change, as the game might still write its saves.
+pub fn poll_game_processes(state: &mut AppState) -> Result<bool> {
+    let poll_due = state
+        .last_process_poll
+        .map(|last| Local::now() - last > Duration::seconds(PROCESS_POLL_INTERVAL))
+        .unwrap_or(true);
+    if !poll_due {
+        return Ok(false);
+    }
+    state.last_process_poll = Some(Local::now());
+
+    let mut games: Vec<(String, String, bool, bool)> = state
+        .config
+        .games
+        .iter()
+        .filter(|(_, game_config)| game_config.has_autosaves())
+        .filter(|(_, game_config)| game_config.autosave_on_launch || game_config.autosave_on_exit)
+        .filter_map(|(game, game_config)| {
+            let process_name = game_config.process_name.clone()?;
+            Some((
+                game.clone(),
+                process_name,
+                game_config.autosave_on_launch,
+                game_config.autosave_on_exit,
+            ))
+        })
+        .collect();
+    games.sort();
+
+    let mut draw_scheduled = false;
+    for (game, process_name, on_launch, on_exit) in games {
+        let running = is_process_running(&process_name);
+        // Only remember the state of games we see for the first time.
+        let Some(was_running) = state.running_games.insert(game.clone(), running) else {
+            continue;
+        };
+
+        let name = state.config.display_name(&game).to_string();
+        if running && !was_running {
+            state.log_event(&format!("{name}: Game has been launched"));
+            if on_launch && !state.worker.is_busy(&game) {
+                state.log(&format!(
+                    "Creating autosave for {name}, as it has been launched"
+                ));
+                state.worker.submit(&state.config, Job::Autosave { game })?;
+                draw_scheduled = true;
+            }
+        } else if !running && was_running {
+            state.log_event(&format!("{name}: Game has exited"));
+            if on_exit {
+                state.engine.change_detected(&game, Local::now());
+            }
         }
     }
+
+    Ok(draw_scheduled)
+}
+
+/// Process the results of all archive operations that finished in the background.
+pub fn handle_finished_jobs(state: &mut AppState) -> Result<bool> {
+    let received = state.worker.receive();
+    let draw_scheduled =
+        received.progress_changed || !received.results.is_empty() || !received.logs.is_empty();
+
+    for message in received.logs {
+        state.log(&message);
+    }
+
+    for JobResult {
+        job,
+        result,
+        summary,
+    } in received.results
+    {
+        let display_name = state.config.display_name(job.game()).to_string();
+        let archive = match result {
+            Ok(archive) => archive,
+            Err(err) => {
+                // Don't launch the game on top of a failed restore.
+                if matches!(job, Job::Restore { .. }) {
+                    state.pending_launches.remove(job.game());
+                }
+                if err.downcast_ref::<EmptySavegameLocation>().is_some() {
+                    state.log(&format!("Skipped save for {display_name}: {err}"));
+                    continue;
+                }
+                if err.downcast_ref::<LowDiskSpace>().is_some() {
+                    error!("Skipped autosave for {display_name}: {err}");
+                    state.log(&format!(
+                        "WARNING: Skipped autosave for {display_name}: {err}"
+                    ));
+                    state.emit(EventKind::Error, job.game(), None, Some(&err.to_string()));
+                    state.notify(
+                        &format!("Skipped autosave for {display_name}"),
+                        &err.to_string(),
+                    );
+                    continue;
+                }
+                if let Some(space) = err.downcast_ref::<InsufficientSpace>() {
+                    let message = format!("Skipped save for {display_name}: {space}");
+                    error!("{message}");
+                    state.log(&message);
+                    state.emit(EventKind::Error, job.game(), None, Some(&message));
+                    state.notify(
+                        &format!("Skipped save for {display_name}"),
+                        &space.to_string(),
+                    );
+                    // Don't stack up messages, if several saves are skipped in a row.
+                    if !matches!(state.state, UiState::Message { .. }) {
+                        state.push_state(UiState::Message {
+                            title: format!("Skipped save for {display_name}"),
+                            message: space.to_string(),
+                        });
+                    }
+                    continue;
+                }
+
+                let (message, path) = match &job {
+                    Job::Autosave { .. } => (
+                        format!("Failed to create autosave for {display_name}"),
+                        None,
+                    ),
+                    Job::ScheduledAutosave { .. } => (
+                        format!("Failed to create scheduled autosave for {display_name}"),
+                        None,
+                    ),
+                    Job::ManualSave { name, .. } => (
+                        format!("Failed to create save '{name}' for {display_name}"),
+                        None,
+                    ),
+                    Job::Restore { save, .. } => (
+                        format!(
+                            "Failed to restore savefile '{}' for {display_name}",
+                            save.file_name
+                        ),
+                        Some(save.path.as_path()),
+                    ),
+                    Job::Verify { .. } => {
+                        // The list of corrupt saves doesn't fit into the event log.
+                        let message = format!("Found corrupt saves of {display_name}");
+                        state.push_state(UiState::Message {
+                            title: message.clone(),
+                            message: err.to_string(),
+                        });
+                        (message, None)
+                    }
+                    // The restore can still be confirmed without knowing what it changes.
+                    Job::RestoreSummary { game, save, launch } => {
+                        state.log(&format!(
+                            "Failed to list the content of '{}': {err}",
+                            save.file_name
+                        ));
+                        show_restore_prompt(state, game.clone(), save.clone(), None, *launch);
+                        continue;
+                    }
+                };
+                error!("{message}:\n{err:?}");
+                let summary = err.to_string();
+                let summary = summary.lines().next().unwrap_or_default();
+                state.log(&format!("{message}: {summary}"));
+                state.emit(
+                    EventKind::Error,
+                    job.game(),
+                    path,
+                    Some(&format!("{message}: {err:#}")),
+                );
+                state.notify(&message, &err.to_string());
+                continue;
+            }
+        };
+
+        match job {
+            Job::Autosave { game } => {
+                // The savegame location changed since the last restore.
+                state.last_restored.remove(&game);
+                state.log(&format!("Autosave created for {display_name}"));
+                state.emit(EventKind::SaveCreated, &game, archive.as_deref(), None);
+                notify_autosave(state, &game);
+                state.update_last_autosave(&game)?;
+                state.update_autosaves()?;
+                state.autosaves.focus();
+            }
+            Job::ScheduledAutosave { game } => {
+                // The savegame location might've changed since the last restore.
+                state.last_restored.remove(&game);
+                state.log(&format!("Scheduled autosave created for {display_name}"));
+                state.emit(EventKind::SaveCreated, &game, archive.as_deref(), None);
+                state.update_autosaves()?;
+                state.autosaves.focus();
+            }
+            Job::ManualSave { game, name } => {
+                state.log(&format!(
+                    "New manual save for {display_name} with name '{name}'"
+                ));
+                state.emit(EventKind::SaveCreated, &game, archive.as_deref(), None);
+                // The lists always show the saves of the selected game, which may have changed
+                // since the save has been started.
+                state.update_manual_saves()?;
+                match &archive {
+                    Some(archive) if game == state.get_selected_game() => {
+                        state.manual_saves.select_path(archive);
+                    }
+                    _ => (),
+                }
+                state.manual_saves.focus();
+                handle_manual_save_limit(state, &game)?;
+            }
+            Job::Restore {
+                game,
+                save,
+                snapshot,
+            } => {
+                // Restart the ignore window, as the restore just finished writing files.
+                state.engine.ignore_changes(&game, Local::now());
+                state.log(&format!(
+                    "Restored savefile '{}' for {display_name}",
+                    save.file_name
+                ));
+                state.emit(EventKind::SaveRestored, &game, Some(&save.path), None);
+                // Undoing a restore brings back a snapshot, which isn't shown in any list.
+                if save.path.starts_with(state.config.undo_dir()) {
+                    state.last_restored.remove(&game);
+                } else {
+                    state.last_restored.insert(game.clone(), save.path.clone());
+                }
+                if let Err(err) = state.persist() {
+                    error!("Failed to remember the restored save:\n{err:?}");
+                }
+                state.notify(
+                    &format!("{display_name} restored"),
+                    &format!("Restored savefile '{}'", save.file_name),
+                );
+                if state.pending_launches.remove(&game) {
+                    launch_game(state, &game)?;
+                }
+                if let Some(snapshot) = snapshot {
+                    state.push_undo(UndoAction::Restore { game, snapshot });
+                }
+            }
+            Job::Verify { .. } => {
+                state.log(&format!("All saves of {display_name} are healthy"));
+            }
+            Job::RestoreSummary { game, save, launch } => {
+                show_restore_prompt(state, game, save, summary, launch);
+            }
+        }
+    }
+
+    Ok(draw_scheduled)
+}
+
+/// Launch a game with its `launch_command`, unless it's already running.
+fn launch_game(state: &mut AppState, game: &str) -> Result<()> {
+    let game_config = state.config.game(game)?;
+    let display_name = state.config.display_name(game);
+    let Some(command) = game_config.launch_command.clone() else {
+        return Ok(());
+    };
+
+    // The game might have been started while the save was being restored.
+    if game_config
+        .process_name
+        .as_deref()
+        .is_some_and(is_process_running)
+    {
+        state.log(&format!(
+            "{display_name} is already running, it isn't launched again"
+        ));
+        return Ok(());
+    }
+
+    match spawn_detached(&command) {
+        Ok(()) => state.log(&format!("Launched {display_name}")),
+        Err(err) => {
+            error!("Failed to launch {display_name}:\n{err:?}");
+            state.log(&format!("Failed to launch {display_name}: {err}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Notify about a new autosave and how many autosave slots are in use.
+fn notify_autosave(state: &AppState, game: &str) {
+    let Some(game_config) = state.config.games.get(game) else {
+        return;
+    };
+    let slots = get_archive_files(&state.config.autosave_dir(game))
+        .map(|saves| saves.len())
+        .unwrap_or(0);
+
+    state.notify(
+        &format!(
+            "{} autosaved (slot {slots}/{})",
+            state.config.display_name(game),
+            game_config.autosaves
+        ),
+        "A new autosave has been created",
+    );
 }
@@ -0,0 +1,53 @@
+use anyhow::{bail, Result};
+
+use game_saver::{
+    config::Config,
+    saves::{verify_saves, VerifyReport},
+};
+
+/// Check all saves of all games for corruption and print a summary for each game.
+/// Fails, if any corrupt save has been found.
+pub fn verify_backups(config: &Config) -> Result<()> {
+    let mut games: Vec<&String> = config.games.keys().collect();
+    games.sort();
+
+    let mut corrupt = 0;
+    for game in games {
+        let report = verify_saves(config, game, &|_, _| {})?;
+        println!(
+            "{game}: {} healthy, {} corrupt",
+            report.healthy,
+            report.corrupt.len()
+        );
+        for (save, reason) in &report.corrupt {
+            println!("  {:?}: {reason}", save.path);
+        }
+        corrupt += report.corrupt.len();
+    }
+
+    if corrupt > 0 {
+        bail!("Found {corrupt} corrupt saves");
+    }
+    println!("All saves are healthy");
+
+    Ok(())
+}
+
+/// Turn a report into an error, which lists all corrupt saves.
+pub fn corrupt_saves_error(report: &VerifyReport) -> Result<()> {
+    if report.corrupt.is_empty() {
+        return Ok(());
+    }
+
+    let saves: Vec<String> = report
+        .corrupt
+        .iter()
+        .map(|(save, reason)| format!("- {}: {reason}", save.file_name))
+        .collect();
+    bail!(
+        "{} of {} saves are corrupt:\n{}",
+        report.corrupt.len(),
+        report.corrupt.len() + report.healthy,
+        saves.join("\n")
+    )
+}
@@ -0,0 +1,413 @@
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, write},
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use game_saver::config::{validate_game_name, Config, GameConfig, LayoutConfig};
+
+use super::helper::terminal::{init_terminal, install_panic_hook, restore_terminal, Terminal};
+
+/// The fields that have to be filled in by the user.
+/// The index of each field corresponds to its position in [Wizard::values].
+const FIELDS: [&str; 4] = [
+    "Backup directory",
+    "Game name",
+    "Savegame location",
+    "Autosave slots",
+];
+
+/// The current step of the wizard.
+enum Step {
+    /// The user is filling in the form.
+    Form,
+    /// A game has been added. Ask whether another game should be added.
+    AddAnother,
+}
+
+/// The state of the first-run setup wizard.
+struct Wizard {
+    step: Step,
+    /// The index of the currently focused field.
+    selected: usize,
+    values: [String; 4],
+    games: HashMap<String, GameConfig>,
+    error: Option<String>,
+    /// The existing config, which doesn't contain any games yet.
+    /// Its settings are kept, only the games are added.
+    existing: Option<Config>,
+}
+
+/// Interactively create a new config and write it to `path`.
+///
+/// This is shown on the very first start, when no config file exists yet.
+/// The user is asked for the backup directory and at least one game.
+///
+/// If an `existing` config without any games is passed, all of its settings are kept.
+pub fn run_setup_wizard(path: &Path, existing: Option<Config>) -> Result<Config> {
+    install_panic_hook();
+    // There's no config yet, so the mouse is captured just like by default.
+    let mut terminal = init_terminal(true)?;
+    terminal.clear()?;
+
+    let mut wizard = Wizard {
+        step: Step::Form,
+        selected: 0,
+        values: [
+            existing
+                .as_ref()
+                .map(|config| config.backup_directory.clone())
+                .unwrap_or_else(|| "~/.local/share/game_saver/".into()),
+            String::new(),
+            String::new(),
+            "5".into(),
+        ],
+        games: HashMap::new(),
+        error: None,
+        existing,
+    };
+
+    // Restore the terminal in case any errors happen.
+    let result = wizard_loop(&mut wizard, &mut terminal);
+    restore_terminal(&mut terminal)?;
+    result?;
+
+    let config = match wizard.existing {
+        Some(existing) => Config {
+            backup_directory: wizard.values[0].clone(),
+            games: wizard.games,
+            ..existing
+        },
+        None => new_config(wizard.values[0].clone(), wizard.games),
+    };
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).context(format!("Failed to create config directory {parent:?}"))?;
+    }
+    let content = toml::to_string_pretty(&config).context("Failed to serialize config")?;
+    write(path, content).context(format!("Failed to write config to {path:?}"))?;
+
+    Ok(config)
+}
+
+/// A config with default settings and the games of the wizard.
+fn new_config(backup_directory: String, games: HashMap<String, GameConfig>) -> Config {
+    Config {
+        backup_directory,
+        layout: LayoutConfig::default(),
+        trash_retention_days: 30,
+        min_free_space_mib: 100,
+        desktop_notifications: false,
+        mouse_capture: true,
+        key_repeat_limit: 1,
+        idle_redraw_seconds: 30,
+        data_directory: None,
+        temp_directory: None,
+        games,
+    }
+}
+
+/// Explain how to add games, as the config at `path` doesn't contain any.
+///
+/// The user can start the setup wizard from here, which returns the config with the new games.
+/// Returns `None`, if the user quit instead.
+pub fn run_empty_config_screen(path: &Path, config: Config) -> Result<Option<Config>> {
+    install_panic_hook();
+    let mut terminal = init_terminal(config.mouse_capture)?;
+    terminal.clear()?;
+
+    let result = empty_config_loop(path, !config.games.is_empty(), &mut terminal);
+    restore_terminal(&mut terminal)?;
+    if !result? {
+        return Ok(None);
+    }
+
+    run_setup_wizard(path, Some(config)).map(Some)
+}
+
+/// Show the hint, until the user either starts the wizard or quits.
+/// Returns true, if the wizard should be started.
+fn empty_config_loop(path: &Path, has_games: bool, terminal: &mut Terminal) -> Result<bool> {
+    draw_empty_config(terminal, path, has_games)?;
+    loop {
+        if !poll(Duration::from_millis(100))? {
+            continue;
+        }
+
+        match read()? {
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            }) => return Ok(false),
+            Event::Key(event) => match event.code {
+                KeyCode::Char('w') | KeyCode::Enter => return Ok(true),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                _ => (),
+            },
+            Event::Resize(_, _) => draw_empty_config(terminal, path, has_games)?,
+            _ => (),
+        }
+    }
+}
+
+/// `has_games` is set, if the config contains games, but none of their savegame locations exists.
+fn draw_empty_config(terminal: &mut Terminal, path: &Path, has_games: bool) -> Result<()> {
+    let (headline, title) = if has_games {
+        (
+            format!("None of the savegame locations in your config {path:?} exists."),
+            "Game-Saver - No savegame locations found",
+        )
+    } else {
+        (
+            format!("Your config {path:?} doesn't contain any games yet."),
+            "Game-Saver - No games configured",
+        )
+    };
+    terminal.draw(|frame| {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Ratio(1, 8),
+                    Constraint::Ratio(3, 4),
+                    Constraint::Ratio(1, 8),
+                ]
+                .as_ref(),
+            )
+            .split(frame.area());
+
+        let lines = vec![
+            Line::from(headline),
+            Line::from(""),
+            Line::from("Add a section like this for each game you want to back up:"),
+            Line::from(""),
+            Line::styled("[games.factorio]", Style::default().fg(Color::Yellow)),
+            Line::styled(
+                "savegame_location = \"~/.factorio/saves\"",
+                Style::default().fg(Color::Yellow),
+            ),
+            Line::styled("autosaves = 5", Style::default().fg(Color::Yellow)),
+            Line::styled("autosave_timeout = 0", Style::default().fg(Color::Yellow)),
+            Line::styled("ignored_files = []", Style::default().fg(Color::Yellow)),
+            Line::from(""),
+            Line::from("Alternatively, the setup wizard adds the games for you."),
+            Line::from("All other settings are kept, but comments in the config are lost."),
+            Line::from(""),
+            Line::from("w/Enter: Start the setup wizard - q/Esc: Quit"),
+        ];
+
+        let paragraph = Paragraph::new(Text::from(lines))
+            .block(Block::default().borders(Borders::ALL).title(title));
+        frame.render_widget(paragraph, chunks[1]);
+    })?;
+
+    Ok(())
+}
+
+/// Handle events and draw the wizard, until the user either finished or aborted the setup.
+fn wizard_loop(wizard: &mut Wizard, terminal: &mut Terminal) -> Result<()> {
+    draw_wizard(terminal, wizard)?;
+    loop {
+        if !poll(Duration::from_millis(100))? {
+            continue;
+        }
+
+        let event = match read()? {
+            Event::Key(event) => event,
+            Event::Resize(_, _) => {
+                draw_wizard(terminal, wizard)?;
+                continue;
+            }
+            _ => continue,
+        };
+
+        if let KeyEvent {
+            code: KeyCode::Char('c'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        } = event
+        {
+            bail!("Setup has been aborted.");
+        }
+
+        let finished = match wizard.step {
+            Step::Form => handle_form(&event, wizard)?,
+            Step::AddAnother => handle_add_another(&event, wizard),
+        };
+        if finished {
+            return Ok(());
+        }
+
+        draw_wizard(terminal, wizard)?;
+    }
+}
+
+/// Handle input while the user is filling in the form.
+fn handle_form(event: &KeyEvent, wizard: &mut Wizard) -> Result<bool> {
+    match event.code {
+        KeyCode::Esc => bail!("Setup has been aborted."),
+        KeyCode::Down | KeyCode::Tab => {
+            wizard.selected = (wizard.selected + 1) % FIELDS.len();
+        }
+        KeyCode::Up | KeyCode::BackTab => {
+            wizard.selected = (wizard.selected + FIELDS.len() - 1) % FIELDS.len();
+        }
+        KeyCode::Backspace => {
+            wizard.values[wizard.selected].pop();
+            wizard.error = None;
+        }
+        KeyCode::Char(character) => {
+            wizard.values[wizard.selected].push(character);
+            wizard.error = None;
+        }
+        KeyCode::Enter => {
+            // Jump to the next field, until we reach the last one.
+            if wizard.selected < FIELDS.len() - 1 {
+                wizard.selected += 1;
+                return Ok(false);
+            }
+
+            match build_game(wizard) {
+                Ok((name, game_config)) => {
+                    wizard.games.insert(name, game_config);
+                    wizard.step = Step::AddAnother;
+                }
+                Err(error) => wizard.error = Some(error.to_string()),
+            }
+        }
+        _ => (),
+    }
+
+    Ok(false)
+}
+
+/// Ask the user whether another game should be added.
+/// Returns true, if the wizard is finished.
+fn handle_add_another(event: &KeyEvent, wizard: &mut Wizard) -> bool {
+    match event.code {
+        KeyCode::Char('y' | 'Y') => {
+            // Reset all game specific fields.
+            wizard.values[1].clear();
+            wizard.values[2].clear();
+            wizard.values[3] = "5".into();
+            wizard.selected = 1;
+            wizard.step = Step::Form;
+            false
+        }
+        KeyCode::Char('n' | 'N') | KeyCode::Enter | KeyCode::Esc => true,
+        _ => false,
+    }
+}
+
+/// Validate the form and build a game config from it.
+fn build_game(wizard: &Wizard) -> Result<(String, GameConfig)> {
+    if wizard.values[0].trim().is_empty() {
+        bail!("The backup directory cannot be empty");
+    }
+
+    let name = wizard.values[1].trim().to_string();
+    validate_game_name(&name)?;
+    if wizard.games.contains_key(&name) {
+        bail!("Game {name} has already been added");
+    }
+
+    let game_config = GameConfig {
+        savegame_location: wizard.values[2].trim().to_string(),
+        autosaves: wizard.values[3]
+            .trim()
+            .parse()
+            .context("Autosave slots must be a number")?,
+        ..Default::default()
+    };
+    if !game_config.savegame_location().exists() {
+        bail!(
+            "Cannot find savegame location {:?}",
+            game_config.savegame_location
+        );
+    }
+
+    Ok((name, game_config))
+}
+
+fn draw_wizard(terminal: &mut Terminal, wizard: &Wizard) -> Result<()> {
+    terminal.draw(|frame| {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Ratio(1, 8),
+                    Constraint::Ratio(3, 4),
+                    Constraint::Ratio(1, 8),
+                ]
+                .as_ref(),
+            )
+            .split(frame.area());
+
+        let intro = if wizard.existing.is_some() {
+            "Your configuration doesn't contain any games yet."
+        } else {
+            "No configuration file has been found."
+        };
+        let mut lines = vec![
+            Line::from(intro),
+            Line::from("Please set up your backup directory and your first game."),
+            Line::from(""),
+        ];
+
+        match wizard.step {
+            Step::Form => {
+                for (index, field) in FIELDS.iter().enumerate() {
+                    let mut style = Style::default();
+                    if index == wizard.selected {
+                        style = style
+                            .fg(Color::White)
+                            .bg(Color::DarkGray)
+                            .add_modifier(Modifier::BOLD);
+                    }
+                    lines.push(Line::from(vec![
+                        Span::raw(format!("{field:<20}")),
+                        Span::styled(wizard.values[index].clone(), style),
+                    ]));
+                }
+                lines.push(Line::from(""));
+                lines.push(Line::from(
+                    "Up/Down: Switch fields - Enter: Next field/Add game - Esc: Abort",
+                ));
+            }
+            Step::AddAnother => {
+                let mut games: Vec<&String> = wizard.games.keys().collect();
+                games.sort();
+                for game in games {
+                    lines.push(Line::from(format!("Added game: {game}")));
+                }
+                lines.push(Line::from(""));
+                lines.push(Line::from("Do you want to add another game? (y/N)"));
+            }
+        }
+
+        if let Some(error) = &wizard.error {
+            lines.push(Line::from(""));
+            lines.push(Line::styled(error.clone(), Style::default().fg(Color::Red)));
+        }
+
+        let paragraph = Paragraph::new(Text::from(lines)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Game-Saver Setup"),
+        );
+        frame.render_widget(paragraph, chunks[1]);
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    thread,
+};
+
+use anyhow::Result;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use log::error;
+
+use game_saver::{
+    config::Config,
+    files::SaveFile,
+    saves::{
+        autosave_game, manually_save_game, restore_save, restore_summary, scheduled_autosave_game,
+        snapshot_savegame, verify_saves, RestoreSummary,
+    },
+};
+
+use super::{hooks::run_hook, verify::corrupt_saves_error};
+
+/// Archive operations that're executed by the background worker.
+#[derive(Clone, Debug)]
+pub enum Job {
+    Autosave {
+        game: String,
+    },
+    /// An autosave for the `autosave_schedule` of a game.
+    ScheduledAutosave {
+        game: String,
+    },
+    ManualSave {
+        game: String,
+        name: String,
+    },
+    /// If a snapshot is given, the current savegame location is archived there before the
+    /// restore. This allows to undo the restore.
+    Restore {
+        game: String,
+        save: SaveFile,
+        snapshot: Option<SaveFile>,
+    },
+    /// Check all saves of a game for corruption.
+    Verify {
+        game: String,
+    },
+    /// Determine what restoring a save would change, so the user can confirm the restore.
+    /// Listing big archives takes a while, which is why this isn't done on the UI thread.
+    RestoreSummary {
+        game: String,
+        save: SaveFile,
+        /// Launch the game after a successful restore.
+        launch: bool,
+    },
+}
+
+impl Job {
+    /// The game this job operates on.
+    pub fn game(&self) -> &str {
+        match self {
+            Job::Autosave { game } => game,
+            Job::ScheduledAutosave { game } => game,
+            Job::ManualSave { game, .. } => game,
+            Job::Restore { game, .. } => game,
+            Job::Verify { game } => game,
+            Job::RestoreSummary { game, .. } => game,
+        }
+    }
+}
+
+/// The message that's sent back to the app, once a job finished.
+#[derive(Debug)]
+pub struct JobResult {
+    pub job: Job,
+    /// The created archive of saves, restores and verifications don't create any.
+    pub result: Result<Option<PathBuf>>,
+    /// The result of a [Job::RestoreSummary].
+    pub summary: Option<RestoreSummary>,
+}
+
+/// Messages that're sent from the worker thread to the app.
+enum Message {
+    /// The processed and total amount of bytes of the currently running job of a game.
+    Progress {
+        game: String,
+        processed: u64,
+        total: u64,
+    },
+    Finished(JobResult),
+    /// A message for the event log.
+    Log(String),
+}
+
+/// Everything that has been received from the worker thread since the last call to
+/// [Worker::receive].
+pub struct Received {
+    /// Whether the progress of any job changed.
+    pub progress_changed: bool,
+    /// The results of all finished jobs.
+    pub results: Vec<JobResult>,
+    /// Messages for the event log, such as the results of hook commands.
+    pub logs: Vec<String>,
+}
+
+/// Archiving and restoring big saves may take a long time.
+/// To keep the UI responsive, these operations are done in a separate thread.
+///
+/// Jobs are processed one after another in the order they've been submitted.
+pub struct Worker {
+    sender: Sender<(Config, Job)>,
+    receiver: Receiver<Message>,
+    /// The jobs that're currently in flight by game.
+    /// The first job of each game is the one that's processed next.
+    jobs: HashMap<String, VecDeque<Job>>,
+    /// The processed and total amount of bytes of the currently running jobs by game.
+    progress: HashMap<String, (u64, u64)>,
+}
+
+impl Worker {
+    /// Spawn the worker thread.
+    pub fn new() -> Worker {
+        let (job_sender, job_receiver) = unbounded::<(Config, Job)>();
+        let (result_sender, result_receiver) = unbounded();
+
+        thread::spawn(move || {
+            // The loop ends, once the app dropped the worker.
+            while let Ok((config, job)) = job_receiver.recv() {
+                let progress = |processed, total| {
+                    let _ = result_sender.send(Message::Progress {
+                        game: job.game().to_string(),
+                        processed,
+                        total,
+                    });
+                };
+
+                let mut summary = None;
+                let result = match &job {
+                    Job::Autosave { game } => {
+                        autosave_game(&config, game, &progress).map(|archive| {
+                            run_post_save_command(&config, game, &archive, &result_sender);
+                            Some(archive)
+                        })
+                    }
+                    Job::ScheduledAutosave { game } => {
+                        scheduled_autosave_game(&config, game, &progress).map(|archive| {
+                            run_post_save_command(&config, game, &archive, &result_sender);
+                            Some(archive)
+                        })
+                    }
+                    Job::ManualSave { game, name } => {
+                        manually_save_game(&config, game, name, &progress).map(|archive| {
+                            run_post_save_command(&config, game, &archive, &result_sender);
+                            Some(archive)
+                        })
+                    }
+                    Job::Restore {
+                        game,
+                        save,
+                        snapshot,
+                    } => run_pre_restore_command(&config, game, save, &result_sender)
+                        .and_then(|_| match snapshot {
+                            Some(snapshot) => snapshot_savegame(&config, game, &snapshot.path),
+                            None => Ok(()),
+                        })
+                        .and_then(|_| restore_save(&config, game, save, &progress))
+                        .map(|_| None),
+                    Job::Verify { game } => verify_saves(&config, game, &progress)
+                        .and_then(|report| corrupt_saves_error(&report))
+                        .map(|_| None),
+                    Job::RestoreSummary { game, save, .. } => restore_summary(&config, game, save)
+                        .map(|result| {
+                            summary = Some(result);
+                            None
+                        }),
+                };
+
+                let result = JobResult {
+                    job,
+                    result,
+                    summary,
+                };
+                if result_sender.send(Message::Finished(result)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Worker {
+            sender: job_sender,
+            receiver: result_receiver,
+            jobs: HashMap::new(),
+            progress: HashMap::new(),
+        }
+    }
+
+    /// Whether there's currently a job in flight for the given game.
+    pub fn is_busy(&self, game: &str) -> bool {
+        self.jobs.get(game).is_some_and(|jobs| !jobs.is_empty())
+    }
+
+    /// Whether there aren't any jobs in flight.
+    pub fn is_idle(&self) -> bool {
+        self.jobs.values().all(VecDeque::is_empty)
+    }
+
+    /// Get the job that's currently in flight for the given game.
+    pub fn current_job(&self, game: &str) -> Option<&Job> {
+        self.jobs.get(game).and_then(VecDeque::front)
+    }
+
+    /// Schedule a new job.
+    /// The current config is passed along, so the worker always operates on the latest config.
+    pub fn submit(&mut self, config: &Config, job: Job) -> Result<()> {
+        self.jobs
+            .entry(job.game().to_string())
+            .or_default()
+            .push_back(job.clone());
+        self.sender.send((config.clone(), job))?;
+
+        Ok(())
+    }
+
+    /// Get the processed and total amount of bytes of the job that's running for a game.
+    pub fn progress(&self, game: &str) -> Option<(u64, u64)> {
+        self.progress.get(game).copied()
+    }
+
+    /// Process all messages from the worker thread since the last call.
+    pub fn receive(&mut self) -> Received {
+        let mut received = Received {
+            progress_changed: false,
+            results: Vec::new(),
+            logs: Vec::new(),
+        };
+
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Progress {
+                    game,
+                    processed,
+                    total,
+                } => {
+                    self.progress.insert(game, (processed, total));
+                    received.progress_changed = true;
+                }
+                Message::Finished(result) => {
+                    let game = result.job.game();
+                    // Jobs are processed in order, so the finished job is the first of its game.
+                    if let Some(jobs) = self.jobs.get_mut(game) {
+                        jobs.pop_front();
+                        if jobs.is_empty() {
+                            self.jobs.remove(game);
+                        }
+                    }
+                    self.progress.remove(game);
+                    received.results.push(result);
+                }
+                Message::Log(message) => received.logs.push(message),
+            }
+        }
+
+        received
+    }
+}
+
+/// Run the `pre_restore_command` of a game, if one is configured.
+///
+/// A failing hook only aborts the restore, if `abort_restore_on_hook_failure` is set.
+/// Otherwise, the failure is logged and the restore proceeds.
+fn run_pre_restore_command(
+    config: &Config,
+    game: &str,
+    save: &SaveFile,
+    sender: &Sender<Message>,
+) -> Result<()> {
+    let game_config = config.game(game)?;
+    let Some(command) = &game_config.pre_restore_command else {
+        return Ok(());
+    };
+
+    if let Err(err) = run_hook(command, game, &save.path) {
+        if game_config.abort_restore_on_hook_failure {
+            return Err(err.context("pre_restore_command failed, the restore has been aborted"));
+        }
+        let name = config.display_name(game);
+        error!("pre_restore_command for {name} failed:\n{err:?}");
+        let _ = sender.send(Message::Log(format!(
+            "pre_restore_command for {name} failed, restoring anyway: {err}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Run the `post_save_command` of a game in a separate thread, if one is configured.
+/// Hooks such as uploads may take a long time, which shouldn't delay any other jobs.
+fn run_post_save_command(config: &Config, game: &str, archive: &Path, sender: &Sender<Message>) {
+    let Some(command) = config
+        .games
+        .get(game)
+        .and_then(|game_config| game_config.post_save_command.clone())
+    else {
+        return;
+    };
+
+    let game = game.to_string();
+    let archive = archive.to_path_buf();
+    let name = config.display_name(&game).to_string();
+    let sender = sender.clone();
+    thread::spawn(move || {
+        let message = match run_hook(&command, &game, &archive) {
+            Ok(()) => format!("post_save_command for {name} finished successfully"),
+            Err(err) => {
+                error!("post_save_command for {name} failed:\n{err:?}");
+                format!("post_save_command for {name} failed: {err}")
+            }
+        };
+        let _ = sender.send(Message::Log(message));
+    });
+}
@@ -0,0 +1,1108 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ffi::{OsStr, OsString},
+    fmt,
+    fs::{copy, metadata, read_dir, remove_file, rename, symlink_metadata, File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDateTime;
+use ignore::gitignore::Gitignore;
+use log::warn;
+
+use crate::{
+    config::ArchiveFormat,
+    files::{available_space, build_ignore, format_size},
+};
+
+/// A callback that's called with the processed and the total amount of bytes, while a save is
+/// being created or restored.
+pub type Progress<'a> = &'a dyn Fn(u64, u64);
+
+/// Tar flags, which strip all metadata that differs between two archives of identical files.
+/// Entries are sorted by name and use a fixed mtime, owner and group.
+const REPRODUCIBLE_TAR_FLAGS: [&str; 6] = [
+    "--sort=name",
+    "--mtime=@0",
+    "--owner=0",
+    "--group=0",
+    "--numeric-owner",
+    "--mode=u+rw,go=rX",
+];
+
+/// The fixed modification time of all entries of reproducible zip archives, 1980-01-01 00:00.
+/// That's the earliest time, which can be represented by the MS-DOS format of zip.
+const REPRODUCIBLE_ZIP_TIME: u16 = 0;
+const REPRODUCIBLE_ZIP_DATE: u16 = (1 << 5) | 1;
+/// The signatures of the end of central directory record and of central directory entries.
+const ZIP_END_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+const ZIP_DIRECTORY_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x01, 0x02];
+
+/// Saves are assumed to compress to at least half of their size.
+/// Archives that turn out bigger make the archiver fail, after which its temporary file is
+/// removed again.
+const ESTIMATED_COMPRESSION_RATIO: u64 = 2;
+/// Additional room for the headers and the index of an archive.
+const ARCHIVE_OVERHEAD: u64 = 1024 * 1024;
+
+/// The error that's returned, if the volume of an archive doesn't have enough space for it.
+#[derive(Debug)]
+pub struct InsufficientSpace {
+    /// The estimated size of the archive, based on the uncompressed size of all files.
+    pub needed: u64,
+    pub available: u64,
+}
+
+impl fmt::Display for InsufficientSpace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Not enough free space: need ~{}, have {}",
+            format_size(self.needed),
+            format_size(self.available)
+        )
+    }
+}
+
+impl std::error::Error for InsufficientSpace {}
+
+/// A file in the listing of an archive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// The uncompressed size in bytes.
+    pub size: u64,
+    /// The modification time in local time, as listed by the archiver.
+    /// Listings only contain the minute. This is `None`, if the time couldn't be parsed.
+    pub modified: Option<NaiveDateTime>,
+}
+
+/// Options that control which files end up in an archive and how they're stored.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArchiveOptions<'a> {
+    /// Strip all metadata that differs between two archives of identical files.
+    pub reproducible: bool,
+    /// Gitignore-style globs relative to the archived directory.
+    /// Matching files and directories aren't archived.
+    pub ignored_files: &'a [String],
+    /// Names of files and directories next to `source_filename`, which are archived as well.
+    /// Missing siblings are skipped and `ignored_files` don't apply to them.
+    pub siblings: &'a [String],
+    /// Archive the targets of symlinks instead of the links themselves.
+    pub follow_symlinks: bool,
+    /// Only archive these files, relative to the archive's working directory.
+    /// Directories are always archived. All files are archived, if this isn't set.
+    pub only: Option<&'a BTreeSet<PathBuf>>,
+    /// An additional file, which is stored as the very first entry at the root of the archive.
+    pub extra_file: Option<&'a Path>,
+    /// The directory, in which the archive is written, before it's moved to its destination.
+    /// Defaults to the directory of the destination.
+    pub temp_directory: Option<&'a Path>,
+    /// Don't check whether the archive fits onto its volume.
+    /// Only a warning is logged, if it probably doesn't fit.
+    pub skip_space_check: bool,
+}
+
+/// Create an archive at `dest`, which contains `cwd/source_filename`.
+/// All paths inside the archive are relative to `cwd`.
+///
+/// If `options.reproducible` is set, identical files result in identical archives.
+///
+/// Symlinks are stored as links, unless `options.follow_symlinks` is set.
+/// The `options.extra_file` can be read again via [read_extra_file].
+///
+/// The archive is written to a hidden temporary file in `options.temp_directory` and only moved
+/// to `dest`, once it's complete. An interrupted save therefore never leaves a partial archive
+/// behind, which would be listed as a save.
+///
+/// Fails with [InsufficientSpace] without creating anything, if the estimated size of the archive
+/// doesn't fit onto the volume of the temporary file, unless `options.skip_space_check` is set.
+pub fn create_archive(
+    format: ArchiveFormat,
+    cwd: &Path,
+    source_filename: &OsStr,
+    dest: &Path,
+    options: &ArchiveOptions,
+    progress: Progress,
+) -> Result<()> {
+    let Some(file_name) = dest.file_name() else {
+        bail!("Invalid archive path {dest:?}");
+    };
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".part");
+    let temp_path = match options.temp_directory {
+        Some(directory) => directory.join(temp_name),
+        None => dest.with_file_name(temp_name),
+    };
+
+    // A previous save might've been interrupted. Zip would add to its leftovers.
+    if symlink_metadata(&temp_path).is_ok() {
+        remove_file(&temp_path).context(format!("Failed to remove {temp_path:?}"))?;
+    }
+
+    let result = write_archive(format, cwd, source_filename, &temp_path, options, progress)
+        .and_then(|_| move_archive(&temp_path, dest));
+    if result.is_err() && symlink_metadata(&temp_path).is_ok() {
+        remove_file(&temp_path).context(format!("Failed to remove {temp_path:?}"))?;
+    }
+
+    result
+}
+
+/// Move a complete archive to its destination.
+///
+/// Renaming is atomic, but only works on the same volume. Archives from a temporary directory on
+/// another volume are copied next to the destination first and renamed afterwards.
+fn move_archive(archive: &Path, dest: &Path) -> Result<()> {
+    match rename(archive, dest) {
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => (),
+        result => return result.context(format!("Failed to move archive to {dest:?}")),
+    }
+
+    let mut copy_name = OsString::from(".");
+    copy_name.push(dest.file_name().unwrap_or_default());
+    copy_name.push(".part");
+    let copy_path = dest.with_file_name(copy_name);
+    let result = copy(archive, &copy_path)
+        .context(format!("Failed to copy archive to {copy_path:?}"))
+        .and_then(|_| {
+            rename(&copy_path, dest).context(format!("Failed to move archive to {dest:?}"))
+        });
+    if result.is_err() {
+        let _ = remove_file(&copy_path);
+    }
+    result?;
+
+    remove_file(archive).context(format!("Failed to remove {archive:?}"))
+}
+
+/// Write an archive of `cwd/source_filename` to `dest`. See [create_archive].
+fn write_archive(
+    format: ArchiveFormat,
+    cwd: &Path,
+    source_filename: &OsStr,
+    dest: &Path,
+    options: &ArchiveOptions,
+    progress: Progress,
+) -> Result<()> {
+    // Collect all entries up front, which allows us to skip ignored files and to report the
+    // progress. Directories are listed as well, so empty directories are archived too.
+    let mut entries = collect_save_entries(cwd, source_filename, options)?;
+    if let Some(only) = options.only {
+        entries.retain(|path, size| size.is_none() || only.contains(path));
+    }
+    let total: u64 = entries.values().flatten().sum();
+
+    // A full volume would leave a truncated archive and a hard to read archiver error behind.
+    // Compression can't be predicted, so the size of the archive is only estimated.
+    // If the volume can't be checked, the archiver reports any problems itself.
+    if let Some(available) = dest.parent().and_then(|dir| available_space(dir).ok()) {
+        let needed = total / ESTIMATED_COMPRESSION_RATIO + ARCHIVE_OVERHEAD;
+        if needed > available {
+            let error = InsufficientSpace { needed, available };
+            if !options.skip_space_check {
+                return Err(error.into());
+            }
+            warn!("Archive {dest:?} probably doesn't fit: {error}");
+        }
+    }
+
+    let dest = dest.to_string_lossy().into_owned();
+    let (program, mut args): (&str, Vec<String>) = match format {
+        // Store a checksum of the content in each frame, which is validated by [check_archive].
+        ArchiveFormat::TarZst => ("tar", vec!["-I".into(), "zstd --check".into()]),
+        // Gzip stores the current time in its header, unless `-n` is passed.
+        ArchiveFormat::TarGz if options.reproducible => {
+            ("tar", vec!["-I".into(), "gzip -n".into()])
+        }
+        ArchiveFormat::TarGz => ("tar", vec!["-z".into()]),
+        // `-y` stores symlinks as links, just like tar does.
+        ArchiveFormat::Zip if options.follow_symlinks => ("zip", Vec::new()),
+        ArchiveFormat::Zip => ("zip", vec!["-y".into()]),
+    };
+    // The entries are passed via stdin. As all of them are listed, the archivers mustn't recurse.
+    // Tar reads null separated names, zip only supports one name per line.
+    let mut file_list = Vec::new();
+    match format {
+        ArchiveFormat::TarZst | ArchiveFormat::TarGz => {
+            if options.reproducible {
+                args.extend(REPRODUCIBLE_TAR_FLAGS.iter().map(|flag| flag.to_string()));
+            }
+            if options.follow_symlinks {
+                args.push("--dereference".into());
+            }
+            // Tar handles `-C` in the order of the arguments. The extra file is added first and
+            // all other entries are read relative to `cwd` afterwards.
+            if let Some(extra_file) = options.extra_file {
+                let (Some(dir), Some(name)) = (extra_file.parent(), extra_file.file_name()) else {
+                    bail!("Invalid extra file {extra_file:?}");
+                };
+                let cwd = std::path::absolute(cwd).context(format!("Couldn't resolve {cwd:?}"))?;
+                args.extend([
+                    "-C".into(),
+                    dir.to_string_lossy().into_owned(),
+                    name.to_string_lossy().into_owned(),
+                    "-C".into(),
+                    cwd.to_string_lossy().into_owned(),
+                ]);
+            }
+            args.extend([
+                "--no-recursion".into(),
+                "--null".into(),
+                "-T".into(),
+                "-".into(),
+                "-cvf".into(),
+                dest.clone(),
+            ]);
+            for path in entries.keys() {
+                file_list.extend(path.as_os_str().as_bytes());
+                file_list.push(b'\0');
+            }
+        }
+        ArchiveFormat::Zip => {
+            // `-X` strips the uid/gid and extended timestamps.
+            if options.reproducible {
+                args.push("-X".into());
+            }
+            args.extend([dest.clone(), "-@".into()]);
+            for path in entries.keys() {
+                file_list.extend(path.as_os_str().as_bytes());
+                file_list.push(b'\n');
+            }
+        }
+    }
+
+    let mut child = spawn(program, &args, cwd, true)?;
+    let stderr = read_in_background(child.stderr.take());
+    // Write the list in the background, as the archiver starts printing, while it's still
+    // reading the list.
+    let stdin = child.stdin.take();
+    let writer = thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            // A failing archiver is reported via its exit code.
+            let _ = stdin.write_all(&file_list);
+        }
+    });
+
+    // In verbose mode, tar prints the path of each file it's about to archive.
+    // Zip prints lines such as `  adding: saves/save.dat (deflated 90%)`.
+    let mut processed = 0;
+    progress(processed, total);
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines() {
+            let line = line.context(format!("Failed to read {program} output"))?;
+            let path = match format {
+                ArchiveFormat::TarZst | ArchiveFormat::TarGz => line.as_str(),
+                ArchiveFormat::Zip => line
+                    .trim_start()
+                    .strip_prefix("adding: ")
+                    .and_then(|line| line.rsplit_once(" ("))
+                    .map(|(path, _)| path)
+                    .unwrap_or_default(),
+            };
+            processed += entries.get(Path::new(path)).copied().flatten().unwrap_or(0);
+            progress(processed, total);
+        }
+    }
+    let _ = writer.join();
+
+    let status = child
+        .wait()
+        .context(format!("Failed to wait for {program} command"))?;
+    if !status.success() {
+        bail!(
+            "{program} command '{:?}' failed:\nSTDERR:\n{}",
+            args,
+            stderr.join().unwrap_or_default(),
+        )
+    }
+
+    // Zip cannot change its working directory, so the extra file is added in a second run.
+    // `-j` stores it without its directories at the root of the archive.
+    if let (ArchiveFormat::Zip, Some(extra_file)) = (format, options.extra_file) {
+        let mut args = vec!["-j".into()];
+        if options.reproducible {
+            args.push("-X".into());
+        }
+        args.extend([dest.clone(), extra_file.to_string_lossy().into_owned()]);
+        list_command("zip", args)?;
+    }
+
+    // Zip doesn't have an option to override the modification times of its entries.
+    if format == ArchiveFormat::Zip && options.reproducible {
+        reset_zip_times(Path::new(&dest))?;
+    }
+
+    // Archivers might exit successfully, while producing a broken archive.
+    // Make sure the archive can be read and contains all files, before reporting success.
+    let mut files = entries.values().filter(|size| size.is_some()).count();
+    if options.extra_file.is_some() {
+        files += 1;
+    }
+    if let Err(err) = verify_archive(format, Path::new(&dest), files) {
+        remove_file(&dest).context(format!("Failed to remove invalid archive {dest}"))?;
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Set the modification times of all entries of a zip archive to [REPRODUCIBLE_ZIP_DATE] and
+/// [REPRODUCIBLE_ZIP_TIME].
+///
+/// The times are stored in the local header of each entry and again in the central directory at
+/// the end of the archive. Neither of them is covered by the CRCs, which only check the content.
+/// Archives, which are too big for the classic zip format, are left as they are.
+fn reset_zip_times(archive: &Path) -> Result<()> {
+    let context = || format!("Failed to reset the modification times of {archive:?}");
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(archive)
+        .with_context(context)?;
+
+    // The end of central directory record is followed by a comment of up to 64 KiB.
+    let len = file.metadata().with_context(context)?.len();
+    let tail_start = len.saturating_sub(22 + u64::from(u16::MAX));
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(tail_start))
+        .and_then(|_| file.read_to_end(&mut tail))
+        .with_context(context)?;
+    let Some(end) = tail
+        .windows(4)
+        .rposition(|window| window == ZIP_END_SIGNATURE)
+        .filter(|end| tail.len() - end >= 22)
+    else {
+        bail!("{archive:?} doesn't contain a zip central directory");
+    };
+    let entries = read_u16(&tail, end + 10);
+    let directory_size = read_u32(&tail, end + 12);
+    let directory_start = read_u32(&tail, end + 16);
+    if entries == u16::MAX || directory_size == u32::MAX || directory_start == u32::MAX {
+        warn!("Cannot reset the modification times of the zip64 archive {archive:?}");
+        return Ok(());
+    }
+
+    let mut directory = vec![0; directory_size as usize];
+    file.seek(SeekFrom::Start(u64::from(directory_start)))
+        .and_then(|_| file.read_exact(&mut directory))
+        .with_context(context)?;
+    let mut time = [0; 4];
+    time[..2].copy_from_slice(&REPRODUCIBLE_ZIP_TIME.to_le_bytes());
+    time[2..].copy_from_slice(&REPRODUCIBLE_ZIP_DATE.to_le_bytes());
+
+    let mut offset = 0;
+    for _ in 0..entries {
+        let header = directory.get(offset..offset + 46);
+        if header.is_none_or(|header| header[..4] != ZIP_DIRECTORY_SIGNATURE) {
+            bail!("{archive:?} has an invalid zip central directory");
+        }
+        directory[offset + 12..offset + 16].copy_from_slice(&time);
+        let local_header = read_u32(&directory, offset + 42);
+        file.seek(SeekFrom::Start(u64::from(local_header) + 10))
+            .and_then(|_| file.write_all(&time))
+            .with_context(context)?;
+
+        let name_len = usize::from(read_u16(&directory, offset + 28));
+        let extra_len = usize::from(read_u16(&directory, offset + 30));
+        let comment_len = usize::from(read_u16(&directory, offset + 32));
+        offset += 46 + name_len + extra_len + comment_len;
+    }
+    file.seek(SeekFrom::Start(u64::from(directory_start)))
+        .and_then(|_| file.write_all(&directory))
+        .with_context(context)?;
+
+    Ok(())
+}
+
+/// Read a little endian u16 at `offset`.
+fn read_u16(buffer: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buffer[offset], buffer[offset + 1]])
+}
+
+/// Read a little endian u32 at `offset`.
+fn read_u32(buffer: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        buffer[offset],
+        buffer[offset + 1],
+        buffer[offset + 2],
+        buffer[offset + 3],
+    ])
+}
+
+/// Check that an archive can be listed and contains the expected amount of files.
+fn verify_archive(format: ArchiveFormat, archive: &Path, expected_files: usize) -> Result<()> {
+    let files = list_archive(format, archive)
+        .context(format!("Created archive {archive:?} cannot be read"))?;
+    if files.len() != expected_files {
+        bail!(
+            "Created archive {archive:?} contains {} instead of {expected_files} files",
+            files.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Check an archive for corruption.
+///
+/// The compressed data is tested against the checksums of its format, which are zstd's content
+/// checksum, gzip's CRC or the CRCs of all zip entries. Afterwards, the archive has to be
+/// listable.
+pub fn check_archive(format: ArchiveFormat, archive: &Path) -> Result<()> {
+    let path = archive.to_string_lossy().into_owned();
+    let (program, args) = match format {
+        ArchiveFormat::TarZst => ("zstd", vec!["-t".into(), "-q".into(), path]),
+        ArchiveFormat::TarGz => ("gzip", vec!["-t".into(), path]),
+        ArchiveFormat::Zip => ("unzip", vec!["-tqq".into(), path]),
+    };
+    list_command(program, args)?;
+    list_archive(format, archive)?;
+
+    Ok(())
+}
+
+/// Extract an archive into `cwd`.
+pub fn extract_archive(
+    format: ArchiveFormat,
+    archive: &Path,
+    cwd: &Path,
+    progress: Progress,
+) -> Result<()> {
+    match format {
+        ArchiveFormat::TarZst => {
+            let args = vec!["-I".into(), "zstd".into(), "-xf".into(), "-".into()];
+            extract_tar(archive, cwd, args, progress)
+        }
+        ArchiveFormat::TarGz => {
+            extract_tar(archive, cwd, vec!["-xzf".into(), "-".into()], progress)
+        }
+        ArchiveFormat::Zip => extract_zip(archive, cwd, progress),
+    }
+}
+
+/// List all files of an archive with their uncompressed size.
+/// Directories aren't included, as they don't contain any data.
+pub fn list_archive(format: ArchiveFormat, archive: &Path) -> Result<HashMap<String, u64>> {
+    let entries = list_archive_entries(format, archive)?;
+
+    Ok(entries
+        .into_iter()
+        .map(|(name, entry)| (name, entry.size))
+        .collect())
+}
+
+/// List all files of an archive with their uncompressed size and modification time.
+/// Directories aren't included, as they don't contain any data.
+pub fn list_archive_entries(
+    format: ArchiveFormat,
+    archive: &Path,
+) -> Result<HashMap<String, ArchiveEntry>> {
+    let archive = archive.to_string_lossy().into_owned();
+    let files = match format {
+        ArchiveFormat::TarZst => {
+            let args = vec!["-I".into(), "zstd".into(), "-tvf".into(), archive];
+            parse_tar_listing(&list_command("tar", args)?)
+        }
+        ArchiveFormat::TarGz => {
+            parse_tar_listing(&list_command("tar", vec!["-tvzf".into(), archive])?)
+        }
+        ArchiveFormat::Zip => {
+            parse_zip_listing(&list_command("unzip", vec!["-l".into(), archive])?)
+        }
+    };
+
+    Ok(files
+        .into_iter()
+        .filter(|(name, _)| !name.ends_with('/'))
+        .collect())
+}
+
+/// Read a file, which has been stored via [ArchiveOptions::extra_file], from an archive.
+/// Returns `None`, if the archive doesn't contain that file.
+///
+/// Only the first entry of tarballs is checked, which is where the extra file is stored.
+/// That way, only the beginning of a potentially huge archive needs to be decompressed.
+pub fn read_extra_file(
+    format: ArchiveFormat,
+    archive: &Path,
+    name: &str,
+) -> Result<Option<Vec<u8>>> {
+    let archive_str = archive.to_string_lossy().into_owned();
+    let decompressor = match format {
+        ArchiveFormat::TarZst => "zstd",
+        ArchiveFormat::TarGz => "gzip",
+        ArchiveFormat::Zip => return extract_file(format, archive, name),
+    };
+
+    let args = vec!["-d".into(), "-c".into(), archive_str];
+    let mut child = spawn(decompressor, &args, Path::new("."), false)?;
+    let _stderr = read_in_background(child.stderr.take());
+    let stdout = child
+        .stdout
+        .take()
+        .context(format!("Failed to get output of {decompressor} command"))?;
+    let content = read_first_tar_entry(stdout, name)
+        .context(format!("Failed to read the first entry of {archive:?}"));
+
+    // The rest of the archive isn't needed.
+    let _ = child.kill();
+    let _ = child.wait();
+
+    content
+}
+
+/// Extract a single file from an archive and return its content.
+/// Returns `None`, if the archive doesn't contain that file.
+///
+/// Zip archives compress each entry on its own and have an index, so only the requested file is
+/// read and decompressed. Tarballs are compressed as a whole and have to be decompressed up to
+/// the requested file.
+pub fn extract_file(format: ArchiveFormat, archive: &Path, name: &str) -> Result<Option<Vec<u8>>> {
+    let archive = archive.to_string_lossy().into_owned();
+    let (program, args) = match format {
+        ArchiveFormat::TarZst => (
+            "tar",
+            vec![
+                "-I".into(),
+                "zstd".into(),
+                "-xOf".into(),
+                archive,
+                name.into(),
+            ],
+        ),
+        ArchiveFormat::TarGz => ("tar", vec!["-xzOf".into(), archive, name.into()]),
+        ArchiveFormat::Zip => ("unzip", vec!["-p".into(), archive, name.into()]),
+    };
+    let output = spawn(program, &args, Path::new("."), false)?
+        .wait_with_output()
+        .context(format!("Failed to wait for {program} command"))?;
+    if output.status.success() {
+        return Ok(Some(output.stdout));
+    }
+
+    // Unzip exits with 11, if no file matched the name. Tar only reports it on stderr.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let not_found = match format {
+        ArchiveFormat::Zip => output.status.code() == Some(11),
+        ArchiveFormat::TarZst | ArchiveFormat::TarGz => stderr.contains("Not found in archive"),
+    };
+    if not_found {
+        return Ok(None);
+    }
+
+    bail!("{program} command '{args:?}' failed:\nSTDERR:\n{stderr}")
+}
+
+/// Read the content of the first entry of an uncompressed tarball, if it has the given name.
+///
+/// Each entry starts with a 512 byte header. It contains the null terminated name in its first
+/// 100 bytes and the octal size of the entry at byte 124.
+fn read_first_tar_entry(mut tarball: impl Read, name: &str) -> Result<Option<Vec<u8>>> {
+    let mut header = [0; 512];
+    tarball
+        .read_exact(&mut header)
+        .context("Failed to read tar header")?;
+
+    let entry_name = header[..100]
+        .split(|byte| *byte == 0)
+        .next()
+        .unwrap_or_default();
+    if entry_name != name.as_bytes() {
+        return Ok(None);
+    }
+    let size = String::from_utf8_lossy(&header[124..136]);
+    let size = size.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    let size = u64::from_str_radix(size, 8).context(format!("Invalid entry size {size}"))?;
+
+    let mut content = Vec::new();
+    tarball
+        .take(size)
+        .read_to_end(&mut content)
+        .context(format!("Failed to read entry {name}"))?;
+    if content.len() as u64 != size {
+        bail!("Entry {name} is truncated");
+    }
+
+    Ok(Some(content))
+}
+
+/// Recompress a zstd compressed archive at the given level.
+///
+/// The archive is only replaced, if the recompressed archive is smaller and contains exactly
+/// the same data. Returns the size of the archive before and after recompressing it.
+pub fn recompress_zstd(archive: &Path, level: u8) -> Result<(u64, u64)> {
+    let old_size = archive
+        .metadata()
+        .context(format!("Failed to read metadata of {archive:?}"))?
+        .len();
+    let mut temp_path = archive.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let archive_str = archive.to_string_lossy().into_owned();
+    let temp_str = temp_path.to_string_lossy().into_owned();
+
+    // Decompress the archive and directly pipe it into a second zstd process.
+    let cwd = archive.parent().unwrap_or(Path::new("."));
+    let decompress_args = vec!["-d".into(), "-c".into(), archive_str];
+    let mut decompress = spawn("zstd", &decompress_args, cwd, false)?;
+    let decompress_stderr = read_in_background(decompress.stderr.take());
+    let decompressed = decompress
+        .stdout
+        .take()
+        .context("Failed to get output of zstd command")?;
+
+    let compress_args = vec![
+        format!("-{level}"),
+        "--check".into(),
+        "-q".into(),
+        "-f".into(),
+        "-o".into(),
+        temp_str,
+    ];
+    let output = Command::new("zstd")
+        .args(&compress_args)
+        .current_dir(cwd)
+        .stdin(decompressed)
+        .stderr(Stdio::piped())
+        .output()
+        .context(format!(
+            "Failed to spawn zstd command: zstd {compress_args:?}"
+        ))?;
+
+    let decompress_status = decompress
+        .wait()
+        .context("Failed to wait for zstd command")?;
+    if !decompress_status.success() || !output.status.success() {
+        let _ = remove_file(&temp_path);
+        bail!(
+            "Failed to recompress {archive:?}:\nSTDERR:\n{}{}",
+            decompress_stderr.join().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr),
+        )
+    }
+
+    let new_size = temp_path
+        .metadata()
+        .context(format!("Failed to read metadata of {temp_path:?}"))?
+        .len();
+    // Make sure no data got lost, before touching the original archive.
+    let verified = same_zstd_content(archive, &temp_path, cwd);
+    if !matches!(verified, Ok(true)) || new_size >= old_size {
+        remove_file(&temp_path).context(format!("Failed to remove {temp_path:?}"))?;
+        match verified {
+            Ok(true) => return Ok((old_size, old_size)),
+            Ok(false) => bail!("Recompressed archive of {archive:?} differs from the original"),
+            Err(err) => return Err(err.context(format!("Failed to verify {temp_path:?}"))),
+        }
+    }
+
+    // Saves are sorted and rotated by their modification time, which must therefore be kept.
+    let modified = archive
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .context(format!("Failed to read modification time of {archive:?}"))?;
+    File::options()
+        .write(true)
+        .open(&temp_path)
+        .and_then(|file| file.set_modified(modified))
+        .context(format!("Failed to set modification time of {temp_path:?}"))?;
+    rename(&temp_path, archive).context(format!("Failed to replace {archive:?}"))?;
+
+    Ok((old_size, new_size))
+}
+
+/// Decompress two zstd archives and compare their content.
+fn same_zstd_content(first: &Path, second: &Path, cwd: &Path) -> Result<bool> {
+    let mut first_child = spawn(
+        "zstd",
+        &[
+            "-d".into(),
+            "-c".into(),
+            first.to_string_lossy().into_owned(),
+        ],
+        cwd,
+        false,
+    )?;
+    let mut second_child = spawn(
+        "zstd",
+        &[
+            "-d".into(),
+            "-c".into(),
+            second.to_string_lossy().into_owned(),
+        ],
+        cwd,
+        false,
+    )?;
+    let _first_stderr = read_in_background(first_child.stderr.take());
+    let _second_stderr = read_in_background(second_child.stderr.take());
+
+    let mut first_reader = BufReader::new(first_child.stdout.take().context("Missing output")?);
+    let mut second_reader = BufReader::new(second_child.stdout.take().context("Missing output")?);
+
+    let same = loop {
+        let first_buffer = first_reader
+            .fill_buf()
+            .context("Failed to read decompressed archive")?;
+        let second_buffer = second_reader
+            .fill_buf()
+            .context("Failed to read decompressed archive")?;
+        let length = first_buffer.len().min(second_buffer.len());
+        if length == 0 {
+            break first_buffer.is_empty() && second_buffer.is_empty();
+        }
+        if first_buffer[..length] != second_buffer[..length] {
+            break false;
+        }
+        first_reader.consume(length);
+        second_reader.consume(length);
+    };
+
+    // Stop both processes, if we stopped reading early.
+    if !same {
+        let _ = first_child.kill();
+        let _ = second_child.kill();
+        let _ = first_child.wait();
+        let _ = second_child.wait();
+        return Ok(false);
+    }
+
+    let first_status = first_child
+        .wait()
+        .context("Failed to wait for zstd command")?;
+    let second_status = second_child
+        .wait()
+        .context("Failed to wait for zstd command")?;
+    if !(first_status.success() && second_status.success()) {
+        bail!("Failed to decompress archives for comparison");
+    }
+
+    Ok(same)
+}
+
+/// Feed the archive to tar ourselves, so we know how much of it has already been processed.
+fn extract_tar(archive: &Path, cwd: &Path, args: Vec<String>, progress: Progress) -> Result<()> {
+    let mut child = spawn("tar", &args, cwd, true)?;
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let mut file = File::open(archive).context(format!("Failed to open archive {archive:?}"))?;
+    let total = file
+        .metadata()
+        .context(format!("Failed to read metadata of {archive:?}"))?
+        .len();
+    if let Some(mut stdin) = child.stdin.take() {
+        let mut buffer = vec![0; 1024 * 1024];
+        let mut processed = 0;
+        progress(processed, total);
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .context(format!("Failed to read archive {archive:?}"))?;
+            if read == 0 {
+                break;
+            }
+            // Tar exits early on corrupted archives, which results in a broken pipe.
+            // The actual error is reported by tar itself.
+            if stdin.write_all(&buffer[..read]).is_err() {
+                break;
+            }
+            processed += read as u64;
+            progress(processed, total);
+        }
+    }
+
+    let status = child.wait().context("Failed to wait for tar command")?;
+    if !status.success() {
+        bail!(
+            "tar command '{:?}' failed:\nSTDOUT:\n{}\n\nSTDERR:\n{}",
+            args,
+            stdout.join().unwrap_or_default(),
+            stderr.join().unwrap_or_default(),
+        )
+    }
+
+    Ok(())
+}
+
+/// Zip archives cannot be streamed, as the file index is located at the end of the file.
+/// The progress is determined via the listed file sizes instead.
+fn extract_zip(archive: &Path, cwd: &Path, progress: Progress) -> Result<()> {
+    let archive = archive.to_string_lossy().into_owned();
+
+    // Get the size of all files from the archive's listing.
+    let file_sizes = parse_zip_listing(&list_command("unzip", vec!["-l".into(), archive.clone()])?);
+    let total: u64 = file_sizes.values().map(|entry| entry.size).sum();
+
+    // `-o` overwrites existing files without asking.
+    let args = vec!["-o".into(), archive];
+    let mut child = spawn("unzip", &args, cwd, false)?;
+    let stderr = read_in_background(child.stderr.take());
+
+    // Unzip prints lines such as `  inflating: saves/save.dat`.
+    let mut processed = 0;
+    progress(processed, total);
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines() {
+            let line = line.context("Failed to read unzip output")?;
+            if let Some((_, path)) = line.trim().split_once(": ") {
+                processed += file_sizes
+                    .get(path.trim_end())
+                    .map_or(0, |entry| entry.size);
+                progress(processed, total);
+            }
+        }
+    }
+
+    let status = child.wait().context("Failed to wait for unzip command")?;
+    if !status.success() {
+        bail!(
+            "unzip command '{:?}' failed:\nSTDERR:\n{}",
+            args,
+            stderr.join().unwrap_or_default(),
+        )
+    }
+
+    Ok(())
+}
+
+/// Parse the output of `unzip -l` into a map of file names to their entries.
+///
+/// The listing looks like this:
+/// ```text
+///   Length      Date    Time    Name
+/// ---------  ---------- -----   ----
+///      5000  2024-01-01 10:00   saves/save.dat
+/// ---------                     -------
+///      5000                     1 file
+/// ```
+fn parse_zip_listing(listing: &str) -> HashMap<String, ArchiveEntry> {
+    let mut entries = HashMap::new();
+    let mut in_body = false;
+    for line in listing.lines() {
+        if line.starts_with("---") {
+            if in_body {
+                break;
+            }
+            in_body = true;
+            continue;
+        }
+        if !in_body {
+            continue;
+        }
+
+        // Skip the length, date and time columns. The rest is the file name.
+        let mut rest = line.trim_start();
+        let mut columns = Vec::new();
+        for _ in 0..3 {
+            let Some((column, remaining)) = rest.split_once(char::is_whitespace) else {
+                break;
+            };
+            columns.push(column);
+            rest = remaining.trim_start();
+        }
+        if columns.len() == 3 && !rest.is_empty() {
+            let entry = ArchiveEntry {
+                size: columns[0].parse().unwrap_or(0),
+                modified: parse_listed_time(columns[1], columns[2]),
+            };
+            entries.insert(rest.to_string(), entry);
+        }
+    }
+
+    entries
+}
+
+/// Parse the output of `tar -tv` into a map of file names to their entries.
+///
+/// The listing looks like this:
+/// ```text
+/// drwxr-xr-x user/group         0 2024-01-01 10:00 saves/
+/// -rw-r--r-- user/group      5000 2024-01-01 10:00 saves/save.dat
+/// ```
+fn parse_tar_listing(listing: &str) -> HashMap<String, ArchiveEntry> {
+    let mut entries = HashMap::new();
+    for line in listing.lines() {
+        // Skip the permission, owner, size, date and time columns. The rest is the file name.
+        let mut rest = line.trim_start();
+        let mut columns = Vec::new();
+        for _ in 0..5 {
+            let Some((column, remaining)) = rest.split_once(char::is_whitespace) else {
+                break;
+            };
+            columns.push(column);
+            rest = remaining.trim_start();
+        }
+        if columns.len() != 5 || rest.is_empty() {
+            continue;
+        }
+
+        // Directories are listed with a trailing slash, just like in zip archives.
+        let mut name = rest.to_string();
+        if columns[0].starts_with('d') && !name.ends_with('/') {
+            name.push('/');
+        }
+        // Symlinks are listed as `link -> target`.
+        if columns[0].starts_with('l') {
+            if let Some((link, _)) = name.split_once(" -> ") {
+                name = link.to_string();
+            }
+        }
+        let entry = ArchiveEntry {
+            size: columns[2].parse().unwrap_or(0),
+            modified: parse_listed_time(columns[3], columns[4]),
+        };
+        entries.insert(name, entry);
+    }
+
+    entries
+}
+
+/// Parse the date and time columns of an archive listing.
+/// Some versions of unzip list the date in the US format.
+fn parse_listed_time(date: &str, time: &str) -> Option<NaiveDateTime> {
+    let listed = format!("{date} {time}");
+    ["%Y-%m-%d %H:%M", "%m-%d-%Y %H:%M", "%m-%d-%y %H:%M"]
+        .into_iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&listed, format).ok())
+}
+
+/// Run a command that lists the content of an archive and return its output.
+fn list_command(program: &str, args: Vec<String>) -> Result<String> {
+    let output = spawn(program, &args, Path::new("."), false)?
+        .wait_with_output()
+        .context(format!("Failed to wait for {program} command"))?;
+    if !output.status.success() {
+        bail!(
+            "{program} command '{:?}' failed:\nSTDERR:\n{}",
+            args,
+            String::from_utf8_lossy(&output.stderr),
+        )
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Spawn an archive command with piped output.
+fn spawn(program: &str, args: &[String], cwd: &Path, pipe_stdin: bool) -> Result<Child> {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .current_dir(cwd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if pipe_stdin {
+        command.stdin(Stdio::piped());
+    }
+
+    command.spawn().context(format!(
+        "Failed to spawn {program} command: {program} {args:?}"
+    ))
+}
+
+/// Collect all entries of `cwd/source_filename` and its existing `options.siblings`, which
+/// aren't ignored, and their size. See [collect_entries].
+pub(crate) fn collect_save_entries(
+    cwd: &Path,
+    source_filename: &OsStr,
+    options: &ArchiveOptions,
+) -> Result<BTreeMap<PathBuf, Option<u64>>> {
+    let ignore = build_ignore(&cwd.join(source_filename), options.ignored_files)?;
+    let mut entries = BTreeMap::new();
+    collect_entries(
+        cwd,
+        Path::new(source_filename),
+        &ignore,
+        options.follow_symlinks,
+        &mut Vec::new(),
+        &mut entries,
+    )?;
+
+    for sibling in options.siblings {
+        if symlink_metadata(cwd.join(sibling)).is_err() {
+            continue;
+        }
+        collect_entries(
+            cwd,
+            Path::new(sibling),
+            &Gitignore::empty(),
+            options.follow_symlinks,
+            &mut Vec::new(),
+            &mut entries,
+        )?;
+    }
+
+    Ok(entries)
+}
+
+/// Recursively collect all entries at `cwd/path`, which aren't ignored, and their size.
+/// The paths are stored relative to `cwd`, which is also how the archivers print them.
+/// Directories don't have a size.
+///
+/// `visited` contains the canonical paths of all directories on the way to `path`.
+/// If a followed symlink leads back to one of them, the walk would never end.
+pub(crate) fn collect_entries(
+    cwd: &Path,
+    path: &Path,
+    ignore: &Gitignore,
+    follow_symlinks: bool,
+    visited: &mut Vec<PathBuf>,
+    entries: &mut BTreeMap<PathBuf, Option<u64>>,
+) -> Result<()> {
+    let full_path = cwd.join(path);
+    let metadata = if follow_symlinks {
+        metadata(&full_path)
+    } else {
+        symlink_metadata(&full_path)
+    }
+    .context(format!("Couldn't read metadata of {full_path:?}"))?;
+    if ignore.matched(&full_path, metadata.is_dir()).is_ignore() {
+        return Ok(());
+    }
+
+    if metadata.is_dir() {
+        if follow_symlinks {
+            let target = full_path
+                .canonicalize()
+                .context(format!("Couldn't resolve {full_path:?}"))?;
+            if visited.contains(&target) {
+                bail!("Symlink loop: {full_path:?} leads back to the parent directory {target:?}");
+            }
+            visited.push(target);
+        }
+        entries.insert(path.to_path_buf(), None);
+        let dir_files =
+            read_dir(&full_path).context(format!("Couldn't read directory {full_path:?}"))?;
+        for dir_entry in dir_files {
+            let dir_entry =
+                dir_entry.context(format!("Couldn't get dir entry in {full_path:?}"))?;
+            let path = path.join(dir_entry.file_name());
+            collect_entries(cwd, &path, ignore, follow_symlinks, visited, entries)?;
+        }
+        if follow_symlinks {
+            visited.pop();
+        }
+    } else {
+        entries.insert(path.to_path_buf(), Some(metadata.len()));
+    }
+
+    Ok(())
+}
+
+/// Read a child's output pipe in a separate thread.
+/// This prevents the child from blocking on a full pipe, while we're busy with something else.
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut output);
+        }
+        output
+    })
+}
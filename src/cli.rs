@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[clap(
@@ -18,4 +18,101 @@ pub struct CliArguments {
     /// You can explicitly specify a configuration path.
     /// Otherwise the default path in "~/.local/share" will be used.
     pub config: Option<PathBuf>,
+
+    /// The directory for runtime files that aren't backups, such as the state of the last session.
+    /// Overrides the `data_directory` of the config.
+    #[clap(long)]
+    pub data_directory: Option<PathBuf>,
+
+    /// Periodically write the current time into this file while game-saver is running.
+    /// Process supervisors can use this file to detect a hung instance.
+    /// The file isn't updated anymore, once the file watcher of any game stopped.
+    #[clap(long)]
+    pub health_file: Option<PathBuf>,
+
+    /// Log every detected file change and the reason why it did or didn't trigger an autosave.
+    /// This helps with tuning the `ignored_files` globs.
+    #[clap(long)]
+    pub debug_events: bool,
+
+    /// Append structured events, such as created or restored saves, to this file.
+    /// Each event is a single line of JSON.
+    #[clap(long)]
+    pub events_file: Option<PathBuf>,
+
+    /// Write the same events as `--events-file` to this unix socket.
+    /// The socket has to be created by the listener, before game-saver is started.
+    #[clap(long, conflicts_with = "events_file")]
+    pub events_socket: Option<PathBuf>,
+
+    /// Validate the config and exit.
+    /// Prints either "OK" or a list of all found problems.
+    #[clap(long)]
+    pub config_check: bool,
+
+    #[clap(subcommand)]
+    pub cmd: Option<SubCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SubCommand {
+    /// Recompress old autosaves at a higher compression level to save space.
+    /// Only saves in the `tar_zst` format are recompressed.
+    Compact {
+        /// Only recompress autosaves that're older than this many days.
+        #[clap(long, default_value_t = 7)]
+        older_than_days: u64,
+
+        /// The zstd compression level (1-19).
+        #[clap(long, default_value_t = 19, value_parser = clap::value_parser!(u8).range(1..=19))]
+        level: u8,
+    },
+
+    /// Pack all saves of a game into a single archive, e.g. to move them to another machine.
+    /// The format is determined by the extension of the output file.
+    Export {
+        /// The name of the game in the config.
+        #[clap(long)]
+        game: String,
+
+        /// The archive the saves are written to, e.g. `saves.tar.zst`.
+        #[clap(long)]
+        out: PathBuf,
+
+        /// Include the autosaves as well.
+        /// By default, only the manual saves are exported to keep the archive small.
+        #[clap(long, alias = "autosaves")]
+        include_autosaves: bool,
+    },
+
+    /// Import the saves of an archive that has been created by `export`.
+    /// Saves with the same name as an existing save are skipped.
+    Import {
+        /// The name of the game in the config, the saves are imported into.
+        #[clap(long)]
+        game: String,
+
+        /// The archive that has been created by `export`.
+        file: PathBuf,
+    },
+
+    /// Check all saves of all games for corruption and print a summary for each game.
+    /// Exits with 1, if any corrupt save has been found.
+    Verify,
+
+    /// Delete the backup directories of games that have been removed from the config.
+    /// All found directories are listed and only deleted after confirmation.
+    Clean {
+        /// Delete the directories without asking.
+        #[clap(long)]
+        yes: bool,
+    },
+
+    /// Create autosaves in the background without the terminal ui.
+    /// Pending autosaves are created, before the daemon exits on SIGTERM or SIGINT.
+    Daemon {
+        /// Write the log into this file instead of stderr.
+        #[clap(long)]
+        log_file: Option<PathBuf>,
+    },
 }
@@ -1,33 +1,296 @@
-use std::{collections::HashMap, fs::File, io::prelude::*, path::PathBuf};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fmt::Write,
+    fs::{read_to_string, write, File},
+    io::prelude::*,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, TimeZone};
+use cron::Schedule;
+use globset::Glob;
+use nix::unistd::{access, AccessFlags};
 use serde_derive::{Deserialize, Serialize};
 use shellexpand::tilde;
 
-static DEFAULT_CONFIG: &str = include_str!("../example_game_saver.toml");
+use crate::{archive::ArchiveOptions, engine::AUTOSAVE_DEBOUNCE};
+
+/// The kinds of file events that can trigger an autosave.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerEvent {
+    Create,
+    Modify,
+    Rename,
+    Remove,
+    Metadata,
+    Access,
+}
+
+/// The name of autosaves, if no `autosave_name_template` is configured.
+pub const DEFAULT_AUTOSAVE_NAME_TEMPLATE: &str = "autosave_%Y-%m-%d_%H-%M-%S";
+
+/// The placeholder in the `autosave_name_template`, which is replaced by an increasing number.
+pub const AUTOSAVE_COUNTER: &str = "{counter}";
+
+/// The category of all games, which don't have a `category`.
+pub const DEFAULT_CATEGORY: &str = "Games";
+
+fn default_trigger_on() -> Vec<TriggerEvent> {
+    vec![
+        TriggerEvent::Create,
+        TriggerEvent::Modify,
+        TriggerEvent::Rename,
+    ]
+}
+
+/// The archive formats that can be used to store saves.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    /// A zstd compressed tarball.
+    #[default]
+    TarZst,
+    /// A gzip compressed tarball.
+    TarGz,
+    /// A zip archive, which can be opened by most file managers.
+    /// Its files are compressed one by one, so single files can be read without decompressing
+    /// the whole archive. See [crate::saves::read_save_file].
+    Zip,
+}
+
+/// How autosaves of a game are stored.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupStrategy {
+    /// Every autosave contains all files.
+    #[default]
+    Full,
+    /// Autosaves only contain the files that changed since the last full autosave.
+    /// Restoring them requires that full autosave, which is kept as long as it's needed.
+    Incremental,
+}
+
+/// Which autosaves are kept, once a new autosave is created.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionPolicy {
+    /// Only keep the newest `autosaves` autosaves.
+    #[default]
+    Count,
+    /// Keep the newest `autosaves` autosaves and thin out the older ones.
+    /// Of those, the newest autosave of each of the last `hourly` hours, `daily` days and
+    /// `weekly` weeks, in which autosaves have been created, is kept.
+    Tiered {
+        #[serde(default)]
+        hourly: usize,
+        #[serde(default)]
+        daily: usize,
+        #[serde(default)]
+        weekly: usize,
+    },
+}
+
+impl ArchiveFormat {
+    pub const ALL: [ArchiveFormat; 3] = [
+        ArchiveFormat::TarZst,
+        ArchiveFormat::TarGz,
+        ArchiveFormat::Zip,
+    ];
+
+    /// The file extension of this format, including the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::TarZst => ".tar.zst",
+            ArchiveFormat::TarGz => ".tar.gz",
+            ArchiveFormat::Zip => ".zip",
+        }
+    }
+
+    /// Detect the archive format of a file by its extension.
+    pub fn from_path(path: &Path) -> Result<ArchiveFormat> {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+
+        ArchiveFormat::split_file_name(&file_name)
+            .map(|(_, format)| format)
+            .ok_or_else(|| anyhow!("Unknown archive format of file {path:?}"))
+    }
+
+    /// Split a file name into the name of the save and its archive format.
+    /// Returns `None`, if the file name doesn't have a known archive extension.
+    pub fn split_file_name(file_name: &str) -> Option<(&str, ArchiveFormat)> {
+        ArchiveFormat::ALL.into_iter().find_map(|format| {
+            file_name
+                .strip_suffix(format.extension())
+                .filter(|name| !name.is_empty())
+                .map(|name| (name, format))
+        })
+    }
+}
 
 /// The config for one game
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct GameConfig {
+    /// The name that's shown in the ui and in messages instead of the game's key.
+    /// The key is still used for the game's directories and on the commandline.
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// The header this entry is listed under, e.g. "Configs" for dotfiles that aren't games.
+    /// Entries without a category are listed under [DEFAULT_CATEGORY].
+    #[serde(default)]
+    pub category: Option<String>,
     /// The folder where the save files are located.
     pub savegame_location: String,
+    /// Restore saves into this location instead of the `savegame_location`.
+    /// The `savegame_location` is still the one that's watched and saved.
+    #[serde(default)]
+    pub restore_location: Option<String>,
+    /// Store the saves of this game in this directory instead of the global `backup_directory`.
+    /// E.g. to keep the saves of a huge game on a larger drive.
+    #[serde(default)]
+    pub backup_directory: Option<String>,
     /// The amount of autosave slots you want to keep.
     /// Once this limit is reached, the oldest autosave files will be deleted.
     ///
     /// Set to 0, if you want to disable.
     pub autosaves: usize,
-    /// By default, game-saver saves the game everytime something changes on disk.
-    /// As this can be quite often, you can specify a timeout up to which all changes on disk will
-    /// be simply ignored.
+    /// Which older autosaves are kept in addition to the newest `autosaves` ones.
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// The cooldown after an autosave in seconds, which is the minimum time between two
+    /// autosaves. Changes during the cooldown aren't lost, they're covered by a single autosave
+    /// once the cooldown is over.
     ///
-    /// The timeout is specified in seconds.
-    /// Set to 0, to disable the timeout.
+    /// Set to 0, to disable the cooldown.
     pub autosave_timeout: usize,
+    /// The time in seconds, for which the savegame location mustn't change, before an autosave
+    /// is created. Each change restarts this period, so saves aren't archived while the game is
+    /// still writing them.
+    #[serde(default = "default_autosave_debounce")]
+    pub autosave_debounce: usize,
     /// A list of glob patterns that should be ignored.
     /// The paths should be relative to `savegame_location/`.
+    /// Ignored files don't trigger autosaves, aren't included in any save and are left untouched
+    /// when restoring a save.
     ///
-    /// `.ignore` Files will also be respected.
+    /// `.gitignore` and `.ignore` files at the top of the savegame location are respected the
+    /// same way. These globs take precedence over them.
     pub ignored_files: Vec<String>,
+    /// The names of files and directories next to the savegame location, which are saved and
+    /// restored together with it, e.g. a config file that the saves depend on.
+    /// Changes of these don't trigger autosaves and the `ignored_files` don't apply to them.
+    #[serde(default)]
+    pub include_siblings: Vec<String>,
+    /// The maximum amount of manual saves you want to keep.
+    /// Once this limit is reached, you'll be asked to delete the oldest manual saves.
+    #[serde(default)]
+    pub max_manual_saves: Option<usize>,
+    /// Don't ask before deleting the oldest manual saves, once `max_manual_saves` is reached.
+    #[serde(default)]
+    pub auto_delete_manual_saves: bool,
+    /// Restore the latest autosave with `CTRL+r` right away, without asking first.
+    #[serde(default)]
+    pub quick_restore_without_prompt: bool,
+    /// Don't create saves, while the savegame location doesn't contain any files.
+    #[serde(default)]
+    pub skip_empty_saves: bool,
+    /// Create an autosave every few minutes, regardless of whether any files changed.
+    #[serde(default)]
+    pub autosave_interval_minutes: Option<u64>,
+    /// A cron expression, on which an autosave is created regardless of any file changes.
+    /// These scheduled autosaves are kept apart from all other autosaves.
+    #[serde(default)]
+    pub autosave_schedule: Option<String>,
+    /// The amount of scheduled autosaves you want to keep.
+    #[serde(default = "default_scheduled_autosaves")]
+    pub scheduled_autosaves: usize,
+    /// The kinds of file events that trigger an autosave.
+    #[serde(default = "default_trigger_on")]
+    pub trigger_on: Vec<TriggerEvent>,
+    /// The format that's used for new saves of this game.
+    #[serde(default)]
+    pub archive_format: ArchiveFormat,
+    /// Whether autosaves contain all files or only the ones that changed.
+    #[serde(default)]
+    pub backup_strategy: BackupStrategy,
+    /// Strip all metadata from archives, so identical files result in identical archives.
+    #[serde(default)]
+    pub reproducible_archives: bool,
+    /// Save the files and directories that symlinks point to instead of the links themselves.
+    /// Restoring such a save replaces the links with copies of their targets.
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// A command that's executed after each successful save.
+    #[serde(default)]
+    pub post_save_command: Option<String>,
+    /// A command that's executed before a save is restored.
+    #[serde(default)]
+    pub pre_restore_command: Option<String>,
+    /// Cancel the restore, if the `pre_restore_command` fails.
+    #[serde(default)]
+    pub abort_restore_on_hook_failure: bool,
+    /// The name of the game's process.
+    /// If it's running, the user has to confirm restores.
+    #[serde(default)]
+    pub process_name: Option<String>,
+    /// Create an autosave, once the game's process starts.
+    #[serde(default)]
+    pub autosave_on_launch: bool,
+    /// Create an autosave, once the game's process exits.
+    #[serde(default)]
+    pub autosave_on_exit: bool,
+    /// The command that launches the game, which is run with `sh -c`.
+    /// Used by the "restore and launch" action.
+    #[serde(default)]
+    pub launch_command: Option<String>,
+    /// The name of new autosaves.
+    /// Supports strftime tokens and a `{counter}` placeholder.
+    #[serde(default)]
+    pub autosave_name_template: Option<String>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            display_name: None,
+            category: None,
+            savegame_location: String::new(),
+            restore_location: None,
+            backup_directory: None,
+            autosaves: 0,
+            retention: RetentionPolicy::default(),
+            autosave_timeout: 0,
+            autosave_debounce: default_autosave_debounce(),
+            ignored_files: Vec::new(),
+            include_siblings: Vec::new(),
+            max_manual_saves: None,
+            auto_delete_manual_saves: false,
+            quick_restore_without_prompt: false,
+            skip_empty_saves: false,
+            autosave_interval_minutes: None,
+            autosave_schedule: None,
+            scheduled_autosaves: default_scheduled_autosaves(),
+            trigger_on: default_trigger_on(),
+            archive_format: ArchiveFormat::default(),
+            backup_strategy: BackupStrategy::default(),
+            reproducible_archives: false,
+            follow_symlinks: false,
+            post_save_command: None,
+            pre_restore_command: None,
+            abort_restore_on_hook_failure: false,
+            process_name: None,
+            autosave_on_launch: false,
+            autosave_on_exit: false,
+            launch_command: None,
+            autosave_name_template: None,
+        }
+    }
 }
 
 impl GameConfig {
@@ -35,45 +298,250 @@ impl GameConfig {
         PathBuf::from(tilde(&self.savegame_location).into_owned())
     }
 
+    /// The location saves are restored into.
+    /// This is the `savegame_location`, unless a separate `restore_location` is configured.
+    pub fn restore_location(&self) -> PathBuf {
+        match &self.restore_location {
+            Some(location) => PathBuf::from(tilde(location).into_owned()),
+            None => self.savegame_location(),
+        }
+    }
+
     pub fn has_autosaves(&self) -> bool {
         self.autosaves != 0
     }
+
+    /// Parse the `autosave_schedule`, if there's one.
+    /// Both the common five fields and the extended format with seconds are supported.
+    pub fn schedule(&self) -> Result<Option<Schedule>> {
+        let Some(expression) = &self.autosave_schedule else {
+            return Ok(None);
+        };
+        let expression = expression.trim();
+        let schedule = if expression.split_whitespace().count() == 5 {
+            Schedule::from_str(&format!("0 {expression}"))
+        } else {
+            Schedule::from_str(expression)
+        }
+        .context(format!("Invalid cron expression '{expression}'"))?;
+
+        Ok(Some(schedule))
+    }
+
+    /// The options that're used to archive the savegame location of this game.
+    pub fn archive_options(&self) -> ArchiveOptions<'_> {
+        ArchiveOptions {
+            reproducible: self.reproducible_archives,
+            ignored_files: &self.ignored_files,
+            siblings: &self.include_siblings,
+            follow_symlinks: self.follow_symlinks,
+            ..Default::default()
+        }
+    }
+
+    pub fn autosave_name_template(&self) -> &str {
+        self.autosave_name_template
+            .as_deref()
+            .unwrap_or(DEFAULT_AUTOSAVE_NAME_TEMPLATE)
+    }
+
+    /// Replace all strftime tokens of the autosave name template.
+    /// The `{counter}` placeholder is left as it is.
+    pub fn format_autosave_name(&self, time: &DateTime<Local>) -> Result<String> {
+        let template = self.autosave_name_template();
+        let mut name = String::new();
+        write!(name, "{}", time.format(template))
+            .map_err(|_| anyhow!("Invalid strftime token in template '{template}'"))?;
+
+        Ok(name)
+    }
+
+    /// Make sure the autosave name template results in a new name for each autosave.
+    /// Without a counter, the name has to change at least every second.
+    fn validate_autosave_name_template(&self) -> Result<()> {
+        let first = Local.timestamp_opt(0, 0).unwrap();
+        let name = self.format_autosave_name(&first)?;
+        if name.contains('/') {
+            bail!("must not contain '/'");
+        }
+        if name.contains(AUTOSAVE_COUNTER) {
+            return Ok(());
+        }
+
+        let second = Local.timestamp_opt(1, 0).unwrap();
+        if name == self.format_autosave_name(&second)? {
+            bail!("must contain {AUTOSAVE_COUNTER} or a strftime token for the seconds, e.g. %S");
+        }
+
+        Ok(())
+    }
+}
+
+/// The proportions of the lists on the right side of the screen.
+/// Each value is a weight relative to the sum of all weights.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LayoutConfig {
+    pub autosaves: u32,
+    pub saves: u32,
+    pub event_log: u32,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            autosaves: 1,
+            saves: 1,
+            event_log: 1,
+        }
+    }
+}
+
+impl LayoutConfig {
+    /// Make sure the weights can be used to build a layout.
+    pub fn validate(&self) -> Result<()> {
+        if self.autosaves == 0 || self.saves == 0 || self.event_log == 0 {
+            bail!("All weights in the [layout] section must be greater than 0");
+        }
+
+        Ok(())
+    }
+}
+
+fn default_trash_retention_days() -> u64 {
+    30
+}
+
+fn default_min_free_space_mib() -> u64 {
+    100
+}
+
+fn default_mouse_capture() -> bool {
+    true
+}
+
+fn default_autosave_debounce() -> usize {
+    AUTOSAVE_DEBOUNCE as usize
+}
+
+fn default_scheduled_autosaves() -> usize {
+    7
+}
+
+fn default_key_repeat_limit() -> usize {
+    1
+}
+
+fn default_idle_redraw_seconds() -> u64 {
+    30
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Config {
     /// The directory where Game-saver will store the backups of your games' save files.
     pub backup_directory: String,
+    /// The proportions of the save lists and the event log.
+    #[serde(default)]
+    pub layout: LayoutConfig,
+    /// Deleted saves are permanently removed from the trash after this many days.
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u64,
+    /// Autosaves are skipped, once the free space on the backup volume drops below this many MiB.
+    #[serde(default = "default_min_free_space_mib")]
+    pub min_free_space_mib: u64,
+    /// Show desktop notifications for autosaves, restores and failed saves.
+    #[serde(default)]
+    pub desktop_notifications: bool,
+    /// Capture mouse events in the terminal ui.
+    /// Disable this to select and copy text with the mouse, as in any other terminal program.
+    #[serde(default = "default_mouse_capture")]
+    pub mouse_capture: bool,
+    /// How often a held navigation key is applied between two draws of the terminal ui.
+    /// Further repeats, which piled up while drawing, are dropped, so lists don't move past the
+    /// intended entry once the key is released. Set to 0 to apply all repeats.
+    #[serde(default = "default_key_repeat_limit")]
+    pub key_repeat_limit: usize,
+    /// Redraw the terminal ui after this many seconds without any other redraw, so relative
+    /// times such as "3m ago" stay current. Set to 0 to only redraw on changes.
+    #[serde(default = "default_idle_redraw_seconds")]
+    pub idle_redraw_seconds: u64,
+    /// The directory for runtime files that aren't backups, such as the state of the last session.
+    /// Defaults to the XDG data directory (`~/.local/share`).
+    #[serde(default)]
+    pub data_directory: Option<String>,
+    /// The directory, in which new archives are written, before they're moved to the saves.
+    /// Defaults to the directory of the saves, which is on the same volume.
+    #[serde(default)]
+    pub temp_directory: Option<String>,
+    /// A missing `[games]` table is shown as an empty config in the terminal ui.
+    #[serde(default)]
     pub games: HashMap<String, GameConfig>,
 }
 
 impl Config {
-    /// Either get the config from an existing configuration file or
-    /// create a new one from scratch
+    /// Read the config from an existing configuration file.
     pub fn new(path: &Option<PathBuf>) -> Result<Self> {
-        let path = if let Some(path) = path {
-            path.clone()
-        } else {
-            Config::get_config_path()?
-        };
+        let path = Config::path(path)?;
+
+        let mut file = File::open(&path).context(format!("Couldn't open config at {path:?}"))?;
+        let mut config = String::new();
+        file.read_to_string(&mut config)
+            .context(format!("Couldn't read config at {path:?}"))?;
 
-        // The config file exists. Try to parse it
-        if path.exists() {
-            let mut file = File::open(path)?;
-            let mut config = String::new();
-            file.read_to_string(&mut config)?;
+        let config: Config =
+            toml::from_str(&config).context(format!("Couldn't parse config at {path:?}"))?;
+        Ok(config)
+    }
 
-            let config: Config = toml::from_str(&config)?;
-            return Ok(config);
+    /// Get the path of the config file.
+    /// Either the explicitly specified path or the default path is used.
+    pub fn path(path: &Option<PathBuf>) -> Result<PathBuf> {
+        if let Some(path) = path {
+            Ok(path.clone())
+        } else {
+            Config::get_config_path()
         }
+    }
 
-        // No config exists yet. Create a default config and persist it onto disk.
-        let mut file = File::create(&path)?;
-        file.write_all(DEFAULT_CONFIG.as_bytes())?;
+    /// Change the `savegame_location` of a game in the config file at `path`.
+    /// Only that value is replaced, the comments and formatting of the file are kept.
+    pub fn update_savegame_location(path: &Path, game: &str, location: &str) -> Result<()> {
+        Config::update_value(
+            path,
+            Some(game),
+            "savegame_location",
+            toml_edit::value(location),
+        )
+    }
 
-        // Recursively load config, now that we made sure it exists.
-        let config = Config::new(&Some(path))?;
-        Ok(config)
+    /// Replace a single value in the config file at `path`.
+    /// The value belongs to the given game or is a global one, if no game is given.
+    /// Only that value is replaced, the comments and formatting of the file are kept.
+    pub fn update_value(
+        path: &Path,
+        game: Option<&str>,
+        key: &str,
+        value: toml_edit::Item,
+    ) -> Result<()> {
+        let content = read_to_string(path).context(format!("Couldn't read config at {path:?}"))?;
+        let mut document: toml_edit::DocumentMut = content
+            .parse()
+            .context(format!("Couldn't parse config at {path:?}"))?;
+
+        let Some(game) = game else {
+            document[key] = value;
+            return write(path, document.to_string())
+                .context(format!("Failed to write config to {path:?}"));
+        };
+        let Some(game_config) = document
+            .get_mut("games")
+            .and_then(|games| games.as_table_like_mut())
+            .and_then(|games| games.get_mut(game))
+        else {
+            bail!("Couldn't find game {game} in config at {path:?}");
+        };
+        game_config[key] = value;
+
+        write(path, document.to_string()).context(format!("Failed to write config to {path:?}"))
     }
 
     pub fn get_config_path() -> Result<PathBuf> {
@@ -81,17 +549,344 @@ impl Config {
         Ok(config_dir.join("game_saver.toml"))
     }
 
+    /// Get the config of a specific game.
+    /// Returns an error, if the game doesn't exist in the config.
+    pub fn game(&self, name: &str) -> Result<&GameConfig> {
+        self.games
+            .get(name)
+            .ok_or_else(|| anyhow!("Couldn't find game {name} in config"))
+    }
+
+    /// Check the config for problems that would prevent normal operation.
+    pub fn validate(&self) -> Result<()> {
+        let errors = self.errors();
+        if !errors.is_empty() {
+            bail!("Found problems in config:\n{}", errors.join("\n"));
+        }
+
+        Ok(())
+    }
+
+    /// Collect all problems that prevent normal operation.
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Err(err) = self.layout.validate() {
+            errors.push(err.to_string());
+        }
+
+        if self.backup_directory.trim().is_empty() {
+            errors.push("The backup directory must not be empty".into());
+        }
+        if self
+            .data_directory
+            .as_ref()
+            .is_some_and(|directory| directory.trim().is_empty())
+        {
+            errors.push("The data directory must not be empty".into());
+        }
+        if self
+            .temp_directory
+            .as_ref()
+            .is_some_and(|directory| directory.trim().is_empty())
+        {
+            errors.push("The temp directory must not be empty".into());
+        }
+
+        // The watcher would pick up our own backups, if a backup directory and a savegame
+        // location are nested in each other. This would result in an endless autosave loop.
+        // This applies to the backup directories of all games, not only the game's own one.
+        let mut backup_dirs = vec![normalize_path(&self.backup_directory())];
+        // New archives are written into the temp directory first, which triggers the watcher just
+        // the same.
+        if let Some(directory) = self.temp_directory() {
+            backup_dirs.push(normalize_path(&directory));
+        }
+        for (name, game_config) in self.sorted_games() {
+            let Some(directory) = &game_config.backup_directory else {
+                continue;
+            };
+            if directory.trim().is_empty() {
+                errors.push(format!("backup_directory of game {name} must not be empty"));
+                continue;
+            }
+            let backup_dir = normalize_path(&self.game_backup_directory(name));
+            if !is_writable(&backup_dir) {
+                errors.push(format!(
+                    "The backup directory of game {name} ({backup_dir:?}) isn't writable"
+                ));
+            }
+            if !backup_dirs.contains(&backup_dir) {
+                backup_dirs.push(backup_dir);
+            }
+        }
+
+        for (name, game_config) in self.sorted_games() {
+            if let Err(err) = validate_game_name(name) {
+                errors.push(format!("Invalid game name '{name}': {err}"));
+            }
+            if game_config
+                .display_name
+                .as_ref()
+                .is_some_and(|display_name| display_name.trim().is_empty())
+            {
+                errors.push(format!("display_name of game {name} must not be empty"));
+            }
+            if game_config
+                .category
+                .as_ref()
+                .is_some_and(|category| category.trim().is_empty())
+            {
+                errors.push(format!("category of game {name} must not be empty"));
+            }
+            if game_config.max_manual_saves == Some(0) {
+                errors.push(format!(
+                    "max_manual_saves of game {name} must be greater than 0"
+                ));
+            }
+
+            for glob in game_config.ignored_files.iter() {
+                if let Err(err) = Glob::new(glob) {
+                    errors.push(format!("Invalid ignore glob of game {name}: {err}"));
+                }
+            }
+
+            if let Err(err) = game_config.validate_autosave_name_template() {
+                errors.push(format!(
+                    "Invalid autosave_name_template of game {name}: {err}"
+                ));
+            }
+
+            if let Err(err) = game_config.schedule() {
+                errors.push(format!("Invalid autosave_schedule of game {name}: {err:#}"));
+            }
+            if game_config.autosave_schedule.is_some() && game_config.scheduled_autosaves == 0 {
+                errors.push(format!(
+                    "scheduled_autosaves of game {name} must be greater than 0"
+                ));
+            }
+            // The process of the game is needed to detect, when it's launched or exits.
+            let has_process = game_config
+                .process_name
+                .as_ref()
+                .is_some_and(|process| !process.trim().is_empty());
+            if (game_config.autosave_on_launch || game_config.autosave_on_exit) && !has_process {
+                errors.push(format!(
+                    "autosave_on_launch and autosave_on_exit of game {name} require a process_name"
+                ));
+            }
+
+            let savegame_location = normalize_path(&game_config.savegame_location());
+            for backup_dir in backup_dirs.iter() {
+                if savegame_location.starts_with(backup_dir)
+                    || backup_dir.starts_with(&savegame_location)
+                {
+                    errors.push(format!(
+                        "The savegame location of game {name} ({savegame_location:?}) and the \
+                        backup directory ({backup_dir:?}) must not be nested in each other."
+                    ));
+                }
+            }
+
+            // Siblings are archived relative to the parent of the savegame location, just like
+            // the savegame location itself.
+            for sibling in game_config.include_siblings.iter() {
+                let is_name = !matches!(sibling.trim(), "" | "." | "..") && !sibling.contains('/');
+                if !is_name || savegame_location.file_name() == Some(OsStr::new(sibling)) {
+                    errors.push(format!(
+                        "include_siblings of game {name} must only contain names of other files \
+                        and directories next to the savegame location, found '{sibling}'"
+                    ));
+                    continue;
+                }
+                let sibling = savegame_location.with_file_name(sibling);
+                if backup_dirs
+                    .iter()
+                    .any(|backup_dir| backup_dir.starts_with(&sibling))
+                {
+                    errors.push(format!(
+                        "The sibling {sibling:?} of game {name} must not contain the backup \
+                        directory."
+                    ));
+                }
+            }
+
+            // Restoring into a backup directory would remove our own backups.
+            if let Some(location) = &game_config.restore_location {
+                let restore_location = normalize_path(&game_config.restore_location());
+                if location.trim().is_empty() {
+                    errors.push(format!(
+                        "The restore location of game {name} must not be empty"
+                    ));
+                    continue;
+                }
+                for backup_dir in backup_dirs.iter() {
+                    if restore_location.starts_with(backup_dir)
+                        || backup_dir.starts_with(&restore_location)
+                    {
+                        errors.push(format!(
+                            "The restore location of game {name} ({restore_location:?}) and the \
+                            backup directory ({backup_dir:?}) must not be nested in each other."
+                        ));
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Collect all problems that don't prevent normal operation, but are most likely mistakes.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (name, game_config) in self.sorted_games() {
+            if game_config.savegame_location().is_relative() {
+                warnings.push(format!(
+                    "The savegame location of game {name} is relative and depends on the \
+                    directory game-saver is started in. Use an absolute path instead."
+                ));
+            }
+            if !game_config.savegame_location().exists() {
+                warnings.push(format!(
+                    "Cannot find savegame location for game {name}: {:?}",
+                    game_config.savegame_location()
+                ));
+            }
+            if game_config.restore_location.is_some() && !game_config.restore_location().exists() {
+                warnings.push(format!(
+                    "Cannot find restore location for game {name}: {:?}",
+                    game_config.restore_location()
+                ));
+            }
+        }
+
+        warnings
+    }
+
+    /// All games sorted by name. Used to get a stable order for messages.
+    fn sorted_games(&self) -> Vec<(&String, &GameConfig)> {
+        let mut games: Vec<(&String, &GameConfig)> = self.games.iter().collect();
+        games.sort_by_key(|(name, _)| *name);
+        games
+    }
+
+    /// The name of a game that's shown to the user.
+    /// That's its `display_name` or its key, if no display name is configured.
+    pub fn display_name<'a>(&'a self, game: &'a str) -> &'a str {
+        self.games
+            .get(game)
+            .and_then(|game_config| game_config.display_name.as_deref())
+            .unwrap_or(game)
+    }
+
+    /// The header a game is listed under.
+    pub fn category<'a>(&'a self, game: &str) -> &'a str {
+        self.games
+            .get(game)
+            .and_then(|game_config| game_config.category.as_deref())
+            .unwrap_or(DEFAULT_CATEGORY)
+    }
+
     pub fn backup_directory(&self) -> PathBuf {
         PathBuf::from(tilde(&self.backup_directory).into_owned())
     }
 
+    /// Get the directory, which contains the saves and the trash of a specific game.
+    /// That's the game's own `backup_directory` or the global one, if it doesn't have one.
+    pub fn game_backup_directory(&self, name: &str) -> PathBuf {
+        match self
+            .games
+            .get(name)
+            .and_then(|game_config| game_config.backup_directory.as_ref())
+        {
+            Some(directory) => PathBuf::from(tilde(directory).into_owned()),
+            None => self.backup_directory(),
+        }
+    }
+
     /// Get the backup directory for a specific game.
     pub fn save_dir(&self, name: &str) -> PathBuf {
-        PathBuf::from(tilde(&self.backup_directory).into_owned()).join(name)
+        self.game_backup_directory(name).join(name)
+    }
+
+    /// The free space on the backup volume, below which autosaves are skipped.
+    pub fn min_free_space(&self) -> u64 {
+        self.min_free_space_mib.saturating_mul(1024 * 1024)
+    }
+
+    /// Get the directory for runtime files that aren't backups, such as the persisted ui state.
+    /// Defaults to the local data directory of the user.
+    pub fn data_directory(&self) -> Result<PathBuf> {
+        match &self.data_directory {
+            Some(directory) => Ok(PathBuf::from(tilde(directory).into_owned())),
+            None => dirs::data_local_dir().context("Couldn't find local data dir"),
+        }
+    }
+
+    /// Get the directory, in which new archives are written, if one is configured.
+    pub fn temp_directory(&self) -> Option<PathBuf> {
+        self.temp_directory
+            .as_ref()
+            .map(|directory| PathBuf::from(tilde(directory).into_owned()))
+    }
+
+    /// Get the directory, in which safety snapshots are kept until exit.
+    /// These are used to undo restores.
+    pub fn undo_dir(&self) -> PathBuf {
+        self.backup_directory().join(".undo")
+    }
+
+    /// Get the directory, in which the safety snapshots of a specific game are kept.
+    pub fn snapshot_dir(&self, name: &str) -> PathBuf {
+        self.undo_dir().join("snapshots").join(name)
+    }
+
+    /// Get the directory, into which deleted saves of a specific game are moved.
+    /// It's on the same volume as the saves, so they can be moved instead of copied.
+    pub fn trash_dir(&self, name: &str) -> PathBuf {
+        self.game_backup_directory(name).join(".trash").join(name)
     }
 
     /// Get the autosave directory for a specific game.
     pub fn autosave_dir(&self, name: &str) -> PathBuf {
         self.save_dir(name).join("autosaves")
     }
+
+    /// Get the directory of the autosaves, which are created by the `autosave_schedule` of a game.
+    pub fn scheduled_dir(&self, name: &str) -> PathBuf {
+        self.save_dir(name).join("scheduled")
+    }
+}
+
+/// Make sure a game name can be used as the name of its directories in the backup directory.
+///
+/// Path separators and `.` or `..` would place the directories somewhere else. Names with a
+/// leading dot are reserved for internal directories, such as the trash.
+pub fn validate_game_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        bail!("Game name cannot be empty");
+    }
+    if name.contains('/') || name.contains('\0') {
+        bail!("Game name cannot contain '/'");
+    }
+    if name.starts_with('.') {
+        bail!("Game name cannot start with '.'");
+    }
+
+    Ok(())
+}
+
+/// Check whether the current user can write to a directory.
+/// Missing directories are created later on, so their closest existing parent is checked.
+fn is_writable(path: &Path) -> bool {
+    let Some(existing) = path.ancestors().find(|ancestor| ancestor.exists()) else {
+        return false;
+    };
+
+    access(existing, AccessFlags::W_OK).is_ok()
+}
+
+/// Resolve symlinks and relative components of a path, if it exists.
+/// Otherwise the path is returned as it is.
+fn normalize_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
 }
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Local};
+
+/// Autosaves are only created, once there haven't been any changes for this many seconds.
+/// Otherwise we might create a backup, while the game is still writing files.
+/// This is the default of each game's `autosave_debounce`.
+pub const AUTOSAVE_DEBOUNCE: i64 = 5;
+
+/// Changes are ignored for this many seconds after a save has been restored.
+pub const RESTORE_IGNORE_DURATION: i64 = 5;
+
+/// How a detected file change has been handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeOutcome {
+    /// The change is ignored, as a save of this game has just been restored.
+    Ignored,
+    /// An autosave was already pending and is postponed until the changes settle.
+    Postponed,
+    /// An autosave has been scheduled, but the autosave timeout has to pass first.
+    AfterTimeout,
+    /// An autosave has been scheduled.
+    Scheduled,
+}
+
+/// Decides when autosaves are created, based on file changes, restores and autosave timeouts.
+///
+/// Two independent delays apply to each autosave:
+/// - The debounce waits for the writes of the game to settle. Every change restarts it.
+/// - The autosave timeout is a cooldown, which enforces a minimum spacing between two autosaves.
+///   It starts with each autosave and isn't affected by any changes.
+///
+/// An autosave is due, once both have passed. All changes up to that point are covered by a
+/// single autosave.
+///
+/// The engine doesn't create any saves by itself and doesn't look at the clock.
+/// The current time is passed to all methods, which makes the timing fully deterministic.
+#[derive(Clone, Debug, Default)]
+pub struct AutosaveEngine {
+    /// Games that recently changed on disk and the time of their latest change.
+    /// We perform changes once there haven't been any changes for some time.
+    changes_detected: HashMap<String, DateTime<Local>>,
+    /// Games whose changes are temporarily ignored, as a save has just been restored.
+    /// (As the restore is a change in the filesystem that get's detected).
+    ignore_changes: HashMap<String, DateTime<Local>>,
+    /// The time of the last autosave and the autosave timeout of games with an active timeout.
+    autosave_timeouts: HashMap<String, (DateTime<Local>, Duration)>,
+    /// The debounce of games that don't use the [AUTOSAVE_DEBOUNCE].
+    debounces: HashMap<String, Duration>,
+}
+
+impl AutosaveEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time for which no changes may happen, before an autosave of a game is due.
+    pub fn set_debounce(&mut self, game: &str, seconds: usize) {
+        let debounce = i64::try_from(seconds)
+            .ok()
+            .and_then(Duration::try_seconds)
+            .unwrap_or(Duration::MAX);
+        self.debounces.insert(game.to_string(), debounce);
+    }
+
+    /// The debounce of a game.
+    fn debounce(&self, game: &str) -> Duration {
+        self.debounces
+            .get(game)
+            .copied()
+            .unwrap_or(Duration::seconds(AUTOSAVE_DEBOUNCE))
+    }
+
+    /// Register a file change of a game at the given time.
+    pub fn change_detected(&mut self, game: &str, time: DateTime<Local>) -> ChangeOutcome {
+        if self.is_ignoring(game) {
+            return ChangeOutcome::Ignored;
+        }
+
+        let outcome = if self.changes_detected.contains_key(game) {
+            ChangeOutcome::Postponed
+        } else if self.autosave_timeouts.contains_key(game) {
+            ChangeOutcome::AfterTimeout
+        } else {
+            ChangeOutcome::Scheduled
+        };
+        self.changes_detected.insert(game.to_string(), time);
+
+        outcome
+    }
+
+    /// Ignore all changes of a game for a short time, starting now.
+    /// This is used while and after restoring a save. Pending changes are dropped.
+    pub fn ignore_changes(&mut self, game: &str, now: DateTime<Local>) {
+        self.ignore_changes.insert(game.to_string(), now);
+        self.changes_detected.remove(game);
+    }
+
+    /// Whether changes of a game are currently ignored.
+    pub fn is_ignoring(&self, game: &str) -> bool {
+        self.ignore_changes.contains_key(game)
+    }
+
+    /// All games with pending changes, sorted by name.
+    pub fn pending_games(&self) -> Vec<String> {
+        let mut games: Vec<String> = self.changes_detected.keys().cloned().collect();
+        games.sort();
+        games
+    }
+
+    /// Forget the pending changes of a game, e.g. because they've been saved by other means.
+    pub fn discard_pending(&mut self, game: &str) {
+        self.changes_detected.remove(game);
+    }
+
+    /// All games whose changes have settled and whose autosave timeout passed, sorted by name.
+    pub fn due_games(&self, now: DateTime<Local>) -> Vec<String> {
+        let mut games: Vec<String> = self
+            .changes_detected
+            .iter()
+            .filter(|(game, time)| now - **time >= self.debounce(game))
+            .filter(|(game, _)| !self.autosave_timeouts.contains_key(*game))
+            .map(|(game, _)| game.clone())
+            .collect();
+        games.sort();
+        games
+    }
+
+    /// Remember that an autosave has been created for a game.
+    /// The pending changes are covered by this save and the autosave timeout starts.
+    pub fn autosave_created(&mut self, game: &str, timeout: usize, now: DateTime<Local>) {
+        self.changes_detected.remove(game);
+        if timeout > 0 {
+            // Absurdly long timeouts simply never pass.
+            let timeout = i64::try_from(timeout)
+                .ok()
+                .and_then(Duration::try_seconds)
+                .unwrap_or(Duration::MAX);
+            self.autosave_timeouts
+                .insert(game.to_string(), (now, timeout));
+        }
+    }
+
+    /// Remove all ignore rules and autosave timeouts that are over.
+    pub fn sweep(&mut self, now: DateTime<Local>) {
+        self.ignore_changes
+            .retain(|_, time| now - *time <= Duration::seconds(RESTORE_IGNORE_DURATION));
+        self.autosave_timeouts
+            .retain(|_, (last_save, timeout)| now - *last_save <= *timeout);
+    }
+
+    /// The seconds until the autosave timeout of a game is over.
+    /// Returns `None`, if no timeout is active.
+    pub fn timeout_remaining(&self, game: &str, now: DateTime<Local>) -> Option<i64> {
+        let (last_save, timeout) = self.autosave_timeouts.get(game)?;
+        let remaining = timeout.checked_sub(&(now - *last_save)).unwrap_or(*timeout);
+
+        Some(remaining.num_seconds().max(0))
+    }
+
+    /// The seconds until the pending autosave of a game is due.
+    /// Returns `None`, if no changes have been detected for that game.
+    pub fn countdown(&self, game: &str, now: DateTime<Local>) -> Option<i64> {
+        let changed = self.changes_detected.get(game)?;
+        let debounce = (*changed - now).num_seconds() + self.debounce(game).num_seconds();
+        // A running autosave timeout postpones the autosave even further.
+        let timeout = self.timeout_remaining(game, now).unwrap_or(0);
+
+        Some(debounce.max(timeout).max(0))
+    }
+}
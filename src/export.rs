@@ -0,0 +1,148 @@
+use std::{
+    ffi::OsStr,
+    fs::{create_dir, create_dir_all, read_dir, remove_dir_all, rename, symlink_metadata},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    archive::{create_archive, extract_archive, ArchiveOptions},
+    config::{ArchiveFormat, Config},
+    files::get_archive_files,
+};
+
+/// What an import added to the saves of a game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// The amount of saves that have been added.
+    pub imported: usize,
+    /// The amount of saves that have been skipped, as a save with the same name already exists.
+    pub skipped: usize,
+}
+
+/// Pack all manual saves of a game and optionally its autosaves into a single archive at `dest`.
+/// Scheduled autosaves are treated like any other autosave.
+/// The format of the archive is determined by the extension of `dest`.
+///
+/// The saves keep their names and modification times, which are used to sort them.
+/// Returns the amount of exported saves.
+pub fn export_saves(
+    config: &Config,
+    game: &str,
+    dest: &Path,
+    include_autosaves: bool,
+) -> Result<usize> {
+    config.game(game)?;
+    let format = ArchiveFormat::from_path(dest)?;
+
+    let mut count = get_archive_files(&config.save_dir(game))?.len();
+    for autosave_dir in [config.autosave_dir(game), config.scheduled_dir(game)] {
+        if include_autosaves && autosave_dir.exists() {
+            count += get_archive_files(&autosave_dir)?.len();
+        }
+    }
+    if count == 0 {
+        bail!("Game {game} doesn't have any saves to export");
+    }
+
+    // The archive contains the backup directory of the game, so the autosaves are excluded by
+    // ignoring their directories.
+    let ignored_files = if include_autosaves {
+        Vec::new()
+    } else {
+        vec!["/autosaves/".to_string(), "/scheduled/".to_string()]
+    };
+    let temp_directory = config.temp_directory();
+    let options = ArchiveOptions {
+        ignored_files: &ignored_files,
+        temp_directory: temp_directory.as_deref(),
+        ..Default::default()
+    };
+    create_archive(
+        format,
+        &config.game_backup_directory(game),
+        OsStr::new(game),
+        dest,
+        &options,
+        &|_, _| {},
+    )
+    .context(format!("Failed to export saves of {game}"))?;
+
+    Ok(count)
+}
+
+/// Import all saves of an archive that has been created by [export_saves] into the saves of a
+/// game. The game may have a different name than the exported one.
+///
+/// Saves with the same name as an existing save are skipped, existing saves are never replaced.
+pub fn import_saves(config: &Config, game: &str, archive: &Path) -> Result<ImportSummary> {
+    config.game(game)?;
+    let format = ArchiveFormat::from_path(archive)?;
+
+    // Extract everything into a staging directory first, so a broken archive doesn't leave any
+    // half imported saves behind.
+    let staging = config
+        .game_backup_directory(game)
+        .join(format!(".import_{game}"));
+    if symlink_metadata(&staging).is_ok() {
+        remove_dir_all(&staging).context(format!("Failed to remove {staging:?}"))?;
+    }
+    create_dir(&staging).context(format!("Failed to create {staging:?}"))?;
+
+    let result = extract_archive(format, archive, &staging, &|_, _| {})
+        .context(format!("Failed to extract {archive:?}"))
+        .and_then(|_| import_staged_saves(config, game, &staging));
+    let cleanup = remove_dir_all(&staging).context(format!("Failed to remove {staging:?}"));
+
+    let summary = result?;
+    cleanup?;
+
+    Ok(summary)
+}
+
+/// Move the extracted saves of an export into the directories of a game.
+fn import_staged_saves(config: &Config, game: &str, staging: &Path) -> Result<ImportSummary> {
+    // An export contains exactly one directory, which is named after the exported game.
+    let entries: Vec<PathBuf> = read_dir(staging)
+        .context(format!("Couldn't read directory {staging:?}"))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()
+        .context(format!("Couldn't get dir entry in {staging:?}"))?;
+    let [exported] = entries.as_slice() else {
+        bail!("The archive isn't an export of game-saver");
+    };
+    if !exported.is_dir() {
+        bail!("The archive isn't an export of game-saver");
+    }
+
+    let mut summary = ImportSummary::default();
+    let directories = [
+        (exported.clone(), config.save_dir(game)),
+        (exported.join("autosaves"), config.autosave_dir(game)),
+        (exported.join("scheduled"), config.scheduled_dir(game)),
+    ];
+    for (source, dest) in directories {
+        if !source.is_dir() {
+            continue;
+        }
+        create_dir_all(&dest).context(format!("Failed to create directory {dest:?}"))?;
+
+        for save in get_archive_files(&source)? {
+            let Some(file_name) = save.path.file_name() else {
+                continue;
+            };
+            let target = dest.join(file_name);
+            if symlink_metadata(&target).is_ok() {
+                summary.skipped += 1;
+                continue;
+            }
+
+            // The extracted save keeps its modification time.
+            rename(&save.path, &target).context(format!("Failed to move save to {target:?}"))?;
+            summary.imported += 1;
+        }
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,377 @@
+use std::{
+    convert::TryInto,
+    ffi::OsString,
+    fs::{canonicalize, create_dir, create_dir_all, read_dir, symlink_metadata, write, DirEntry},
+    path::{self, Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, LocalResult, TimeZone};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use log::warn;
+use nix::sys::statvfs::statvfs;
+
+use crate::config::{ArchiveFormat, Config};
+
+#[derive(Clone, Debug)]
+pub struct SaveFile {
+    pub path: PathBuf,
+    /// The name of the save without its archive extension.
+    pub file_name: String,
+    pub last_modified: DateTime<Local>,
+    pub format: ArchiveFormat,
+}
+
+/// Return all paths and filenames of archive files for a given directory.
+/// The files are sorted by datetime.
+///
+/// Single entries that cannot be read, e.g. because they've been removed in the meantime, are
+/// skipped and logged, so the remaining saves can still be listed.
+pub fn get_archive_files(path: &Path) -> Result<Vec<SaveFile>> {
+    let mut files = Vec::new();
+
+    let dir_files = read_dir(path).context(format!("Couldn't read directory {path:?}"))?;
+    for dir_entry in dir_files {
+        let dir_entry = match dir_entry {
+            Ok(dir_entry) => dir_entry,
+            Err(err) => {
+                warn!("Couldn't get dir entry in {path:?}: {err}");
+                continue;
+            }
+        };
+
+        match read_save_file(&dir_entry) {
+            Ok(Some(save)) => files.push(save),
+            Ok(None) => (),
+            Err(err) => warn!("Skipped {:?}: {err:#}", dir_entry.path()),
+        }
+    }
+
+    // Sort by descending order
+    files.sort_by_key(|file| std::cmp::Reverse(file.last_modified));
+
+    Ok(files)
+}
+
+/// Read a single entry of a directory with saves.
+/// Returns `None`, if the entry isn't an archive of a known format.
+fn read_save_file(dir_entry: &DirEntry) -> Result<Option<SaveFile>> {
+    let path = dir_entry.path();
+
+    // File must be an archive of a known format.
+    let full_name = dir_entry.file_name().to_string_lossy().into_owned();
+    let (file_name, format) = match ArchiveFormat::split_file_name(&full_name) {
+        Some((file_name, format)) => (file_name.to_string(), format),
+        None => return Ok(None),
+    };
+
+    // Extract a DateTime<Local> from the file's creation date
+    let metadata = dir_entry
+        .metadata()
+        .context(format!("Couldn't read metadata of file {path:?}"))?;
+    let last_modified = metadata
+        .modified()
+        .context(format!("Couldn't read creation time of file {path:?}"))?;
+    let seconds = last_modified
+        .duration_since(UNIX_EPOCH)
+        .context(format!("File {path:?} has been modified before 1970"))?
+        .as_secs();
+    let last_modified_result = Local.timestamp_opt(seconds.try_into().unwrap_or(i64::MAX), 0);
+
+    let last_modified = match last_modified_result {
+        LocalResult::None => bail!("Failed to get last modified date of file at {path:?}."),
+        LocalResult::Single(today) => today,
+        LocalResult::Ambiguous(today, _) => today,
+    };
+
+    // It must be a file
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    Ok(Some(SaveFile {
+        path,
+        file_name,
+        last_modified,
+        format,
+    }))
+}
+
+/// Check whether a path contains any files.
+/// Directories that only contain empty directories are considered empty.
+pub fn contains_files(path: &Path) -> Result<bool> {
+    if !path.is_dir() {
+        return Ok(path.exists());
+    }
+
+    let dir_files = read_dir(path).context(format!("Couldn't read directory {path:?}"))?;
+    for dir_entry in dir_files {
+        let dir_entry = dir_entry.context(format!("Couldn't get dir entry in {path:?}"))?;
+        if contains_files(&dir_entry.path())? {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// The names of the ignore files, which are respected at the top of a savegame location.
+/// Later files take precedence over earlier ones.
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Get all existing [IGNORE_FILES] at the top of `root`.
+pub fn ignore_files(root: &Path) -> Vec<PathBuf> {
+    IGNORE_FILES
+        .iter()
+        .map(|name| root.join(name))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Build a matcher for the gitignore-style `ignored_files` globs of a game.
+/// The globs are relative to `root`, just like in the file watcher.
+///
+/// The [IGNORE_FILES] at the top of `root` are respected as well. The `ignored_files` take
+/// precedence over them.
+pub fn build_ignore(root: &Path, globs: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(root);
+    for path in ignore_files(root) {
+        // Invalid lines are skipped, the valid ones of the file are still used.
+        if let Some(err) = builder.add(&path) {
+            warn!("Failed to read ignore file {path:?}: {err}");
+        }
+    }
+    for glob in globs {
+        builder
+            .add_line(None, glob)
+            .context(format!("Invalid ignore glob {glob}"))?;
+    }
+
+    builder.build().context("Failed to build ignore globs")
+}
+
+/// Split the location of a save into its parent directory, which is the working directory of
+/// the archivers, and its name, which is the top-level entry of the archives.
+///
+/// Relative locations are resolved against the current directory. Locations that end in `..`
+/// are resolved to the directory they point to, as they don't have a name on their own.
+/// Symlinks are only resolved in that case, so a linked location keeps its own name.
+/// The root directory doesn't have a name and would result in malformed archives, which is why
+/// it's rejected.
+pub fn split_location(location: &Path) -> Result<(PathBuf, OsString)> {
+    let mut absolute =
+        path::absolute(location).context(format!("Couldn't resolve location {location:?}"))?;
+    if absolute.file_name().is_none() {
+        absolute = canonicalize(&absolute).unwrap_or(absolute);
+    }
+    let (Some(parent), Some(name)) = (absolute.parent(), absolute.file_name()) else {
+        bail!("Location {location:?} has to be a file or directory below the root directory");
+    };
+
+    Ok((parent.to_path_buf(), name.to_os_string()))
+}
+
+/// Count all files below a path, which aren't ignored.
+/// Directories themselves aren't counted.
+pub fn count_files(path: &Path, ignore: &Gitignore) -> Result<usize> {
+    // Symlinks are removed by a restore, but their targets aren't.
+    let Ok(metadata) = symlink_metadata(path) else {
+        return Ok(0);
+    };
+    if ignore.matched(path, metadata.is_dir()).is_ignore() {
+        return Ok(0);
+    }
+    if !metadata.is_dir() {
+        return Ok(1);
+    }
+
+    let mut count = 0;
+    let dir_files = read_dir(path).context(format!("Couldn't read directory {path:?}"))?;
+    for dir_entry in dir_files {
+        let dir_entry = dir_entry.context(format!("Couldn't get dir entry in {path:?}"))?;
+        count += count_files(&dir_entry.path(), ignore)?;
+    }
+
+    Ok(count)
+}
+
+/// Create all directories that are needed for normal operation.
+pub fn init_directories(config: &Config) -> Result<()> {
+    config.validate()?;
+
+    let backup_dir = config.backup_directory();
+    // Create the backup directory
+    create_dir_all(backup_dir).context("Failed to create backup directory")?;
+    if let Some(temp_dir) = config.temp_directory() {
+        create_dir_all(&temp_dir)
+            .context(format!("Failed to create temp directory {temp_dir:?}"))?;
+    }
+
+    // Create subfolders for each game
+    for (name, game_config) in &config.games {
+        // Show a special error message, if the user is using the path from the example file.
+        if game_config.savegame_location == "~/some/path/to/your/save/files" {
+            bail!("Please adjust the default configuration file at ~/.config/game_saver.toml",);
+        }
+
+        // Games may store their backups in their own backup directory.
+        let root = config.game_backup_directory(name);
+        create_dir_all(&root).context(format!("Failed to create backup directory {root:?}"))?;
+
+        // Create the backup directory for this game.
+        let game_backup_dir = config.save_dir(name);
+        if !game_backup_dir.exists() {
+            create_dir(&game_backup_dir)
+                .context(format!("Failed to create backup directory for game {name}",))?;
+        }
+
+        // Only create an autosave directory, if autosave is enabled for this game.
+        if !game_config.has_autosaves() {
+            continue;
+        }
+
+        let autosave_dir = config.autosave_dir(name);
+        if !autosave_dir.exists() {
+            create_dir(autosave_dir).context(format!(
+                "Failed to create autosave directory for game {name}",
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Find all directories in the global backup directory, which don't belong to any configured
+/// game, e.g. because the game has been removed from the config. The trash of such games is
+/// included.
+///
+/// The own backup directories of games are never scanned, as they might be shared with other
+/// data. Only directories that look like the saves of a game, i.e. contain an `autosaves`
+/// directory or archives, are listed.
+/// Directories that are named after a configured game or contain the saves, trash or runtime
+/// files of one are never listed, even if they're nested inside of the backup directory.
+pub fn orphaned_backup_directories(config: &Config) -> Result<Vec<PathBuf>> {
+    let mut protected: Vec<PathBuf> = vec![config.undo_dir()];
+    protected.extend(config.temp_directory());
+    protected.extend(config.data_directory().ok());
+    for name in config.games.keys() {
+        protected.push(config.save_dir(name));
+        protected.push(config.trash_dir(name));
+    }
+    let protected: Vec<PathBuf> = protected.iter().map(|path| normalize(path)).collect();
+
+    let root = config.backup_directory();
+    let mut orphans = Vec::new();
+    for dir in [root.clone(), root.join(".trash")] {
+        if !dir.is_dir() {
+            continue;
+        }
+        let dir_files = read_dir(&dir).context(format!("Couldn't read directory {dir:?}"))?;
+        for dir_entry in dir_files {
+            let dir_entry = dir_entry.context(format!("Couldn't get dir entry in {dir:?}"))?;
+            let path = dir_entry.path();
+            // Hidden directories, such as the trash, are used by game-saver itself.
+            // Symlinks are never followed, as their targets might be anything.
+            let is_dir = dir_entry
+                .file_type()
+                .is_ok_and(|file_type| file_type.is_dir());
+            let name = dir_entry.file_name().to_string_lossy().into_owned();
+            if !is_dir || name.starts_with('.') {
+                continue;
+            }
+            // Directories that're named after a configured game might still contain its
+            // saves, e.g. if the game got its own backup directory afterwards.
+            if config.games.contains_key(&name) {
+                continue;
+            }
+            let normalized = normalize(&path);
+            if protected.iter().any(|path| path.starts_with(&normalized)) {
+                continue;
+            }
+            if !is_save_directory(&path)? {
+                continue;
+            }
+            orphans.push(path);
+        }
+    }
+    orphans.sort();
+
+    Ok(orphans)
+}
+
+/// Whether a directory looks like it has been created by game-saver for a game.
+/// That's the case, if it contains an `autosaves` directory or any archives.
+fn is_save_directory(path: &Path) -> Result<bool> {
+    let dir_files = read_dir(path).context(format!("Couldn't read directory {path:?}"))?;
+    for dir_entry in dir_files {
+        let dir_entry = dir_entry.context(format!("Couldn't get dir entry in {path:?}"))?;
+        let Ok(file_type) = dir_entry.file_type() else {
+            continue;
+        };
+        let name = dir_entry.file_name().to_string_lossy().into_owned();
+        if file_type.is_dir() && name == "autosaves" {
+            return Ok(true);
+        }
+        if file_type.is_file() && ArchiveFormat::split_file_name(&name).is_some() {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Resolve a path, so it can be compared with other paths. Missing paths are kept as they are.
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Get the size of all files in a directory in bytes. Symlinks aren't followed.
+pub fn directory_size(path: &Path) -> Result<u64> {
+    let metadata = symlink_metadata(path).context(format!("Couldn't read metadata of {path:?}"))?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut size = 0;
+    let dir_files = read_dir(path).context(format!("Couldn't read directory {path:?}"))?;
+    for dir_entry in dir_files {
+        let dir_entry = dir_entry.context(format!("Couldn't get dir entry in {path:?}"))?;
+        size += directory_size(&dir_entry.path())?;
+    }
+
+    Ok(size)
+}
+
+/// Write the current time into the health file.
+/// Supervisors can check the file's content or mtime to detect a hung instance.
+pub fn write_health_file(path: &Path) -> Result<()> {
+    write(path, Local::now().to_rfc3339()).context(format!("Failed to write health file {path:?}"))
+}
+
+/// Get the amount of bytes that're available to unprivileged users on the volume of `path`.
+pub fn available_space(path: &Path) -> Result<u64> {
+    let stat = statvfs(path).context(format!("Failed to get filesystem stats of {path:?}"))?;
+
+    // The types of these fields differ between platforms.
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+}
+
+/// Format an amount of bytes in a human readable way, e.g. `212.4 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
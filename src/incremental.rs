@@ -0,0 +1,193 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    fmt::Write,
+    fs::{metadata, read_dir, read_link, remove_dir_all, remove_file, symlink_metadata, File},
+    io,
+    os::unix::ffi::OsStrExt,
+    path::Path,
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{bail, Context, Result};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    archive::{collect_save_entries, read_extra_file, ArchiveOptions},
+    config::ArchiveFormat,
+};
+
+/// The name of the manifest inside of an archive.
+/// It's stored next to the savegame location, so it's never restored into it.
+pub const MANIFEST_NAME: &str = ".game-saver-manifest.toml";
+
+/// The content of a save, which is stored in the archives of incremental autosaves.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Manifest {
+    /// The file name of the full autosave, which contains all files that didn't change.
+    /// This isn't set for full autosaves.
+    #[serde(default)]
+    pub base: Option<String>,
+    /// All directories of the save, relative to the parent of the savegame location.
+    #[serde(default)]
+    pub directories: BTreeSet<String>,
+    /// All files of the save, relative to the parent of the savegame location.
+    #[serde(default)]
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+/// A single file of a [Manifest].
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub size: u64,
+    /// The modification time in nanoseconds since the unix epoch.
+    pub modified: i64,
+    /// The hex encoded sha256 hash of the file's content.
+    /// Symlinks, which aren't followed, are hashed by their target.
+    pub hash: String,
+}
+
+impl Manifest {
+    /// The uncompressed size of all files.
+    pub fn size(&self) -> u64 {
+        self.files.values().map(|entry| entry.size).sum()
+    }
+
+    /// All files that are new or differ from the ones in `base`.
+    pub fn changed_files(&self, base: &Manifest) -> BTreeSet<String> {
+        self.files
+            .iter()
+            .filter(|(path, entry)| {
+                base.files
+                    .get(*path)
+                    .is_none_or(|base_entry| base_entry.hash != entry.hash)
+            })
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+}
+
+/// Build the manifest of `cwd/source_filename` and its siblings. Ignored files aren't included.
+///
+/// Hashing huge saves takes a while. Files, whose size and modification time didn't change since
+/// they've been hashed for the `previous` manifest, reuse that hash.
+pub fn build_manifest(
+    cwd: &Path,
+    source_filename: &OsStr,
+    options: &ArchiveOptions,
+    previous: Option<&Manifest>,
+) -> Result<Manifest> {
+    let entries = collect_save_entries(cwd, source_filename, options)?;
+
+    let mut manifest = Manifest::default();
+    for (path, size) in entries {
+        // Manifests are stored as toml, which only supports utf-8.
+        let Some(name) = path.to_str().map(str::to_string) else {
+            bail!("Incremental saves don't support the file name {path:?}");
+        };
+        if size.is_none() {
+            manifest.directories.insert(name);
+            continue;
+        }
+
+        let full_path = cwd.join(&path);
+        let file_metadata = if options.follow_symlinks {
+            metadata(&full_path)
+        } else {
+            symlink_metadata(&full_path)
+        }
+        .context(format!("Couldn't read metadata of {full_path:?}"))?;
+        let modified = file_metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|modified| modified.as_nanos() as i64)
+            .unwrap_or_default();
+
+        let previous_entry = previous.and_then(|previous| previous.files.get(&name));
+        let hash = match previous_entry {
+            Some(entry) if entry.size == file_metadata.len() && entry.modified == modified => {
+                entry.hash.clone()
+            }
+            _ if file_metadata.is_symlink() => {
+                let target =
+                    read_link(&full_path).context(format!("Couldn't read link {full_path:?}"))?;
+                hex_hash(Sha256::digest(target.as_os_str().as_bytes()).as_slice())
+            }
+            _ => hash_file(&full_path)?,
+        };
+
+        let entry = ManifestEntry {
+            size: file_metadata.len(),
+            modified,
+            hash,
+        };
+        manifest.files.insert(name, entry);
+    }
+
+    Ok(manifest)
+}
+
+/// Read the manifest of a save.
+/// Returns `None` for saves, which have been created without a manifest.
+pub fn read_manifest(archive: &Path) -> Result<Option<Manifest>> {
+    let format = ArchiveFormat::from_path(archive)?;
+    let Some(content) = read_extra_file(format, archive, MANIFEST_NAME)? else {
+        return Ok(None);
+    };
+    let content = String::from_utf8(content).context("Manifest isn't valid utf-8")?;
+    let manifest = toml::from_str(&content).context(format!("Invalid manifest in {archive:?}"))?;
+
+    Ok(Some(manifest))
+}
+
+/// Remove everything in an extracted save that isn't listed in its manifest.
+///
+/// An incremental save is restored by extracting its base and the incremental save on top of it.
+/// Files that have been removed after the base was created are removed afterwards.
+pub fn remove_unlisted(root: &Path, manifest: &Manifest) -> Result<()> {
+    remove_unlisted_entries(root, Path::new(""), manifest)
+}
+
+fn remove_unlisted_entries(root: &Path, relative: &Path, manifest: &Manifest) -> Result<()> {
+    let dir = root.join(relative);
+    let dir_files = read_dir(&dir).context(format!("Couldn't read directory {dir:?}"))?;
+    for dir_entry in dir_files {
+        let dir_entry = dir_entry.context(format!("Couldn't get dir entry in {dir:?}"))?;
+        let path = relative.join(dir_entry.file_name());
+        let name = path.to_string_lossy();
+        let full_path = dir_entry.path();
+
+        let file_type = dir_entry
+            .file_type()
+            .context(format!("Couldn't get file type of {full_path:?}"))?;
+        if file_type.is_dir() {
+            if manifest.directories.contains(name.as_ref()) {
+                remove_unlisted_entries(root, &path, manifest)?;
+            } else {
+                remove_dir_all(&full_path).context(format!("Failed to remove {full_path:?}"))?;
+            }
+        } else if !manifest.files.contains_key(name.as_ref()) && name != MANIFEST_NAME {
+            remove_file(&full_path).context(format!("Failed to remove {full_path:?}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Hash the content of a file with sha256.
+fn hash_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).context(format!("Failed to open {path:?}"))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).context(format!("Failed to read {path:?}"))?;
+
+    Ok(hex_hash(hasher.finalize().as_slice()))
+}
+
+fn hex_hash(hash: &[u8]) -> String {
+    hash.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
@@ -0,0 +1,37 @@
+//! The core of game-saver, without the terminal ui.
+//!
+//! This library can be used by other programs to create and restore saves in the same format
+//! and directory layout as the `game-saver` binary.
+//! Disable the default `tui` feature to only pull in the dependencies of the core:
+//!
+//! ```toml
+//! game-saver = { version = "0.1", default-features = false }
+//! ```
+//!
+//! Everything returns [anyhow::Result]s. Errors of the file system, the config parser and the
+//! archivers are wrapped with a context, which names the affected path.
+//! Errors, which callers may want to handle separately, are typed and can be retrieved via
+//! [anyhow::Error::downcast_ref]:
+//! - [EmptySavegameLocation]
+//! - [InsufficientSpace]
+//! - [LowDiskSpace]
+pub mod archive;
+pub mod config;
+pub mod engine;
+pub mod export;
+pub mod files;
+pub mod incremental;
+pub mod saves;
+
+pub use self::{
+    archive::{InsufficientSpace, Progress},
+    config::{ArchiveFormat, BackupStrategy, Config, GameConfig, RetentionPolicy},
+    export::{export_saves, import_saves, ImportSummary},
+    files::{get_archive_files, SaveFile},
+    saves::{
+        autosave_game, compare_saves, manually_save_game, read_save_file, restore_save,
+        restore_summary, rotate_autosaves, save_game, scheduled_autosave_game, surplus_autosaves,
+        verify_saves, EmptySavegameLocation, LowDiskSpace, RestoreSummary, SaveComparison,
+        VerifyReport,
+    },
+};
@@ -1,23 +1,57 @@
-use anyhow::{Context, Result};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{bail, Context, Result};
 use clap::Parser;
 use crossbeam_channel::unbounded;
-use flexi_logger::Logger;
+use flexi_logger::{detailed_format, FileSpec, Logger};
 use log::{info, LevelFilter};
 
+use game_saver::{config::Config, export_saves, import_saves};
+
 mod app;
 mod cli;
-mod config;
 mod watcher;
 
-use config::Config;
+use cli::SubCommand;
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 4)]
 async fn main() -> Result<()> {
     // Parse commandline options.
     let opt = cli::CliArguments::parse();
-    init_app(opt.verbosity);
+    init_app(&opt)?;
+
+    if opt.config_check {
+        check_config(&opt.config);
+    }
 
-    let config = Config::new(&opt.config)?;
+    if let Some(cmd) = &opt.cmd {
+        return run_subcommand(cmd, &opt).await;
+    }
+
+    // Start the interactive setup, if no config exists yet.
+    let config_path = Config::path(&opt.config)?;
+    let config = if config_path.exists() {
+        Config::new(&opt.config)?
+    } else {
+        app::run_setup_wizard(&config_path, None)?
+    };
+    // Explain how to add games, instead of showing an empty main view.
+    // That's also the case, if none of the savegame locations exists.
+    let config = if !app::has_listed_games(&apply_overrides(config.clone(), &opt)) {
+        match app::run_empty_config_screen(&config_path, config)? {
+            Some(config) => config,
+            None => return Ok(()),
+        }
+    } else {
+        config
+    };
+    let config = apply_overrides(config, &opt);
 
     // Create the mpsc channel that's used to send notifications from the file watcher thread
     // to the actual application loop.
@@ -31,25 +65,123 @@ async fn main() -> Result<()> {
     info!("All watchers have been spawned, waiting for updates");
 
     // Run the actual main app.
-    app::run(config, receiver)?;
+    app::run(config, receiver, &opt)?;
 
     Ok(())
 }
 
+/// Apply the options of the commandline, which take precedence over the config file.
+fn apply_overrides(mut config: Config, opt: &cli::CliArguments) -> Config {
+    if let Some(directory) = &opt.data_directory {
+        config.data_directory = Some(directory.to_string_lossy().into_owned());
+    }
+
+    config
+}
+
+/// Run a subcommand instead of the interactive app.
+async fn run_subcommand(cmd: &SubCommand, opt: &cli::CliArguments) -> Result<()> {
+    let config = apply_overrides(Config::new(&opt.config)?, opt);
+    config.validate()?;
+
+    match cmd {
+        SubCommand::Compact {
+            older_than_days,
+            level,
+        } => app::compact_autosaves(&config, *older_than_days, *level),
+        SubCommand::Export {
+            game,
+            out,
+            include_autosaves,
+        } => {
+            let count = export_saves(&config, game, out, *include_autosaves)?;
+            println!("Exported {count} saves of {game} to {out:?}");
+            Ok(())
+        }
+        SubCommand::Import { game, file } => {
+            let summary = import_saves(&config, game, file)?;
+            println!(
+                "Imported {} saves into {game}, skipped {} saves that already exist",
+                summary.imported, summary.skipped
+            );
+            Ok(())
+        }
+        SubCommand::Verify => app::verify_backups(&config),
+        SubCommand::Clean { yes } => app::clean_orphaned_directories(&config, *yes),
+        SubCommand::Daemon { .. } => run_daemon(config, opt).await,
+    }
+}
+
+/// Spawn the watchers and create autosaves without the terminal ui.
+/// SIGTERM and SIGINT stop the daemon, once all pending autosaves have been created.
+async fn run_daemon(config: Config, opt: &cli::CliArguments) -> Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let signal = shutdown.clone();
+    ctrlc::set_handler(move || {
+        signal.store(true, Ordering::SeqCst);
+    })
+    .context("Failed to set signal handler")?;
+
+    let (sender, receiver) = unbounded();
+    info!("Spawning watchers");
+    watcher::spawn_watchers(&config, &sender)
+        .await
+        .context("Failed while spawning watchers")?;
+
+    app::run_daemon(config, receiver, &shutdown, opt)
+}
+
+/// Validate the config, print all problems and exit.
+/// The exit code is 1, if any problems have been found.
+fn check_config(path: &Option<PathBuf>) -> ! {
+    let config = match Config::new(path) {
+        Ok(config) => config,
+        Err(err) => {
+            println!("Failed to load config:\n{err:?}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut problems = config.errors();
+    problems.extend(config.warnings());
+    if problems.is_empty() {
+        println!("OK");
+        std::process::exit(0);
+    }
+
+    for problem in problems {
+        println!("- {problem}");
+    }
+    std::process::exit(1);
+}
+
 /// Run all boilerplate initialization code that's unrelated to actual application logic.
-fn init_app(verbosity: u8) {
+fn init_app(opt: &cli::CliArguments) -> Result<()> {
     // Beautify panics for better debug output.
     better_panic::install();
 
     // This section handles Shutdown via SigTerm/SigInt process signals
     // Notify the TaskHandler, so it can shutdown gracefully.
     // The actual program exit will be done via the TaskHandler.
-    ctrlc::set_handler(move || {
-        std::process::exit(1);
-    })
-    .expect("Failed to set signal handler");
+    // The daemon installs its own handler, as it has to create pending autosaves first.
+    let daemon_log_file = match &opt.cmd {
+        Some(SubCommand::Daemon { log_file }) => Some(log_file.as_deref()),
+        _ => {
+            ctrlc::set_handler(move || {
+                std::process::exit(1);
+            })
+            .expect("Failed to set signal handler");
+            None
+        }
+    };
 
     // Set the verbosity level and initialize the logger.
+    // The daemon has no other output, which is why it always logs its actions.
+    let verbosity = if daemon_log_file.is_some() {
+        opt.verbosity.max(2)
+    } else {
+        opt.verbosity
+    };
     let level = match verbosity {
         0 => LevelFilter::Error,
         1 => LevelFilter::Warn,
@@ -58,10 +190,25 @@ fn init_app(verbosity: u8) {
     };
 
     let log_info = format!("{}, watchexec=warn", level.to_string().to_lowercase());
-    Logger::try_with_str(log_info)
-        .expect("Failed to init logger")
-        .start()
-        .expect("Failed to start logger");
+    let mut logger = Logger::try_with_str(log_info).expect("Failed to init logger");
+    if let Some(Some(path)) = daemon_log_file {
+        logger = logger
+            .log_to_file(file_spec(path)?)
+            .append()
+            .format(detailed_format);
+    }
+    logger.start().expect("Failed to start logger");
 
     info!("Initialized logger with verbosity {}", level);
+
+    Ok(())
+}
+
+/// Get the spec of a log file at the given path.
+fn file_spec(path: &Path) -> Result<FileSpec> {
+    if path.file_name().is_none() {
+        bail!("Invalid log file {path:?}");
+    }
+
+    FileSpec::try_from(path).context(format!("Invalid log file {path:?}"))
 }
@@ -0,0 +1,1042 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    ffi::OsString,
+    fmt,
+    fs::{
+        create_dir, create_dir_all, read_dir, remove_dir, remove_dir_all, remove_file, rename,
+        symlink_metadata, write,
+    },
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, Timelike};
+use ignore::gitignore::Gitignore;
+use log::warn;
+
+use crate::{
+    archive::{
+        check_archive, create_archive, extract_archive, extract_file, list_archive_entries,
+        ArchiveOptions, Progress,
+    },
+    config::{
+        ArchiveFormat, BackupStrategy, Config, GameConfig, RetentionPolicy, AUTOSAVE_COUNTER,
+    },
+    files::{
+        available_space, build_ignore, contains_files, count_files, format_size, get_archive_files,
+        split_location, SaveFile,
+    },
+    incremental::{build_manifest, read_manifest, remove_unlisted, Manifest, MANIFEST_NAME},
+};
+
+/// The error that's returned, if a save has been skipped due to an empty savegame location.
+#[derive(Debug)]
+pub struct EmptySavegameLocation;
+
+impl fmt::Display for EmptySavegameLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "savegame location is empty")
+    }
+}
+
+impl std::error::Error for EmptySavegameLocation {}
+
+/// The error that's returned, if an autosave has been skipped due to low disk space.
+#[derive(Debug)]
+pub struct LowDiskSpace {
+    pub available: u64,
+}
+
+impl fmt::Display for LowDiskSpace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "only {} free on the backup volume",
+            format_size(self.available)
+        )
+    }
+}
+
+impl std::error::Error for LowDiskSpace {}
+
+/// A wrapper around [save_game], which handles the cycling of autosaves.
+/// Returns the path of the new autosave.
+pub fn autosave_game(config: &Config, game: &str, progress: Progress) -> Result<PathBuf> {
+    let game_config = config.game(game)?;
+    create_autosave(
+        config,
+        game,
+        &config.autosave_dir(game),
+        game_config.autosaves,
+        &game_config.retention,
+        game_config.backup_strategy,
+        progress,
+    )
+    .context("Failed to create autosave")
+}
+
+/// Create an autosave for the `autosave_schedule` of a game.
+/// These are stored in their own directory and only the newest `scheduled_autosaves` are kept,
+/// so they're never rotated away by frequent autosaves.
+/// Scheduled autosaves always contain all files.
+pub fn scheduled_autosave_game(config: &Config, game: &str, progress: Progress) -> Result<PathBuf> {
+    let game_config = config.game(game)?;
+    let scheduled_dir = config.scheduled_dir(game);
+    create_dir_all(&scheduled_dir).context(format!("Failed to create {scheduled_dir:?}"))?;
+    create_autosave(
+        config,
+        game,
+        &scheduled_dir,
+        game_config.scheduled_autosaves,
+        &RetentionPolicy::Count,
+        BackupStrategy::Full,
+        progress,
+    )
+    .context("Failed to create scheduled autosave")
+}
+
+/// Create a new autosave in `autosave_dir` and remove the oldest ones, so only `keep` autosaves
+/// remain, including the new one.
+fn create_autosave(
+    config: &Config,
+    game: &str,
+    autosave_dir: &Path,
+    keep: usize,
+    policy: &RetentionPolicy,
+    strategy: BackupStrategy,
+    progress: Progress,
+) -> Result<PathBuf> {
+    let game_config = config.game(game)?;
+    check_empty_savegame_location(game, game_config)?;
+
+    // Don't fill up the disk with autosaves. Failing mid-write would only leave broken archives.
+    let available = available_space(&config.game_backup_directory(game))?;
+    if available < config.min_free_space() {
+        return Err(LowDiskSpace { available }.into());
+    }
+
+    // Delete the expired autosaves, so exactly one slot is left for the new save.
+    let mut save_files = get_archive_files(autosave_dir)?;
+    // Determine the name before rotating, so the counter continues after the newest save.
+    let format = game_config.archive_format;
+    let file_name = autosave_name(game_config, &save_files, &Local::now())?;
+    let autosave_path = autosave_dir.join(format!("{file_name}{}", format.extension()));
+
+    remove_expired_autosaves(&mut save_files, keep.saturating_sub(1), policy)?;
+
+    let temp_directory = config.temp_directory();
+    let options = ArchiveOptions {
+        temp_directory: temp_directory.as_deref(),
+        ..game_config.archive_options()
+    };
+    match strategy {
+        BackupStrategy::Full => save_game(
+            &game_config.savegame_location(),
+            &autosave_path,
+            format,
+            &options,
+            progress,
+        ),
+        BackupStrategy::Incremental => {
+            save_incremental(game_config, &save_files, &autosave_path, &options, progress)
+        }
+    }?;
+
+    Ok(autosave_path)
+}
+
+/// Remove all autosaves of a game, which exceed its `autosaves` count and aren't retained by its
+/// retention policy. This is needed, once the count has been decreased.
+/// Returns the amount of removed autosaves.
+pub fn rotate_autosaves(config: &Config, game: &str) -> Result<usize> {
+    let surplus = surplus_autosaves(config, game)?;
+    for save in surplus.iter() {
+        let path = &save.path;
+        remove_file(path).context(format!("Failed to remove old autosave: {path:?}"))?;
+    }
+
+    Ok(surplus.len())
+}
+
+/// Return all autosaves of a game, which exceed its `autosaves` count and aren't retained by its
+/// retention policy. Bases of incremental autosaves are kept, as long as they're needed.
+pub fn surplus_autosaves(config: &Config, game: &str) -> Result<Vec<SaveFile>> {
+    let game_config = config.game(game)?;
+    let autosave_dir = config.autosave_dir(game);
+    if !autosave_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut save_files = get_archive_files(&autosave_dir)?;
+    let old_saves = expired_autosaves(
+        &mut save_files,
+        game_config.autosaves,
+        &game_config.retention,
+    );
+    let bases = required_bases(&save_files);
+
+    Ok(old_saves
+        .into_iter()
+        .filter(|save| {
+            !save
+                .path
+                .file_name()
+                .is_some_and(|name| bases.contains(name))
+        })
+        .collect())
+}
+
+/// Remove all autosaves, which aren't retained, from the disk and from `saves`.
+/// Returns the amount of removed autosaves.
+fn remove_expired_autosaves(
+    saves: &mut Vec<SaveFile>,
+    keep: usize,
+    policy: &RetentionPolicy,
+) -> Result<usize> {
+    let old_saves = expired_autosaves(saves, keep, policy);
+    if old_saves.is_empty() {
+        return Ok(0);
+    }
+
+    // Incremental autosaves cannot be restored without their base.
+    // Bases are therefore kept, until no other autosave depends on them anymore.
+    let bases = required_bases(saves);
+    let mut removed = 0;
+    for save in old_saves {
+        let path = &save.path;
+        if path.file_name().is_some_and(|name| bases.contains(name)) {
+            saves.push(save);
+            continue;
+        }
+        // The save might've been removed in the meantime, which is just fine.
+        if !path.exists() {
+            continue;
+        }
+        remove_file(path).context(format!("Failed to remove old autosave: {path:?}"))?;
+        removed += 1;
+    }
+
+    Ok(removed)
+}
+
+/// Remove all autosaves, which aren't retained by the policy, from `saves` and return them.
+/// `saves` must be sorted by date in descending order, like it's done by [get_archive_files].
+///
+/// The newest `keep` autosaves are always retained. The tiers of [RetentionPolicy::Tiered] then
+/// retain the newest autosave of each hour, day and week, until their count has been reached.
+pub fn expired_autosaves(
+    saves: &mut Vec<SaveFile>,
+    keep: usize,
+    policy: &RetentionPolicy,
+) -> Vec<SaveFile> {
+    let RetentionPolicy::Tiered {
+        hourly,
+        daily,
+        weekly,
+    } = *policy
+    else {
+        // The oldest saves are at the end.
+        return saves.split_off(keep.min(saves.len()));
+    };
+
+    // Each tier has a remaining count and the period of the last autosave it retained.
+    let mut tiers: [(usize, Option<String>, &str); 3] = [
+        (hourly, None, "%Y-%m-%d %H"),
+        (daily, None, "%Y-%m-%d"),
+        (weekly, None, "%G-%V"),
+    ];
+    let mut expired = Vec::new();
+    for (index, save) in std::mem::take(saves).into_iter().enumerate() {
+        let mut retain = index < keep;
+        for (remaining, last_period, format) in tiers.iter_mut() {
+            let period = save.last_modified.format(format).to_string();
+            if *remaining > 0 && last_period.as_ref() != Some(&period) {
+                *remaining -= 1;
+                *last_period = Some(period);
+                retain = true;
+            }
+        }
+
+        if retain {
+            saves.push(save);
+        } else {
+            expired.push(save);
+        }
+    }
+
+    expired
+}
+
+/// The file names of all bases of the given incremental autosaves.
+fn required_bases(saves: &[SaveFile]) -> HashSet<OsString> {
+    saves
+        .iter()
+        // Saves that cannot be read cannot be restored either, so their base isn't needed.
+        .filter_map(|save| read_manifest(&save.path).ok().flatten())
+        .filter_map(|manifest| manifest.base.map(OsString::from))
+        .collect()
+}
+
+/// Create an autosave at `dest`, which only contains the files that changed since the last full
+/// autosave. The list of all files and their hashes is stored in the autosave's manifest.
+///
+/// A full autosave is created instead, if there's none yet or if the changed files make up more
+/// than half of its size. Otherwise, the incremental autosaves would grow with every change,
+/// while depending on an ever older base.
+fn save_incremental(
+    game_config: &GameConfig,
+    autosaves: &[SaveFile],
+    dest: &Path,
+    options: &ArchiveOptions,
+    progress: Progress,
+) -> Result<()> {
+    let (cwd, source_filename) = split_location(&game_config.savegame_location())?;
+    let (cwd, source_filename) = (cwd.as_path(), source_filename.as_os_str());
+
+    // A broken autosave shouldn't prevent any further autosaves.
+    let base = match autosaves.first().map(find_base).transpose() {
+        Ok(base) => base.flatten(),
+        Err(err) => {
+            warn!("Creating a full autosave, as the last autosave cannot be read: {err:#}");
+            None
+        }
+    };
+
+    let mut manifest = build_manifest(
+        cwd,
+        source_filename,
+        options,
+        base.as_ref().map(|(_, base)| base),
+    )?;
+    let mut only = None;
+    if let Some((base_name, base)) = base {
+        let changed = manifest.changed_files(&base);
+        let changed_size: u64 = changed
+            .iter()
+            .filter_map(|path| manifest.files.get(path))
+            .map(|entry| entry.size)
+            .sum();
+        if changed_size * 2 <= base.size() {
+            manifest.base = Some(base_name);
+            only = Some(changed.iter().map(PathBuf::from).collect::<BTreeSet<_>>());
+        }
+    }
+
+    // The manifest is written into its own directory, as it's stored by its file name.
+    let mut manifest_dir_name = OsString::from(".");
+    manifest_dir_name.push(dest.file_name().unwrap_or_default());
+    manifest_dir_name.push(".manifest");
+    let manifest_dir = dest.with_file_name(manifest_dir_name);
+    if symlink_metadata(&manifest_dir).is_ok() {
+        remove_dir_all(&manifest_dir).context(format!("Failed to remove {manifest_dir:?}"))?;
+    }
+    create_dir(&manifest_dir).context(format!("Failed to create {manifest_dir:?}"))?;
+
+    let manifest_path = manifest_dir.join(MANIFEST_NAME);
+    let result = toml::to_string(&manifest)
+        .context("Failed to serialize manifest")
+        .and_then(|content| {
+            write(&manifest_path, content).context(format!("Failed to write {manifest_path:?}"))
+        })
+        .and_then(|_| {
+            let options = ArchiveOptions {
+                only: only.as_ref(),
+                extra_file: Some(&manifest_path),
+                ..*options
+            };
+            create_archive(
+                game_config.archive_format,
+                cwd,
+                source_filename,
+                dest,
+                &options,
+                progress,
+            )
+        });
+    let cleanup =
+        remove_dir_all(&manifest_dir).context(format!("Failed to remove {manifest_dir:?}"));
+
+    result.and(cleanup)
+}
+
+/// Find the full autosave, which a new incremental autosave can be based on.
+/// That's either the newest autosave itself or its base.
+fn find_base(newest: &SaveFile) -> Result<Option<(String, Manifest)>> {
+    let Some(manifest) = read_manifest(&newest.path)? else {
+        return Ok(None);
+    };
+    let Some(base_name) = manifest.base else {
+        let name = newest.path.file_name().unwrap_or_default();
+        return Ok(Some((name.to_string_lossy().into_owned(), manifest)));
+    };
+
+    let base_path = newest.path.with_file_name(&base_name);
+    if !base_path.exists() {
+        return Ok(None);
+    }
+    let base = read_manifest(&base_path)?.filter(|base| base.base.is_none());
+
+    Ok(base.map(|base| (base_name, base)))
+}
+
+/// Build the name of a new autosave from the game's `autosave_name_template`.
+///
+/// The `{counter}` placeholder is replaced with the next number after the highest counter of all
+/// existing autosaves that share the same rest of the name.
+pub fn autosave_name(
+    game_config: &GameConfig,
+    existing: &[SaveFile],
+    time: &DateTime<Local>,
+) -> Result<String> {
+    let name = game_config.format_autosave_name(time)?;
+    let name = match name.split_once(AUTOSAVE_COUNTER) {
+        Some((prefix, suffix)) => {
+            let highest = existing
+                .iter()
+                .filter_map(|save| {
+                    save.file_name
+                        .strip_prefix(prefix)?
+                        .strip_suffix(suffix)?
+                        .parse::<u64>()
+                        .ok()
+                })
+                .max()
+                .unwrap_or(0);
+            format!("{prefix}{:03}{suffix}", highest + 1)
+        }
+        None => name,
+    };
+
+    sanitize_save_name(&name).context("Invalid autosave_name_template")
+}
+
+/// A wrapper around [save_game], which handles manual saving of files.
+/// Returns the path of the new save.
+pub fn manually_save_game(
+    config: &Config,
+    game: &str,
+    name: &str,
+    progress: Progress,
+) -> Result<PathBuf> {
+    let save_dir = config.save_dir(game);
+    let game_config = config.game(game)?;
+    check_empty_savegame_location(game, game_config)?;
+
+    let name = sanitize_save_name(name)?;
+    let format = game_config.archive_format;
+    let file_name = format!("{name}{}", format.extension());
+
+    let save_path = save_dir.join(file_name);
+    let temp_directory = config.temp_directory();
+    let options = ArchiveOptions {
+        temp_directory: temp_directory.as_deref(),
+        ..game_config.archive_options()
+    };
+    save_game(
+        &game_config.savegame_location(),
+        &save_path,
+        format,
+        &options,
+        progress,
+    )
+    .context("Failed to create manual save")?;
+
+    Ok(save_path)
+}
+
+/// Saving an empty savegame location results in useless backups.
+/// This is most likely caused by a misconfigured path or a game that didn't create saves yet.
+///
+/// Depending on the config, we either only warn about it or refuse to save.
+fn check_empty_savegame_location(game: &str, game_config: &GameConfig) -> Result<()> {
+    let location = game_config.savegame_location();
+    if contains_files(&location)? {
+        return Ok(());
+    }
+
+    if game_config.skip_empty_saves {
+        return Err(EmptySavegameLocation.into());
+    }
+    warn!("Savegame location of {game} is empty: {location:?}");
+
+    Ok(())
+}
+
+/// Return the oldest manual saves of a game that exceed its `max_manual_saves` limit.
+pub fn surplus_manual_saves(config: &Config, game: &str) -> Result<Vec<SaveFile>> {
+    let game_config = config.game(game)?;
+    let max_manual_saves = match game_config.max_manual_saves {
+        Some(max_manual_saves) => max_manual_saves,
+        None => return Ok(Vec::new()),
+    };
+
+    // Saves are sorted by date in descending order, the oldest ones are at the end.
+    let mut save_files = get_archive_files(&config.save_dir(game))?;
+    if save_files.len() <= max_manual_saves {
+        return Ok(Vec::new());
+    }
+
+    Ok(save_files.split_off(max_manual_saves))
+}
+
+/// Create an archive of `source` at `dest`.
+/// The archive contains `source` itself, with its file name as the top-level entry.
+pub fn save_game(
+    source: &Path,
+    dest: &Path,
+    format: ArchiveFormat,
+    options: &ArchiveOptions,
+    progress: Progress,
+) -> Result<()> {
+    // Use the parent of the source as working directory for the archiver.
+    let (cwd, source_filename) =
+        split_location(source).context(format!("Cannot save savegame location {source:?}"))?;
+
+    create_archive(format, &cwd, &source_filename, dest, options, progress)
+}
+
+/// Archive the current state of a game's restore location to `dest`.
+/// This is done right before a restore, so the restore can be undone.
+/// The restore location is used, as that's the directory the restore is going to overwrite.
+pub fn snapshot_savegame(config: &Config, game: &str, dest: &Path) -> Result<()> {
+    let game_config = config.game(game)?;
+    let temp_directory = config.temp_directory();
+    // The snapshot is only a safety net, which shouldn't prevent the restore.
+    let options = ArchiveOptions {
+        temp_directory: temp_directory.as_deref(),
+        skip_space_check: true,
+        ..game_config.archive_options()
+    };
+    save_game(
+        &game_config.restore_location(),
+        dest,
+        ArchiveFormat::from_path(dest)?,
+        &options,
+        &|_, _| {},
+    )
+    .context("Failed to create snapshot before restore")
+}
+
+/// Make sure a user provided name can be safely used as the file name of a save.
+///
+/// The name is used as a path and passed to `tar`, so path separators, path traversal and
+/// leading dashes (which would be interpreted as `tar` flags) are rejected.
+pub fn sanitize_save_name(name: &str) -> Result<String> {
+    // Whitespace-only names would result in invisible entries in the save lists.
+    if name.trim().is_empty() {
+        bail!("Name cannot be empty");
+    }
+    if name.contains('/') || name.contains('\0') {
+        bail!("Name cannot contain '/'");
+    }
+    if name.starts_with('-') {
+        bail!("Name cannot start with '-'");
+    }
+    if name.starts_with('.') {
+        bail!("Name cannot start with '.'");
+    }
+
+    Ok(name.to_string())
+}
+
+/// Take a savefile and restore the save of the respective game.
+///
+/// The save is extracted next to the savegame location first and only moved into place, once
+/// the extraction succeeded. A corrupt or truncated save thereby leaves the current save untouched.
+/// Zstd compressed saves carry a content checksum, so even single flipped bits fail the
+/// extraction.
+pub fn restore_save(
+    config: &Config,
+    game_name: &str,
+    save: &SaveFile,
+    progress: Progress,
+) -> Result<()> {
+    let game_config = config.game(game_name)?;
+    let dest = game_config.restore_location();
+    // The archive contains the savegame location relative to its parent directory.
+    let (_, source_name) = split_location(&game_config.savegame_location())?;
+
+    // The format of the save might differ from the configured one, e.g. if the format has been
+    // changed or the save has been created somewhere else.
+    // Detect it before touching any files, so unknown formats don't leave us without a save.
+    let format = ArchiveFormat::from_path(&save.path)?;
+
+    let (cwd, file_name) =
+        split_location(&dest).context(format!("Cannot restore into location {dest:?}"))?;
+    let dest = cwd.join(&file_name);
+    // The restore location doesn't exist on fresh installs, e.g. when setting up a new machine
+    // from backups. The save itself is moved into place, but its parents have to exist.
+    create_dir_all(&cwd).context(format!("Failed to create directory {cwd:?}"))?;
+    // The staging directory is placed next to the restore location, so it's on the same file
+    // system and the files can be moved into place without copying them.
+    let mut staging_name = OsString::from(".");
+    staging_name.push(file_name);
+    staging_name.push(".restore");
+    let staging = cwd.join(staging_name);
+    // Remove the leftovers of an interrupted restore.
+    if symlink_metadata(&staging).is_ok() {
+        remove_dir_all(&staging).context(format!("Failed to remove {staging:?}"))?;
+    }
+    create_dir(&staging).context(format!("Failed to create {staging:?}"))?;
+
+    let staged = staging.join(source_name);
+    let result = extract_and_swap(game_config, save, format, &staged, &dest, progress);
+    let cleanup = remove_dir_all(&staging).context(format!("Failed to remove {staging:?}"));
+
+    result.and(cleanup)
+}
+
+/// Extract a save into the staging directory and replace the restore location with it.
+/// `staged` is the path of the savegame location inside the staging directory.
+fn extract_and_swap(
+    game_config: &GameConfig,
+    save: &SaveFile,
+    format: ArchiveFormat,
+    staged: &Path,
+    dest: &Path,
+    progress: Progress,
+) -> Result<()> {
+    let staging = staged.parent().unwrap_or(staged);
+    // Incremental saves only contain the changed files, the other ones are taken from their base.
+    let manifest = read_manifest(&save.path).context(format!(
+        "Failed to read {:?}, the existing save hasn't been touched",
+        save.path
+    ))?;
+    let manifest = manifest.filter(|manifest| manifest.base.is_some());
+    if let Some(base) = manifest
+        .as_ref()
+        .and_then(|manifest| manifest.base.as_ref())
+    {
+        let base_path = save.path.with_file_name(base);
+        if !base_path.exists() {
+            bail!(
+                "The base {base_path:?} of incremental save {:?} is missing, the existing save \
+                hasn't been touched",
+                save.path
+            );
+        }
+        extract_archive(
+            ArchiveFormat::from_path(&base_path)?,
+            &base_path,
+            staging,
+            progress,
+        )
+        .context(format!(
+            "Failed to extract {base_path:?}, the existing save hasn't been touched"
+        ))?;
+    }
+    extract_archive(format, &save.path, staging, progress).context(format!(
+        "Failed to extract {:?}, the existing save hasn't been touched",
+        save.path
+    ))?;
+    if let Some(manifest) = &manifest {
+        remove_unlisted(staging, manifest)?;
+    }
+    // Snapshots taken before a restore contain the restore location, whose name may differ from
+    // the savegame location's.
+    let snapshot_staged = staging.join(dest.file_name().unwrap_or_default());
+    let staged = if symlink_metadata(staged).is_err() && symlink_metadata(&snapshot_staged).is_ok()
+    {
+        snapshot_staged.as_path()
+    } else {
+        staged
+    };
+    if symlink_metadata(staged).is_err() {
+        bail!(
+            "Save {:?} doesn't contain {:?}, the existing save hasn't been touched",
+            save.path,
+            staged.file_name().unwrap_or_default()
+        );
+    }
+
+    if symlink_metadata(dest).is_err() {
+        rename(staged, dest).context(format!("Failed to move restored save to {dest:?}"))?;
+    } else if dest.is_dir() != staged.is_dir() {
+        bail!(
+            "Save {:?} and the restore location {dest:?} aren't of the same type",
+            save.path
+        );
+    } else if dest.is_dir() {
+        // Ignored files aren't part of the save, which is why they're left untouched.
+        let ignore = build_ignore(dest, &game_config.ignored_files)?;
+        remove_all_children(dest, &ignore)
+            .context("Failed while removing existing savefiles during restore.")?;
+        move_children(staged, dest).context(format!("Failed to move restored save to {dest:?}"))?;
+    } else {
+        // Some games only use a single save file instead of a directory.
+        // In that case, only that file is replaced.
+        rename(staged, dest).context(format!("Failed to move restored save to {dest:?}"))?;
+    }
+
+    // Siblings are replaced as a whole. Siblings that aren't part of the save are kept.
+    for sibling in game_config.include_siblings.iter() {
+        let staged_sibling = staging.join(sibling);
+        if symlink_metadata(&staged_sibling).is_err() {
+            continue;
+        }
+        let target = dest.with_file_name(sibling);
+        if let Ok(metadata) = symlink_metadata(&target) {
+            if metadata.is_dir() {
+                remove_dir_all(&target)
+            } else {
+                remove_file(&target)
+            }
+            .context(format!("Failed to remove existing sibling {target:?}"))?;
+        }
+        rename(&staged_sibling, &target)
+            .context(format!("Failed to move restored sibling to {target:?}"))?;
+    }
+
+    Ok(())
+}
+
+/// Move all entries of a directory into another directory.
+/// Directories that exist in both, e.g. as they contain ignored files, are merged.
+fn move_children(from: &Path, to: &Path) -> Result<()> {
+    let dir_files = read_dir(from).context(format!("Couldn't read directory {from:?}"))?;
+    for dir_entry in dir_files {
+        let dir_entry = dir_entry.context(format!("Couldn't get dir entry in {from:?}"))?;
+        let source = dir_entry.path();
+        let target = to.join(dir_entry.file_name());
+
+        let target_is_dir = symlink_metadata(&target).is_ok_and(|metadata| metadata.is_dir());
+        let file_type = dir_entry
+            .file_type()
+            .context(format!("Couldn't get file type of {source:?}"))?;
+        if target_is_dir && file_type.is_dir() {
+            move_children(&source, &target)?;
+        } else {
+            rename(&source, &target).context(format!("Failed to move {source:?}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// What a restore is going to change in a game's savegame location.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RestoreSummary {
+    /// The amount of files that're written.
+    pub files: usize,
+    /// The uncompressed size of all written files.
+    pub bytes: u64,
+    /// The amount of existing files that're removed.
+    pub removed: usize,
+}
+
+impl fmt::Display for RestoreSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "writes {} files ({}) and removes {} existing files",
+            self.files,
+            format_size(self.bytes),
+            self.removed
+        )
+    }
+}
+
+/// Determine what restoring a save would change, without touching any files.
+/// The written files are taken from the archive listing, all existing files, which aren't
+/// ignored, are removed.
+pub fn restore_summary(config: &Config, game: &str, save: &SaveFile) -> Result<RestoreSummary> {
+    let game_config = config.game(game)?;
+    let files = list_save(save)?;
+
+    let location = game_config.restore_location();
+    let ignore = build_ignore(&location, &game_config.ignored_files)?;
+    let mut removed = count_files(&location, &ignore)?;
+    // Siblings are only replaced, if the save contains them.
+    for sibling in game_config.include_siblings.iter() {
+        if files
+            .keys()
+            .any(|path| Path::new(path).starts_with(sibling))
+        {
+            removed += count_files(&location.with_file_name(sibling), &Gitignore::empty())?;
+        }
+    }
+
+    Ok(RestoreSummary {
+        files: files.len(),
+        bytes: files.values().map(|file| file.size).sum(),
+        removed,
+    })
+}
+
+/// The result of [verify_saves].
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// The amount of saves that passed all checks.
+    pub healthy: usize,
+    /// All corrupt saves and the reason why they're corrupt.
+    pub corrupt: Vec<(SaveFile, String)>,
+}
+
+/// Check all saves of a game for corruption, including its autosaves, its trash and the safety
+/// snapshots, which are taken before restores.
+///
+/// Each archive is tested with [check_archive]. Incremental autosaves additionally need their
+/// base, as they cannot be restored without it.
+/// The progress is reported as the amount of checked saves.
+pub fn verify_saves(config: &Config, game: &str, progress: Progress) -> Result<VerifyReport> {
+    config.game(game)?;
+
+    let trash_dir = config.trash_dir(game);
+    // Trashed saves are renamed, so the bases of incremental saves cannot be found by their name.
+    // Their bases are checked once they've been restored from the trash.
+    let mut saves = Vec::new();
+    for (dir, check_base) in [
+        (config.save_dir(game), true),
+        (config.autosave_dir(game), true),
+        (config.scheduled_dir(game), true),
+        (trash_dir.join("autosaves"), false),
+        (trash_dir.join("scheduled"), false),
+        (trash_dir, false),
+        (config.snapshot_dir(game), true),
+    ] {
+        if dir.exists() {
+            let files = get_archive_files(&dir)?;
+            saves.extend(files.into_iter().map(|save| (save, check_base)));
+        }
+    }
+
+    let total = saves.len() as u64;
+    let mut report = VerifyReport::default();
+    for (index, (save, check_base)) in saves.into_iter().enumerate() {
+        progress(index as u64, total);
+        match check_save(&save, check_base) {
+            Ok(()) => report.healthy += 1,
+            Err(err) => report.corrupt.push((save, format!("{err:#}"))),
+        }
+    }
+    progress(total, total);
+
+    Ok(report)
+}
+
+/// Check a single save for corruption.
+/// If `check_base` is set, the base of incremental saves has to exist next to them.
+fn check_save(save: &SaveFile, check_base: bool) -> Result<()> {
+    check_archive(save.format, &save.path)?;
+    if !check_base {
+        return Ok(());
+    }
+
+    let manifest = read_manifest(&save.path)?;
+    if let Some(base) = manifest.and_then(|manifest| manifest.base) {
+        if !save.path.with_file_name(&base).exists() {
+            bail!("The base {base} of this incremental save is missing");
+        }
+    }
+
+    Ok(())
+}
+
+/// The differences between two saves of the same game.
+///
+/// Only the archive listings or manifests are compared, as extracting both saves would be too
+/// slow.
+/// Files are considered to be changed, if their size differs. Files of the same size are
+/// compared by their content hashes, if both saves have a manifest. Otherwise, their listed
+/// modification times are compared, which are only precise to the minute.
+/// Reproducible archives don't contain modification times, so only their sizes are compared.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SaveComparison {
+    /// The amount of files in the first save.
+    pub first_files: usize,
+    /// The uncompressed size of all files in the first save.
+    pub first_bytes: u64,
+    /// The amount of files in the second save.
+    pub second_files: usize,
+    /// The uncompressed size of all files in the second save.
+    pub second_bytes: u64,
+    /// Files that only exist in the second save, sorted by path.
+    pub added: Vec<String>,
+    /// Files that only exist in the first save, sorted by path.
+    pub removed: Vec<String>,
+    /// Files that exist in both saves, but differ in size or content, sorted by path.
+    pub changed: Vec<String>,
+}
+
+/// Compare the files of two saves.
+pub fn compare_saves(first: &SaveFile, second: &SaveFile) -> Result<SaveComparison> {
+    let first_files = list_save(first)?;
+    let second_files = list_save(second)?;
+
+    let mut comparison = SaveComparison {
+        first_files: first_files.len(),
+        first_bytes: first_files.values().map(|file| file.size).sum(),
+        second_files: second_files.len(),
+        second_bytes: second_files.values().map(|file| file.size).sum(),
+        ..Default::default()
+    };
+    for (path, file) in second_files.iter() {
+        match first_files.get(path) {
+            None => comparison.added.push(path.clone()),
+            Some(first_file) if first_file.differs(file) => comparison.changed.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    comparison.removed = first_files
+        .into_keys()
+        .filter(|path| !second_files.contains_key(path))
+        .collect();
+
+    comparison.added.sort();
+    comparison.removed.sort();
+    comparison.changed.sort();
+
+    Ok(comparison)
+}
+
+/// A file of a save, as it's compared by [compare_saves].
+struct ListedFile {
+    size: u64,
+    /// The modification time in local time, truncated to the minute.
+    modified: Option<NaiveDateTime>,
+    /// The content hash, which is only known for saves with a manifest.
+    hash: Option<String>,
+}
+
+impl ListedFile {
+    /// Whether the file has been changed between two saves.
+    fn differs(&self, other: &ListedFile) -> bool {
+        if self.size != other.size {
+            return true;
+        }
+        if let (Some(hash), Some(other_hash)) = (&self.hash, &other.hash) {
+            return hash != other_hash;
+        }
+        match (self.modified, other.modified) {
+            (Some(modified), Some(other_modified)) => modified != other_modified,
+            _ => false,
+        }
+    }
+}
+
+/// List all files of a save with their uncompressed size and modification time.
+/// Incremental saves only contain the changed files, which is why their manifest is used instead.
+fn list_save(save: &SaveFile) -> Result<HashMap<String, ListedFile>> {
+    if let Some(manifest) = read_manifest(&save.path)? {
+        let files = manifest.files.into_iter().map(|(path, entry)| {
+            let modified = DateTime::from_timestamp_nanos(entry.modified)
+                .with_timezone(&Local)
+                .naive_local()
+                .with_second(0)
+                .and_then(|modified| modified.with_nanosecond(0));
+            let file = ListedFile {
+                size: entry.size,
+                modified,
+                hash: Some(entry.hash),
+            };
+            (path, file)
+        });
+        return Ok(files.collect());
+    }
+
+    let entries = list_archive_entries(ArchiveFormat::from_path(&save.path)?, &save.path)?;
+    Ok(entries
+        .into_iter()
+        .map(|(path, entry)| {
+            let file = ListedFile {
+                size: entry.size,
+                modified: entry.modified,
+                hash: None,
+            };
+            (path, file)
+        })
+        .collect())
+}
+
+/// Read a single file of a save, without extracting the whole save.
+/// `path` is relative to the parent of the savegame location, just like the paths that are
+/// compared by [compare_saves]. Returns `None`, if the save doesn't contain that file.
+///
+/// Use the `zip` archive format for fast access to single files of huge saves.
+/// See [extract_file].
+pub fn read_save_file(save: &SaveFile, path: &str) -> Result<Option<Vec<u8>>> {
+    // Incremental saves only contain the changed files, the other ones are taken from their base.
+    let manifest = read_manifest(&save.path)?;
+    let Some((base, files)) = manifest.and_then(|manifest| Some((manifest.base?, manifest.files)))
+    else {
+        return extract_file(save.format, &save.path, path);
+    };
+    if !files.contains_key(path) {
+        return Ok(None);
+    }
+    if let Some(content) = extract_file(save.format, &save.path, path)? {
+        return Ok(Some(content));
+    }
+
+    let base_path = save.path.with_file_name(base);
+    if !base_path.exists() {
+        bail!(
+            "The base {base_path:?} of incremental save {:?} is missing",
+            save.path
+        );
+    }
+    extract_file(ArchiveFormat::from_path(&base_path)?, &base_path, path)
+}
+
+/// Remove all files in a directory, except for ignored ones.
+/// We remove all files in a `savegame_location` before moving a restored save into place.
+/// That way we ensure that no artifacts from old or newer saves remain.
+pub fn remove_all_children(path: &Path, ignore: &Gitignore) -> Result<()> {
+    let dir_files = read_dir(path).context(format!("Couldn't read directory {path:?}"))?;
+    for dir_entry in dir_files {
+        let dir_entry = dir_entry.context(format!("Couldn't get dir entry in {path:?}"))?;
+        let path = dir_entry.path();
+        // Don't follow symlinks, only the links themselves are removed.
+        let file_type = dir_entry
+            .file_type()
+            .context(format!("Couldn't get file type of {path:?}"))?;
+        let is_dir = file_type.is_dir();
+        if ignore.matched(&path, is_dir).is_ignore() {
+            continue;
+        }
+
+        if is_dir && ignore.is_empty() {
+            remove_dir_all(&path).context(format!("Failed to remove {path:?}"))?;
+        } else if is_dir {
+            // Keep directories that still contain ignored files.
+            remove_all_children(&path, ignore)?;
+            let mut children =
+                read_dir(&path).context(format!("Couldn't read directory {path:?}"))?;
+            if children.next().is_none() {
+                remove_dir(&path).context(format!("Failed to remove {path:?}"))?;
+            }
+        } else {
+            remove_file(&path).context(format!("Failed to remove {path:?}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Take an existing savefile and rename it.
+/// Returns the new path of the save.
+pub fn rename_save(save: &SaveFile, new_name: &str) -> Result<PathBuf> {
+    if !save.path.exists() || !save.path.is_file() {
+        bail!("Trying to rename non-existing file {:?}", &save.path);
+    }
+    let new_path = save
+        .path
+        .parent()
+        .expect("The save shouldn't be the filesystem root.");
+    let new_name = sanitize_save_name(new_name)?;
+    let new_path = new_path.join(format!("{new_name}{}", save.format.extension()));
+    std::fs::rename(&save.path, &new_path)
+        .context(format!("Failed to rename {:?} to {new_path:?}", save.path))?;
+
+    Ok(new_path)
+}
+
+/// Take an existing savefile and delete it.
+pub fn delete_save(save: &SaveFile) -> Result<()> {
+    if !save.path.exists() || !save.path.is_file() {
+        bail!("Trying to delete non-existing file {:?}", &save.path);
+    }
+    std::fs::remove_file(&save.path).context(format!("Failed to delete {:?}", save.path))?;
+
+    Ok(())
+}
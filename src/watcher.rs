@@ -1,8 +1,14 @@
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
 use crossbeam_channel::Sender;
+use futures::future::try_join_all;
+use ignore_files::IgnoreFile;
 use log::{error, info};
 use watchexec::Watchexec;
 use watchexec_events::{
@@ -11,58 +17,175 @@ use watchexec_events::{
 };
 use watchexec_filterer_globset::GlobsetFilterer;
 
-use crate::config::{Config, GameConfig};
+use game_saver::{
+    config::{Config, GameConfig, TriggerEvent},
+    files::ignore_files,
+};
 
 /// This is th message that will be send via the mpsc channel as soon as files change.
 #[derive(Debug)]
 pub struct Update {
     pub game_name: String,
-    #[allow(dead_code)]
     pub locations: Vec<PathBuf>,
+    /// The kinds of all events that triggered this update.
+    pub kinds: Vec<String>,
     pub time: DateTime<Local>,
 }
 
+/// How often a stopped watcher is restarted in a row, before its game is given up on.
+const MAX_WATCHER_RESTARTS: u32 = 5;
+/// The delay before the first restart of a watcher, which doubles with each further restart.
+const WATCHER_RESTART_DELAY: Duration = Duration::from_secs(1);
+/// Watchers that ran for this long are considered to be stable again.
+const WATCHER_STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// The messages that're sent by the file watchers via the mpsc channel.
+#[derive(Debug)]
+pub enum WatcherMessage {
+    /// Files of a game changed.
+    Update(Update),
+    /// The file watcher of a game ran into an error.
+    /// Once a watcher `stopped`, no further changes of its game are detected.
+    Error {
+        game_name: String,
+        error: String,
+        stopped: bool,
+    },
+}
+
 /// Convenience wrapper around `spawn_watcher` for multiple watchers.
-pub async fn spawn_watchers(config: &Config, sender: &Sender<Update>) -> Result<()> {
+/// All watchers are spawned concurrently, as building them may take a while for big directories.
+pub async fn spawn_watchers(config: &Config, sender: &Sender<WatcherMessage>) -> Result<()> {
+    let mut watchers = Vec::new();
     for (name, game_config) in &config.games {
         if !game_config.savegame_location().exists() {
             error!("Cannot find savegame_location for game {}", name);
             continue;
         }
         info!("Building watcher for {}", name);
-        spawn_watcher(name, game_config, sender).await?;
+        watchers.push(spawn_watcher(name, game_config, sender));
     }
 
+    try_join_all(watchers).await?;
+
     Ok(())
 }
 
 /// Create a new watcher from a GameConfig and spin it of in its own thread.
 /// As soon as files change, the handler sends notifications via the mpsc channel.
+///
+/// The watcher is supervised and restarted, if it stops. See [supervise_watcher].
 async fn spawn_watcher(
     game_name: &str,
     game_config: &GameConfig,
-    sender: &Sender<Update>,
+    sender: &Sender<WatcherMessage>,
 ) -> Result<()> {
+    let watcher = build_watcher(game_name, game_config, sender).await?;
+    tokio::spawn(supervise_watcher(
+        game_name.to_string(),
+        game_config.clone(),
+        sender.clone(),
+        watcher,
+    ));
+    info!("Spawned watcher thread for {}", game_name);
+
+    Ok(())
+}
+
+/// Run a watcher and restart it, whenever it stops.
+///
+/// Filesystems occasionally drop their watches, which stops the watcher. Restarts are delayed
+/// by [WATCHER_RESTART_DELAY], which doubles with each consecutive restart. Once a watcher
+/// stopped [MAX_WATCHER_RESTARTS] times in a row, its game is given up on.
+/// Restarts of a watcher that ran for [WATCHER_STABLE_AFTER] aren't consecutive anymore.
+async fn supervise_watcher(
+    game_name: String,
+    game_config: GameConfig,
+    sender: Sender<WatcherMessage>,
+    mut watcher: Arc<Watchexec>,
+) {
+    let mut restarts = 0;
+    loop {
+        let started = Instant::now();
+        let mut error = match watcher.main().await {
+            Ok(Ok(())) => "The watcher exited".to_string(),
+            Ok(Err(err)) => err.to_string(),
+            Err(err) => err.to_string(),
+        };
+        if started.elapsed() >= WATCHER_STABLE_AFTER {
+            restarts = 0;
+        }
+
+        // Rebuilding the watcher fails as well, e.g. if the savegame location has been removed.
+        loop {
+            if restarts == MAX_WATCHER_RESTARTS {
+                error!("File watcher for game {game_name} stopped for good: {error}");
+                let _ = sender.send(WatcherMessage::Error {
+                    game_name,
+                    error: format!("{error} (gave up after {restarts} restarts)"),
+                    stopped: true,
+                });
+                return;
+            }
+
+            let delay = WATCHER_RESTART_DELAY * 2_u32.pow(restarts);
+            restarts += 1;
+            error!("File watcher for game {game_name} stopped: {error}");
+            let _ = sender.send(WatcherMessage::Error {
+                game_name: game_name.clone(),
+                error: format!(
+                    "{error}. Restarting it in {}s (attempt {restarts}/{MAX_WATCHER_RESTARTS})",
+                    delay.as_secs()
+                ),
+                stopped: false,
+            });
+            tokio::time::sleep(delay).await;
+
+            match build_watcher(&game_name, &game_config, &sender).await {
+                Ok(new_watcher) => {
+                    info!("Restarted watcher for {game_name}");
+                    watcher = new_watcher;
+                    break;
+                }
+                Err(err) => error = format!("{err:#}"),
+            }
+        }
+    }
+}
+
+/// Build the watcher of a game. It isn't started yet.
+async fn build_watcher(
+    game_name: &str,
+    game_config: &GameConfig,
+    sender: &Sender<WatcherMessage>,
+) -> Result<Arc<Watchexec>> {
     let sender_clone = sender.clone();
     let game_name_clone = game_name.to_string();
+    let trigger_on = game_config.trigger_on.clone();
     // Define the handler that's called if any changes are detected.
     let watcher = Watchexec::new(move |action| {
-        // Only trigger on File event types that're interesting for us.
+        // Only trigger on File event types that're configured for this game.
         let mut should_trigger = false;
         let mut locations = Vec::new();
+        let mut kinds = Vec::new();
         for event in action.events.iter() {
             let mut interesting_event = false;
             for tag in &event.tags {
                 if let Tag::FileEventKind(fek) = tag {
-                    match fek {
-                        FileEventKind::Access(_) => continue,
-                        FileEventKind::Modify(ModifyKind::Name(_)) => interesting_event = true,
-                        FileEventKind::Modify(ModifyKind::Metadata(_)) => continue,
-                        FileEventKind::Modify(_) => interesting_event = true,
-                        FileEventKind::Create(_) => interesting_event = true,
-                        FileEventKind::Remove(_) => continue,
+                    let trigger_event = match fek {
+                        FileEventKind::Access(_) => TriggerEvent::Access,
+                        FileEventKind::Modify(ModifyKind::Name(_)) => TriggerEvent::Rename,
+                        FileEventKind::Modify(ModifyKind::Metadata(_)) => TriggerEvent::Metadata,
+                        FileEventKind::Modify(_) => TriggerEvent::Modify,
+                        FileEventKind::Create(_) => TriggerEvent::Create,
+                        FileEventKind::Remove(_) => TriggerEvent::Remove,
                         _ => continue,
                     };
+                    if !trigger_on.contains(&trigger_event) {
+                        continue;
+                    }
+                    interesting_event = true;
+                    kinds.push(format!("{fek:?}"));
                 }
             }
 
@@ -77,23 +200,35 @@ async fn spawn_watcher(
 
         // If anything interesting happened, notify the main program about it.
         locations.dedup();
+        kinds.sort();
+        kinds.dedup();
         if should_trigger {
             sender_clone
-                .send(Update {
+                .send(WatcherMessage::Update(Update {
                     game_name: game_name_clone.clone(),
                     locations,
+                    kinds,
                     time: Local::now(),
-                })
+                }))
                 .expect("Failed to send update.");
         }
 
         action
     })?;
 
-    // Set the watched directory
-    watcher
-        .config
-        .pathset(vec![game_config.savegame_location()]);
+    // Set the watched directory.
+    // Games with a single save file are often written by replacing the file, which isn't
+    // picked up when watching the file itself. Watch its parent and only allow that file instead.
+    let location = game_config.savegame_location();
+    let (watched_path, filters) =
+        match (location.is_file(), location.parent(), location.file_name()) {
+            (true, Some(parent), Some(file_name)) => (
+                parent.to_path_buf(),
+                vec![(format!("/{}", file_name.to_string_lossy()), None)],
+            ),
+            _ => (location, Vec::new()),
+        };
+    watcher.config.pathset(vec![watched_path.clone()]);
 
     // Create the filter that enforces all ignored globs from the configuration file.
     let ignores: Vec<(String, Option<PathBuf>)> = game_config
@@ -101,27 +236,40 @@ async fn spawn_watcher(
         .iter()
         .map(|glob| (glob.clone(), None))
         .collect();
+    // The same ignore files are respected, as when the savegame location is archived.
+    // Single save files don't have any.
+    let ignore_files: Vec<IgnoreFile> = ignore_files(&game_config.savegame_location())
+        .into_iter()
+        .map(|path| IgnoreFile {
+            path,
+            applies_in: Some(watched_path.clone()),
+            applies_to: None,
+        })
+        .collect();
     let globset_filterer = GlobsetFilterer::new(
-        game_config.savegame_location(),
-        Vec::new(),
+        watched_path,
+        filters,
         ignores,
         Vec::new(),
-        Vec::new(),
+        ignore_files,
         Vec::new(),
     )
     .await
-    .context("Failed to init globset filter for game {game_name}")?;
+    .context(format!(
+        "Failed to init globset filter for game {game_name}"
+    ))?;
     watcher.config.filterer.replace(globset_filterer);
 
+    // The terminal ui hides anything that's printed, so errors are reported via the channel.
+    let sender_clone = sender.clone();
     let game_name_clone = game_name.to_string();
-    tokio::spawn(async move {
-        if let Err(err) = watcher.main().await {
-            eprintln!("Error in file watcher for game {game_name_clone}:\n{err:?}");
-        };
-
-        println!("Exiting file watcher worker for {game_name_clone}");
+    watcher.config.on_error(move |hook| {
+        let _ = sender_clone.send(WatcherMessage::Error {
+            game_name: game_name_clone.clone(),
+            error: hook.error.to_string(),
+            stopped: false,
+        });
     });
-    info!("Spawned watcher thread for {}", game_name);
 
-    Ok(())
+    Ok(watcher)
 }
@@ -0,0 +1,52 @@
+use std::{
+    env,
+    fs::{set_permissions, Permissions},
+    os::unix::fs::PermissionsExt,
+};
+
+use anyhow::Result;
+use game_saver::{files::get_archive_files, manually_save_game, GameConfig};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// A `tar` that exits successfully, but writes garbage instead of an archive.
+/// Everything else is passed to the real `tar`.
+const BROKEN_TAR: &str = r#"#!/bin/sh
+case " $* " in
+    *" -cvf "*)
+        for dest; do :; done
+        printf 'garbage' > "$dest"
+        exit 0
+        ;;
+esac
+# Remove this script's directory from the front of the `PATH` to find the real `tar`.
+PATH="${PATH#*:}" exec tar "$@"
+"#;
+
+/// Broken archives are detected and removed, even if the archiver reported success.
+///
+/// This test changes `PATH` and therefore lives in its own test binary.
+#[test]
+fn broken_archive_is_removed() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+
+    let bin = fixture.dir.path().join("bin");
+    write_file(&bin, "tar", BROKEN_TAR.as_bytes());
+    set_permissions(bin.join("tar"), Permissions::from_mode(0o755))?;
+    let path = env::var("PATH")?;
+    env::set_var("PATH", format!("{}:{path}", bin.to_string_lossy()));
+
+    let result = manually_save_game(&fixture.config, GAME, "broken", &no_progress);
+    env::set_var("PATH", path);
+
+    let err = format!(
+        "{:#}",
+        result.expect_err("The broken archive hasn't been detected")
+    );
+    assert!(err.contains("cannot be read"), "Unexpected error: {err}");
+    assert_eq!(get_archive_files(&fixture.config.save_dir(GAME))?.len(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use std::{
+    fs::File,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use game_saver::{
+    autosave_game, files::get_archive_files, manually_save_game, rotate_autosaves,
+    scheduled_autosave_game, surplus_autosaves, GameConfig, LowDiskSpace,
+};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// Once all slots are filled, the oldest autosaves are removed, so that exactly `autosaves`
+/// saves remain after a new autosave.
+#[test]
+fn rotation_never_exceeds_slots() -> Result<()> {
+    let autosaves = 3;
+    let fixture = setup(GameConfig {
+        autosaves,
+        ..Default::default()
+    });
+    let autosave_dir = fixture.config.autosave_dir(GAME);
+
+    // Seed more autosaves than there are slots, e.g. because the limit has been lowered.
+    // The seeded saves are a few hours old, so they're older than the new autosave.
+    for hour in 1..=autosaves + 3 {
+        let path = autosave_dir.join(format!("autosave_seeded_{hour}.tar.zst"));
+        let file = File::create(&path)?;
+        file.set_modified(SystemTime::now() - Duration::from_secs(3600 * hour as u64))?;
+    }
+
+    let path = autosave_game(&fixture.config, GAME, &no_progress)?;
+
+    let saves = get_archive_files(&autosave_dir)?;
+    let names: Vec<&str> = saves.iter().map(|save| save.file_name.as_str()).collect();
+    assert_eq!(saves.len(), autosaves);
+    assert_eq!(saves[0].path, path);
+    // Only the newest seeded saves are kept.
+    assert_eq!(names[1..], ["autosave_seeded_1", "autosave_seeded_2"]);
+
+    Ok(())
+}
+
+/// Lowering the autosave count removes the oldest autosaves right away.
+#[test]
+fn rotation_after_lowering_count() -> Result<()> {
+    let mut fixture = setup(GameConfig {
+        autosaves: 4,
+        ..Default::default()
+    });
+    let autosave_dir = fixture.config.autosave_dir(GAME);
+    for hour in 1..=4 {
+        let path = autosave_dir.join(format!("autosave_{hour}.tar.zst"));
+        let file = File::create(&path)?;
+        file.set_modified(SystemTime::now() - Duration::from_secs(3600 * hour))?;
+    }
+
+    assert_eq!(rotate_autosaves(&fixture.config, GAME)?, 0);
+    fixture.config.games.get_mut(GAME).unwrap().autosaves = 2;
+    let surplus = surplus_autosaves(&fixture.config, GAME)?;
+    let names: Vec<&str> = surplus.iter().map(|save| save.file_name.as_str()).collect();
+    assert_eq!(names, ["autosave_3", "autosave_4"]);
+    assert_eq!(rotate_autosaves(&fixture.config, GAME)?, 2);
+
+    let saves = get_archive_files(&autosave_dir)?;
+    let names: Vec<&str> = saves.iter().map(|save| save.file_name.as_str()).collect();
+    assert_eq!(names, ["autosave_1", "autosave_2"]);
+
+    Ok(())
+}
+
+/// The counter continues after the highest existing counter, even if all older autosaves have
+/// been rotated away.
+#[test]
+fn name_template_counter() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 1,
+        autosave_name_template: Some("factorio-auto-{counter}".into()),
+        ..Default::default()
+    });
+    let autosave_dir = fixture.config.autosave_dir(GAME);
+
+    for expected in [
+        "factorio-auto-001",
+        "factorio-auto-002",
+        "factorio-auto-003",
+    ] {
+        autosave_game(&fixture.config, GAME, &no_progress)?;
+        let saves = get_archive_files(&autosave_dir)?;
+        assert_eq!(saves.len(), 1);
+        assert_eq!(saves[0].file_name, expected);
+    }
+
+    Ok(())
+}
+
+/// Templates that would result in the same name for two autosaves are rejected.
+#[test]
+fn name_template_must_be_unique() {
+    for (template, valid) in [
+        ("autosave_%Y-%m-%d", false),
+        ("autosave", false),
+        ("autosave_%s", true),
+        ("autosave_%Y-%m-%d_{counter}", true),
+        ("saves/%S", false),
+        ("autosave_%Q", false),
+    ] {
+        let fixture = setup(GameConfig {
+            autosaves: 1,
+            ..Default::default()
+        });
+        let mut config = fixture.config.clone();
+        config.games.get_mut(GAME).unwrap().autosave_name_template = Some(template.into());
+
+        assert_eq!(config.errors().is_empty(), valid, "template {template}");
+    }
+}
+
+/// Scheduled autosaves have their own slots, which aren't shared with the other autosaves.
+#[test]
+fn scheduled_autosaves_are_separate() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 1,
+        autosave_schedule: Some("0 3 * * *".into()),
+        scheduled_autosaves: 2,
+        ..Default::default()
+    });
+    let scheduled_dir = fixture.config.scheduled_dir(GAME);
+    for hour in 1..=3 {
+        write_file(&scheduled_dir, &format!("autosave_{hour}.tar.zst"), b"save");
+        let file = File::options()
+            .write(true)
+            .open(scheduled_dir.join(format!("autosave_{hour}.tar.zst")))?;
+        file.set_modified(SystemTime::now() - Duration::from_secs(3600 * hour))?;
+    }
+
+    let scheduled = scheduled_autosave_game(&fixture.config, GAME, &no_progress)?;
+    autosave_game(&fixture.config, GAME, &no_progress)?;
+
+    assert_eq!(
+        get_archive_files(&fixture.config.autosave_dir(GAME))?.len(),
+        1
+    );
+    let saves = get_archive_files(&scheduled_dir)?;
+    let names: Vec<&str> = saves.iter().map(|save| save.file_name.as_str()).collect();
+    assert_eq!(saves[0].path, scheduled);
+    assert_eq!(names[1..], ["autosave_1"]);
+
+    Ok(())
+}
+
+/// Both the common five fields and the extended format with seconds are valid schedules.
+#[test]
+fn autosave_schedule() {
+    for (schedule, valid) in [
+        ("0 3 * * *", true),
+        ("30 0 3 * * Mon", true),
+        ("every day at 3am", false),
+        ("0 25 * * *", false),
+    ] {
+        let mut fixture = setup(GameConfig::default());
+        let game_config = fixture.config.games.get_mut(GAME).unwrap();
+        game_config.autosave_schedule = Some(schedule.into());
+        assert_eq!(
+            fixture.config.errors().is_empty(),
+            valid,
+            "schedule {schedule}"
+        );
+    }
+}
+
+/// Autosaves are skipped on an almost full backup volume, without rotating any existing
+/// autosaves away. Manual saves are still possible.
+#[test]
+fn low_disk_space() -> Result<()> {
+    let mut fixture = setup(GameConfig {
+        autosaves: 2,
+        ..Default::default()
+    });
+    let autosave_dir = fixture.config.autosave_dir(GAME);
+    autosave_game(&fixture.config, GAME, &no_progress)?;
+    let existing = get_archive_files(&autosave_dir)?;
+
+    // No volume has that much free space.
+    fixture.config.min_free_space_mib = u64::MAX;
+    let err = autosave_game(&fixture.config, GAME, &no_progress).unwrap_err();
+    let low_space = err
+        .downcast_ref::<LowDiskSpace>()
+        .expect("Expected a LowDiskSpace error");
+    assert!(low_space.available < fixture.config.min_free_space());
+
+    let remaining = get_archive_files(&autosave_dir)?;
+    assert_eq!(
+        remaining.iter().map(|save| &save.path).collect::<Vec<_>>(),
+        existing.iter().map(|save| &save.path).collect::<Vec<_>>()
+    );
+    assert!(manually_save_game(&fixture.config, GAME, "manual", &no_progress)?.exists());
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{create_dir_all, read, read_dir, write},
+    path::{Path, PathBuf},
+};
+
+use game_saver::{config::Config, files::init_directories, GameConfig};
+use tempfile::TempDir;
+
+/// The name of the game that's configured by [setup].
+pub const GAME: &str = "test_game";
+
+/// A temporary environment with a backup directory and a savegame location.
+/// Everything is removed, once this is dropped.
+pub struct Fixture {
+    pub dir: TempDir,
+    pub config: Config,
+}
+
+impl Fixture {
+    pub fn savegame_location(&self) -> PathBuf {
+        self.dir.path().join("savegames")
+    }
+}
+
+/// Create a config with a single game and initialize all directories of the backup directory.
+/// The savegame location is filled with a few nested files.
+pub fn setup(game_config: GameConfig) -> Fixture {
+    let dir = TempDir::new().expect("Failed to create temporary directory");
+    let savegame_location = dir.path().join("savegames");
+
+    let game_config = GameConfig {
+        savegame_location: savegame_location.to_string_lossy().to_string(),
+        ..game_config
+    };
+    let config = Config {
+        backup_directory: dir.path().join("backups").to_string_lossy().to_string(),
+        layout: Default::default(),
+        trash_retention_days: 30,
+        // Don't skip any saves on almost full test machines.
+        min_free_space_mib: 0,
+        desktop_notifications: false,
+        mouse_capture: true,
+        key_repeat_limit: 1,
+        idle_redraw_seconds: 30,
+        // Never touch the state of the user's own game-saver.
+        data_directory: Some(dir.path().join("data").to_string_lossy().to_string()),
+        temp_directory: None,
+        games: HashMap::from([(GAME.to_string(), game_config)]),
+    };
+    init_directories(&config).expect("Failed to initialize directories");
+
+    write_file(&savegame_location, "save.dat", b"the current save");
+    write_file(&savegame_location, "settings.ini", b"fullscreen=true");
+    write_file(&savegame_location, "slots/1/slot.dat", b"first slot");
+    write_file(&savegame_location, "slots/2/slot.dat", &[0, 1, 2, 255]);
+
+    Fixture { dir, config }
+}
+
+/// Write a file relative to `root` and create all missing parent directories.
+pub fn write_file(root: &Path, relative: &str, content: &[u8]) {
+    let path = root.join(relative);
+    create_dir_all(path.parent().unwrap()).expect("Failed to create parent directory");
+    write(&path, content).expect("Failed to write file");
+}
+
+/// Read all files below `root`, keyed by their path relative to `root`.
+/// Directories are included with empty content, so empty directories are compared as well.
+pub fn read_tree(root: &Path) -> BTreeMap<PathBuf, Option<Vec<u8>>> {
+    let mut tree = BTreeMap::new();
+    collect_tree(root, root, &mut tree);
+    tree
+}
+
+fn collect_tree(root: &Path, path: &Path, tree: &mut BTreeMap<PathBuf, Option<Vec<u8>>>) {
+    for entry in read_dir(path).expect("Failed to read directory") {
+        let path = entry.expect("Failed to read dir entry").path();
+        let relative = path.strip_prefix(root).unwrap().to_path_buf();
+        if path.is_dir() {
+            tree.insert(relative, None);
+            collect_tree(root, &path, tree);
+        } else {
+            tree.insert(relative, Some(read(&path).expect("Failed to read file")));
+        }
+    }
+}
+
+/// A progress callback that ignores all updates.
+pub fn no_progress(_: u64, _: u64) {}
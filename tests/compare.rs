@@ -0,0 +1,87 @@
+use std::{
+    fs::{remove_file, File},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use game_saver::{
+    autosave_game, compare_saves, files::get_archive_files, manually_save_game, BackupStrategy,
+    GameConfig,
+};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// Added, removed, resized and modified files are detected.
+#[test]
+fn compare_two_saves() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let location = fixture.savegame_location();
+
+    manually_save_game(&fixture.config, GAME, "first", &no_progress)?;
+    write_file(
+        &location,
+        "save.dat",
+        b"a longer version of the current save",
+    );
+    // The slot is edited an hour later, but keeps its size.
+    write_file(&location, "slots/1/slot.dat", b"same size!");
+    File::options()
+        .write(true)
+        .open(location.join("slots/1/slot.dat"))?
+        .set_modified(SystemTime::now() + Duration::from_secs(3600))?;
+    write_file(&location, "slots/3/slot.dat", b"third slot");
+    remove_file(location.join("settings.ini"))?;
+    manually_save_game(&fixture.config, GAME, "second", &no_progress)?;
+
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    let first = saves.iter().find(|save| save.file_name == "first").unwrap();
+    let second = saves
+        .iter()
+        .find(|save| save.file_name == "second")
+        .unwrap();
+    let comparison = compare_saves(first, second)?;
+
+    assert_eq!(comparison.first_files, 4);
+    assert_eq!(comparison.first_bytes, 45);
+    assert_eq!(comparison.second_files, 4);
+    assert_eq!(comparison.second_bytes, 60);
+    assert_eq!(comparison.added, ["savegames/slots/3/slot.dat"]);
+    assert_eq!(comparison.removed, ["savegames/settings.ini"]);
+    // Files with the same size are compared by their modification time.
+    assert_eq!(
+        comparison.changed,
+        ["savegames/save.dat", "savegames/slots/1/slot.dat"]
+    );
+
+    Ok(())
+}
+
+/// Saves with a manifest are compared by their hashes, which detects edits within the same
+/// minute as well.
+#[test]
+fn compare_by_hash() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 3,
+        backup_strategy: BackupStrategy::Incremental,
+        autosave_name_template: Some("auto_{counter}".into()),
+        ..Default::default()
+    });
+    let location = fixture.savegame_location();
+
+    let first = autosave_game(&fixture.config, GAME, &no_progress)?;
+    write_file(&location, "slots/1/slot.dat", b"same size!");
+    let second = autosave_game(&fixture.config, GAME, &no_progress)?;
+
+    let saves = get_archive_files(&fixture.config.autosave_dir(GAME))?;
+    let first = saves.iter().find(|save| save.path == first).unwrap();
+    let second = saves.iter().find(|save| save.path == second).unwrap();
+    let comparison = compare_saves(first, second)?;
+
+    assert!(comparison.added.is_empty());
+    assert!(comparison.removed.is_empty());
+    assert_eq!(comparison.changed, ["savegames/slots/1/slot.dat"]);
+
+    Ok(())
+}
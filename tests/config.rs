@@ -0,0 +1,179 @@
+use std::fs::{read_to_string, write};
+
+use anyhow::Result;
+use game_saver::{
+    config::{validate_game_name, DEFAULT_CATEGORY},
+    Config,
+};
+use pretty_assertions::assert_eq;
+
+const CONFIG: &str = r#"# Where all backups are stored.
+backup_directory = "~/.local/share/game_saver"
+
+[games.factorio]
+# The game was moved to another drive.
+savegame_location = "~/.factorio/saves"
+autosaves = 5
+autosave_timeout = 0
+ignored_files = []
+
+[games.tetris]
+savegame_location = "~/.tetris"
+autosaves = 0
+autosave_timeout = 0
+ignored_files = []
+"#;
+
+/// Only the savegame location of the relocated game changes, comments are kept.
+#[test]
+fn update_savegame_location() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("game_saver.toml");
+    write(&path, CONFIG)?;
+
+    Config::update_savegame_location(&path, "factorio", "/mnt/games/factorio/saves")?;
+
+    let expected = CONFIG.replace("~/.factorio/saves", "/mnt/games/factorio/saves");
+    assert_eq!(read_to_string(&path)?, expected);
+
+    let config = Config::new(&Some(path))?;
+    assert_eq!(
+        config.game("factorio")?.savegame_location,
+        "/mnt/games/factorio/saves"
+    );
+    assert_eq!(config.game("tetris")?.savegame_location, "~/.tetris");
+
+    Ok(())
+}
+
+/// Global values are replaced outside of the game tables.
+#[test]
+fn update_global_value() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("game_saver.toml");
+    write(&path, CONFIG)?;
+
+    Config::update_value(
+        &path,
+        None,
+        "backup_directory",
+        toml_edit::value("/mnt/backups"),
+    )?;
+
+    let expected = CONFIG.replace("~/.local/share/game_saver", "/mnt/backups");
+    assert_eq!(read_to_string(&path)?, expected);
+
+    Ok(())
+}
+
+/// Unknown games aren't added to the config.
+#[test]
+fn update_unknown_game() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("game_saver.toml");
+    write(&path, CONFIG)?;
+
+    assert!(Config::update_savegame_location(&path, "doom", "~/.doom").is_err());
+    assert_eq!(read_to_string(&path)?, CONFIG);
+
+    Ok(())
+}
+
+/// Entries without a category are listed under the default category.
+#[test]
+fn category() -> Result<()> {
+    let content = CONFIG.replace(
+        "[games.tetris]\n",
+        "[games.tetris]\ncategory = \"Configs\"\n",
+    );
+    let config: Config = toml::from_str(&content)?;
+
+    assert_eq!(config.category("factorio"), DEFAULT_CATEGORY);
+    assert_eq!(config.category("tetris"), "Configs");
+
+    Ok(())
+}
+
+/// A config without any games can be loaded, so the user can be told how to add one.
+#[test]
+fn without_games() -> Result<()> {
+    let config: Config = toml::from_str("backup_directory = \"~/.local/share/game_saver\"\n")?;
+    assert!(config.games.is_empty());
+
+    Ok(())
+}
+
+/// Launch and exit autosaves need the name of the game's process.
+#[test]
+fn process_triggers_require_process_name() -> Result<()> {
+    for (options, valid) in [
+        ("autosave_on_launch = true\n", false),
+        ("autosave_on_exit = true\n", false),
+        ("autosave_on_exit = true\nprocess_name = \" \"\n", false),
+        (
+            "autosave_on_launch = true\nprocess_name = \"factorio\"\n",
+            true,
+        ),
+        ("process_name = \"factorio\"\n", true),
+    ] {
+        let content = CONFIG.replace(
+            "[games.factorio]\n",
+            &format!("[games.factorio]\n{options}"),
+        );
+        let config: Config = toml::from_str(&content)?;
+        let has_error = config
+            .errors()
+            .iter()
+            .any(|error| error.contains("require a process_name"));
+        assert_eq!(has_error, !valid, "{options}");
+    }
+
+    Ok(())
+}
+
+/// Invalid configs are reported with their path.
+#[test]
+fn invalid_config() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("game_saver.toml");
+    write(&path, "backup_directory = ")?;
+
+    let err = Config::new(&Some(path.clone())).unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains("Couldn't parse config"), "{message}");
+    assert!(
+        message.contains(&path.to_string_lossy().to_string()),
+        "{message}"
+    );
+
+    Ok(())
+}
+
+/// Game names are used as directory names in the backup directory.
+#[test]
+fn game_names() -> Result<()> {
+    for (name, valid) in [
+        ("tetris", true),
+        ("Witcher 3", true),
+        ("-dashed", true),
+        ("v1.2", true),
+        ("", false),
+        ("  ", false),
+        ("nested/game", false),
+        (".", false),
+        ("..", false),
+        (".trash", false),
+    ] {
+        assert_eq!(validate_game_name(name).is_ok(), valid, "{name:?}");
+
+        let content = CONFIG.replace("[games.tetris]", &format!("[games.{name:?}]"));
+        let config: Config = toml::from_str(&content)?;
+        let has_error = config
+            .errors()
+            .iter()
+            .any(|error| error.starts_with("Invalid game name"));
+        assert_eq!(has_error, !valid, "{name:?}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::fs::{create_dir_all, remove_dir_all};
+
+use anyhow::Result;
+use game_saver::{
+    autosave_game, files::get_archive_files, manually_save_game, EmptySavegameLocation, GameConfig,
+};
+
+mod common;
+use common::*;
+
+/// Replace the savegame location with a directory, that only contains empty directories.
+fn empty_location(fixture: &Fixture) {
+    let location = fixture.savegame_location();
+    remove_dir_all(&location).expect("Failed to remove savegame location");
+    create_dir_all(location.join("slots/1")).expect("Failed to create empty directories");
+}
+
+/// Empty savegame locations are refused with a typed error, if `skip_empty_saves` is set.
+/// No archives are written in that case.
+#[test]
+fn skip_empty_location() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 3,
+        skip_empty_saves: true,
+        ..Default::default()
+    });
+    empty_location(&fixture);
+    let config = &fixture.config;
+
+    let err = manually_save_game(config, GAME, "empty", &no_progress).unwrap_err();
+    assert!(err.downcast_ref::<EmptySavegameLocation>().is_some());
+    let err = autosave_game(config, GAME, &no_progress).unwrap_err();
+    assert!(err.downcast_ref::<EmptySavegameLocation>().is_some());
+
+    assert!(get_archive_files(&config.save_dir(GAME))?.is_empty());
+    assert!(get_archive_files(&config.autosave_dir(GAME))?.is_empty());
+
+    Ok(())
+}
+
+/// Without `skip_empty_saves`, empty savegame locations are only warned about and saved anyway.
+#[test]
+fn save_empty_location() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 3,
+        ..Default::default()
+    });
+    empty_location(&fixture);
+    let config = &fixture.config;
+
+    let save = manually_save_game(config, GAME, "empty", &no_progress)?;
+    assert!(save.exists());
+    autosave_game(config, GAME, &no_progress)?;
+    assert_eq!(get_archive_files(&config.autosave_dir(GAME))?.len(), 1);
+
+    Ok(())
+}
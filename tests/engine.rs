@@ -0,0 +1,149 @@
+use chrono::{DateTime, Duration, Local, TimeZone};
+use game_saver::engine::{AutosaveEngine, ChangeOutcome, AUTOSAVE_DEBOUNCE};
+use pretty_assertions::assert_eq;
+
+const GAME: &str = "test_game";
+
+/// A fixed point in time, offset by the given amount of seconds.
+fn at(seconds: i64) -> DateTime<Local> {
+    Local.timestamp_opt(1_700_000_000, 0).unwrap() + Duration::seconds(seconds)
+}
+
+/// An autosave is only due, once there haven't been any changes for the debounce duration.
+#[test]
+fn debounce() {
+    let mut engine = AutosaveEngine::new();
+    assert_eq!(
+        engine.change_detected(GAME, at(0)),
+        ChangeOutcome::Scheduled
+    );
+    assert_eq!(engine.countdown(GAME, at(0)), Some(AUTOSAVE_DEBOUNCE));
+    assert!(engine.due_games(at(AUTOSAVE_DEBOUNCE - 1)).is_empty());
+
+    // Another change restarts the debounce.
+    assert_eq!(
+        engine.change_detected(GAME, at(3)),
+        ChangeOutcome::Postponed
+    );
+    assert!(engine.due_games(at(AUTOSAVE_DEBOUNCE)).is_empty());
+    assert_eq!(engine.due_games(at(3 + AUTOSAVE_DEBOUNCE)), vec![GAME]);
+}
+
+/// Changes after an autosave wait for the autosave timeout to pass.
+#[test]
+fn autosave_timeout() {
+    let mut engine = AutosaveEngine::new();
+    engine.change_detected(GAME, at(0));
+    engine.autosave_created(GAME, 60, at(10));
+    assert_eq!(engine.pending_games(), Vec::<String>::new());
+    assert_eq!(engine.timeout_remaining(GAME, at(10)), Some(60));
+
+    assert_eq!(
+        engine.change_detected(GAME, at(20)),
+        ChangeOutcome::AfterTimeout
+    );
+    // The countdown includes the rest of the timeout.
+    assert_eq!(engine.countdown(GAME, at(20)), Some(50));
+
+    engine.sweep(at(70));
+    assert!(engine.due_games(at(70)).is_empty());
+
+    engine.sweep(at(71));
+    assert_eq!(engine.timeout_remaining(GAME, at(71)), None);
+    assert_eq!(engine.due_games(at(71)), vec![GAME]);
+}
+
+/// A timeout of 0 disables the autosave timeout.
+#[test]
+fn no_autosave_timeout() {
+    let mut engine = AutosaveEngine::new();
+    engine.autosave_created(GAME, 0, at(0));
+    assert_eq!(engine.timeout_remaining(GAME, at(0)), None);
+    assert_eq!(
+        engine.change_detected(GAME, at(1)),
+        ChangeOutcome::Scheduled
+    );
+}
+
+/// Changes are ignored for a short time after a restore, pending changes are dropped.
+#[test]
+fn ignore_after_restore() {
+    let mut engine = AutosaveEngine::new();
+    engine.change_detected(GAME, at(0));
+    engine.ignore_changes(GAME, at(1));
+    assert_eq!(engine.pending_games(), Vec::<String>::new());
+    assert_eq!(engine.change_detected(GAME, at(2)), ChangeOutcome::Ignored);
+
+    engine.sweep(at(6));
+    assert!(engine.is_ignoring(GAME));
+
+    engine.sweep(at(7));
+    assert!(!engine.is_ignoring(GAME));
+    assert_eq!(
+        engine.change_detected(GAME, at(7)),
+        ChangeOutcome::Scheduled
+    );
+}
+
+/// Absurdly long timeouts neither overflow nor ever pass.
+#[test]
+fn huge_autosave_timeout() {
+    let mut engine = AutosaveEngine::new();
+    engine.autosave_created(GAME, usize::MAX, at(0));
+    engine.sweep(at(1_000_000_000));
+    assert!(engine.timeout_remaining(GAME, at(1_000_000_000)).is_some());
+}
+
+/// Drive the engine like the app does: check for due autosaves every second and create them
+/// with the given autosave timeout. Returns the times of all created autosaves.
+fn simulate(engine: &mut AutosaveEngine, changes: &[i64], timeout: usize, until: i64) -> Vec<i64> {
+    let mut autosaves = Vec::new();
+    for second in 0..=until {
+        for _ in changes.iter().filter(|change| **change == second) {
+            engine.change_detected(GAME, at(second));
+        }
+        engine.sweep(at(second));
+        for game in engine.due_games(at(second)) {
+            engine.autosave_created(&game, timeout, at(second));
+            autosaves.push(second);
+        }
+    }
+
+    autosaves
+}
+
+/// A burst of changes during the cooldown results in exactly one autosave, once the cooldown
+/// is over. The debounce doesn't extend the cooldown, if the changes settled in the meantime.
+#[test]
+fn burst_during_cooldown() {
+    let mut engine = AutosaveEngine::new();
+    let changes = [0, 10, 12, 15, 20, 30];
+    let autosaves = simulate(&mut engine, &changes, 60, 200);
+
+    // The first change is saved after the debounce, which starts the cooldown.
+    // All other changes settled within the cooldown and are saved right after it.
+    assert_eq!(autosaves, vec![AUTOSAVE_DEBOUNCE, AUTOSAVE_DEBOUNCE + 61]);
+}
+
+/// Changes at the end of the cooldown still have to settle for the debounce.
+#[test]
+fn debounce_after_cooldown() {
+    let mut engine = AutosaveEngine::new();
+    let autosaves = simulate(&mut engine, &[0, 64], 60, 200);
+
+    assert_eq!(autosaves, vec![AUTOSAVE_DEBOUNCE, 64 + AUTOSAVE_DEBOUNCE]);
+}
+
+/// The debounce can be set per game, independent of the cooldown.
+#[test]
+fn custom_debounce() {
+    let mut engine = AutosaveEngine::new();
+    engine.set_debounce(GAME, 20);
+    engine.change_detected(GAME, at(0));
+    assert_eq!(engine.countdown(GAME, at(0)), Some(20));
+    assert!(engine.due_games(at(19)).is_empty());
+    assert_eq!(engine.due_games(at(20)), vec![GAME]);
+
+    engine.set_debounce(GAME, 0);
+    assert_eq!(engine.due_games(at(0)), vec![GAME]);
+}
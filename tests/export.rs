@@ -0,0 +1,77 @@
+use std::{
+    fs::File,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use game_saver::{
+    autosave_game, export_saves, files::get_archive_files, import_saves, manually_save_game,
+    GameConfig, ImportSummary,
+};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// The names and modification times of all saves of a directory.
+/// The modification times are compared in seconds, as tar doesn't store any fractions.
+fn saves(dir: &Path) -> Result<Vec<(String, DateTime<Local>)>> {
+    Ok(get_archive_files(dir)?
+        .into_iter()
+        .map(|save| (save.file_name, save.last_modified))
+        .collect())
+}
+
+/// Exported saves are imported on another machine with their names and modification times.
+/// Importing them a second time doesn't add or replace anything.
+#[test]
+fn export_and_import() -> Result<()> {
+    let game_config = GameConfig {
+        autosaves: 2,
+        ..Default::default()
+    };
+    let source = setup(game_config.clone());
+    for (hour, name) in [(2, "first"), (1, "second")] {
+        let path = manually_save_game(&source.config, GAME, name, &no_progress)?;
+        let file = File::options().write(true).open(path)?;
+        file.set_modified(SystemTime::now() - Duration::from_secs(3600 * hour))?;
+    }
+    autosave_game(&source.config, GAME, &no_progress)?;
+
+    let manual_only = source.dir.path().join("manual.tar.zst");
+    assert_eq!(export_saves(&source.config, GAME, &manual_only, false)?, 2);
+    let everything = source.dir.path().join("everything.tar.zst");
+    assert_eq!(export_saves(&source.config, GAME, &everything, true)?, 3);
+
+    let target = setup(game_config);
+    let summary = import_saves(&target.config, GAME, &manual_only)?;
+    assert_eq!(
+        summary,
+        ImportSummary {
+            imported: 2,
+            skipped: 0
+        }
+    );
+    assert_eq!(
+        saves(&target.config.save_dir(GAME))?,
+        saves(&source.config.save_dir(GAME))?
+    );
+    assert_eq!(saves(&target.config.autosave_dir(GAME))?, Vec::new());
+
+    let summary = import_saves(&target.config, GAME, &everything)?;
+    assert_eq!(
+        summary,
+        ImportSummary {
+            imported: 1,
+            skipped: 2
+        }
+    );
+    assert_eq!(
+        saves(&target.config.autosave_dir(GAME))?,
+        saves(&source.config.autosave_dir(GAME))?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::{
+    fs::{read_to_string, File},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use game_saver::{
+    files::{self, get_archive_files, init_directories},
+    GameConfig,
+};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// Entries that cannot be read are skipped, the other saves are still listed.
+#[test]
+fn unreadable_entries_are_skipped() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    for name in ["first.tar.zst", "broken.tar.zst", "second.zip"] {
+        write_file(dir.path(), name, b"save");
+    }
+    // A modification time before the unix epoch cannot be converted into a date of a save.
+    let broken = File::options()
+        .write(true)
+        .open(dir.path().join("broken.tar.zst"))?;
+    broken.set_modified(UNIX_EPOCH - Duration::from_secs(60))?;
+
+    let saves = get_archive_files(dir.path())?;
+    let mut names: Vec<&str> = saves.iter().map(|save| save.file_name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, ["first", "second"]);
+
+    Ok(())
+}
+
+/// Directories of removed games and their trash are orphaned, everything of configured games isn't.
+/// Only the global backup directory is scanned and only directories that contain saves count.
+#[test]
+fn orphaned_backup_directories() -> Result<()> {
+    let mut fixture = setup(GameConfig::default());
+    let backup_dir = fixture.config.backup_directory();
+    write_file(&backup_dir, "removed/old.tar.zst", b"save");
+    write_file(&backup_dir, "removed_autosaves/autosaves/old.zip", b"save");
+    write_file(&backup_dir, ".trash/removed/deleted.tar.zst", b"save");
+    write_file(
+        &backup_dir,
+        &format!(".trash/{GAME}/deleted.tar.zst"),
+        b"save",
+    );
+    write_file(&backup_dir, ".undo/snapshot.tar.zst", b"save");
+    write_file(&backup_dir, "notes.txt", b"not a directory");
+    // Directories without any saves might belong to something else entirely.
+    write_file(&backup_dir, "documents/notes.txt", b"unrelated");
+
+    // The own backup directory of a game may contain anything and is never scanned.
+    let other_drive = fixture.dir.path().join("other_drive");
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.backup_directory = Some(other_drive.to_string_lossy().to_string());
+    init_directories(&fixture.config)?;
+    write_file(&other_drive, "unrelated/old.zip", b"save");
+
+    assert_eq!(
+        files::orphaned_backup_directories(&fixture.config)?,
+        [
+            backup_dir.join(".trash/removed"),
+            backup_dir.join("removed"),
+            backup_dir.join("removed_autosaves"),
+        ]
+    );
+
+    Ok(())
+}
+
+/// The health file contains the time it has been written, which is refreshed on every write.
+#[test]
+fn health_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("health");
+
+    let before = Local::now();
+    files::write_health_file(&path)?;
+    let written = DateTime::parse_from_rfc3339(&read_to_string(&path)?)?;
+    assert!(written >= before - chrono::Duration::seconds(1));
+    assert!(written <= Local::now());
+
+    files::write_health_file(&path)?;
+    let rewritten = DateTime::parse_from_rfc3339(&read_to_string(&path)?)?;
+    assert!(rewritten >= written);
+
+    Ok(())
+}
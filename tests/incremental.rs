@@ -0,0 +1,207 @@
+use std::{
+    collections::BTreeSet,
+    fs::{read, remove_file, File},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use game_saver::{
+    archive::list_archive,
+    autosave_game,
+    files::get_archive_files,
+    incremental::{read_manifest, MANIFEST_NAME},
+    read_save_file, restore_save, restore_summary, ArchiveFormat, BackupStrategy, GameConfig,
+};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+fn incremental_config(format: ArchiveFormat, autosaves: usize) -> GameConfig {
+    GameConfig {
+        autosaves,
+        archive_format: format,
+        backup_strategy: BackupStrategy::Incremental,
+        autosave_name_template: Some("auto_{counter}".into()),
+        ..Default::default()
+    }
+}
+
+/// Create an autosave, which is `age` hours old.
+/// Autosaves are sorted by their modification time, which only has a precision of seconds.
+fn autosave(fixture: &Fixture, age: u64) -> Result<PathBuf> {
+    let path = autosave_game(&fixture.config, GAME, &no_progress)?;
+    let file = File::options().write(true).open(&path)?;
+    file.set_modified(SystemTime::now() - Duration::from_secs(3600 * age))?;
+
+    Ok(path)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+/// Only changed files are stored in incremental autosaves.
+/// Restoring them results in exactly the same files as at the time of the autosave.
+fn restore_incremental(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(incremental_config(format, 5));
+    let location = fixture.savegame_location();
+    let original = read_tree(&location);
+    let base = autosave(&fixture, 2)?;
+
+    write_file(&location, "slots/2/slot.dat", b"changed");
+    write_file(&location, "slots/3/new.dat", b"new");
+    remove_file(location.join("settings.ini"))?;
+    let changed = read_tree(&location);
+    let incremental = autosave(&fixture, 1)?;
+
+    let manifest = read_manifest(&incremental)?.expect("Missing manifest");
+    assert_eq!(manifest.base, Some(file_name(&base)));
+    let files: BTreeSet<String> = list_archive(format, &incremental)?.into_keys().collect();
+    let expected = [
+        MANIFEST_NAME,
+        "savegames/slots/2/slot.dat",
+        "savegames/slots/3/new.dat",
+    ];
+    assert_eq!(files, expected.map(String::from).into());
+
+    let saves = get_archive_files(&fixture.config.autosave_dir(GAME))?;
+    let summary = restore_summary(&fixture.config, GAME, &saves[0])?;
+    assert_eq!(summary.files, 4);
+
+    write_file(&location, "save.dat", b"a newer save");
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+    assert_eq!(read_tree(&location), changed);
+
+    restore_save(&fixture.config, GAME, &saves[1], &no_progress)?;
+    assert_eq!(read_tree(&location), original);
+
+    Ok(())
+}
+
+#[test]
+fn restore_incremental_tar_zst() -> Result<()> {
+    restore_incremental(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn restore_incremental_zip() -> Result<()> {
+    restore_incremental(ArchiveFormat::Zip)
+}
+
+/// The base of incremental autosaves isn't rotated away, while it's still needed.
+#[test]
+fn rotation_keeps_base() -> Result<()> {
+    let fixture = setup(incremental_config(ArchiveFormat::TarZst, 2));
+    let location = fixture.savegame_location();
+    let base = autosave(&fixture, 3)?;
+
+    for (age, content) in [(2, "second"), (1, "third")] {
+        write_file(&location, "slots/1/slot.dat", content.as_bytes());
+        autosave(&fixture, age)?;
+    }
+
+    let saves = get_archive_files(&fixture.config.autosave_dir(GAME))?;
+    let names: Vec<&str> = saves.iter().map(|save| save.file_name.as_str()).collect();
+    assert_eq!(names, ["auto_003", "auto_002", "auto_001"]);
+    assert_eq!(saves[2].path, base);
+
+    let expected = read_tree(&location);
+    write_file(&location, "slots/1/slot.dat", b"fourth");
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+    assert_eq!(read_tree(&location), expected);
+
+    Ok(())
+}
+
+/// Once the changes make up more than half of the base, a new full autosave is created.
+#[test]
+fn large_changes_create_new_base() -> Result<()> {
+    let fixture = setup(incremental_config(ArchiveFormat::TarZst, 5));
+    let location = fixture.savegame_location();
+    autosave(&fixture, 2)?;
+
+    write_file(&location, "save.dat", &[1; 100]);
+    let path = autosave(&fixture, 1)?;
+
+    let manifest = read_manifest(&path)?.expect("Missing manifest");
+    assert_eq!(manifest.base, None);
+    assert_eq!(manifest.files.len(), 4);
+
+    Ok(())
+}
+
+/// Single files of incremental autosaves are read from their base, if they didn't change.
+#[test]
+fn read_single_file_from_base() -> Result<()> {
+    let fixture = setup(incremental_config(ArchiveFormat::Zip, 5));
+    let location = fixture.savegame_location();
+    autosave(&fixture, 2)?;
+    write_file(&location, "slots/1/slot.dat", b"changed");
+    remove_file(location.join("slots/2/slot.dat"))?;
+    let path = autosave(&fixture, 1)?;
+    assert!(read_manifest(&path)?
+        .expect("Missing manifest")
+        .base
+        .is_some());
+
+    let saves = get_archive_files(&fixture.config.autosave_dir(GAME))?;
+    let changed = read_save_file(&saves[0], "savegames/slots/1/slot.dat")?;
+    assert_eq!(changed, Some(b"changed".to_vec()));
+    let unchanged = read_save_file(&saves[0], "savegames/save.dat")?;
+    assert_eq!(unchanged, Some(b"the current save".to_vec()));
+    assert_eq!(
+        read_save_file(&saves[0], "savegames/slots/2/slot.dat")?,
+        None
+    );
+
+    Ok(())
+}
+
+/// Incremental autosaves cannot be restored without their base.
+#[test]
+fn missing_base_keeps_current_save() -> Result<()> {
+    let fixture = setup(incremental_config(ArchiveFormat::TarZst, 5));
+    let location = fixture.savegame_location();
+    let base = autosave(&fixture, 2)?;
+    write_file(&location, "slots/1/slot.dat", b"changed");
+    autosave(&fixture, 1)?;
+
+    remove_file(&base)?;
+    let current = read_tree(&location);
+    let saves = get_archive_files(&fixture.config.autosave_dir(GAME))?;
+    assert!(restore_save(&fixture.config, GAME, &saves[0], &no_progress).is_err());
+    assert_eq!(read_tree(&location), current);
+
+    Ok(())
+}
+
+/// Changed siblings are stored in incremental autosaves like any other file.
+#[test]
+fn incremental_siblings() -> Result<()> {
+    let fixture = setup(GameConfig {
+        include_siblings: vec!["options.cfg".into()],
+        ..incremental_config(ArchiveFormat::TarZst, 5)
+    });
+    let root = fixture.dir.path();
+    write_file(root, "options.cfg", b"difficulty=hard");
+    autosave(&fixture, 2)?;
+
+    write_file(root, "options.cfg", b"difficulty=easy");
+    let incremental = autosave(&fixture, 1)?;
+    let files: BTreeSet<String> = list_archive(ArchiveFormat::TarZst, &incremental)?
+        .into_keys()
+        .collect();
+    assert_eq!(
+        files,
+        [MANIFEST_NAME, "options.cfg"].map(String::from).into()
+    );
+
+    write_file(root, "options.cfg", b"difficulty=normal");
+    let saves = get_archive_files(&fixture.config.autosave_dir(GAME))?;
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+    assert_eq!(read(root.join("options.cfg"))?, b"difficulty=easy");
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::{
+    env,
+    fs::{read_to_string, set_permissions, write, Permissions},
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use game_saver::{files::get_archive_files, manually_save_game, GameConfig};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// A `tar` that's killed, while it's writing the archive.
+/// It records the path of the archive next to itself. Everything else is passed to the real `tar`.
+const INTERRUPTED_TAR: &str = r#"#!/bin/sh
+case " $* " in
+    *" -cvf "*)
+        for dest; do :; done
+        printf 'partial' > "$dest"
+        printf '%s' "$dest" > "$(dirname "$0")/dest"
+        kill -9 $$
+        ;;
+esac
+# Remove this script's directory from the front of the `PATH` to find the real `tar`.
+PATH="${PATH#*:}" exec tar "$@"
+"#;
+
+/// Archives are written to a hidden temporary file, so an interrupted save never shows up as a
+/// save. Not even, if game-saver itself is killed and cannot remove the partial archive.
+///
+/// This test changes `PATH` and therefore lives in its own test binary.
+#[test]
+fn interrupted_save_is_hidden() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let save_dir = fixture.config.save_dir(GAME);
+
+    let bin = fixture.dir.path().join("bin");
+    write_file(&bin, "tar", INTERRUPTED_TAR.as_bytes());
+    set_permissions(bin.join("tar"), Permissions::from_mode(0o755))?;
+    let path = env::var("PATH")?;
+    env::set_var("PATH", format!("{}:{path}", bin.to_string_lossy()));
+
+    let result = manually_save_game(&fixture.config, GAME, "interrupted", &no_progress);
+    env::set_var("PATH", path);
+
+    assert!(result.is_err(), "The interrupted save succeeded");
+    let partial = PathBuf::from(read_to_string(bin.join("dest"))?);
+    assert_eq!(partial, save_dir.join(".interrupted.tar.zst.part"));
+    assert!(!partial.exists(), "The partial archive hasn't been removed");
+
+    // Simulate that game-saver has been killed as well.
+    write(&partial, "partial")?;
+    assert_eq!(get_archive_files(&save_dir)?.len(), 0);
+
+    // The next save replaces the partial archive.
+    let save = manually_save_game(&fixture.config, GAME, "interrupted", &no_progress)?;
+    assert!(
+        !partial.exists(),
+        "The partial archive hasn't been replaced"
+    );
+    let saves = get_archive_files(&save_dir)?;
+    assert_eq!(saves.len(), 1);
+    assert_eq!(saves[0].path, save);
+
+    Ok(())
+}
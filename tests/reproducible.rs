@@ -0,0 +1,62 @@
+use std::{
+    fs::{read, File},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use game_saver::{
+    archive::check_archive, files::get_archive_files, manually_save_game, restore_save,
+    ArchiveFormat, GameConfig,
+};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// Saves of identical files result in identical archives, even if the files have been touched
+/// in between. The archives can still be restored.
+fn identical_archives(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(GameConfig {
+        archive_format: format,
+        reproducible_archives: true,
+        ..Default::default()
+    });
+    let location = fixture.savegame_location();
+    let original = read_tree(&location);
+
+    let first = manually_save_game(&fixture.config, GAME, "first", &no_progress)?;
+    let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+    for file in ["save.dat", "slots/1/slot.dat"] {
+        File::options()
+            .write(true)
+            .open(location.join(file))?
+            .set_modified(an_hour_ago)?;
+    }
+    let second = manually_save_game(&fixture.config, GAME, "second", &no_progress)?;
+
+    assert!(read(&first)? == read(&second)?, "Archives differ");
+    check_archive(format, &second)?;
+
+    write_file(&location, "save.dat", b"a newer save");
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    let save = saves.iter().find(|save| save.path == second).unwrap();
+    restore_save(&fixture.config, GAME, save, &no_progress)?;
+    assert_eq!(read_tree(&location), original);
+
+    Ok(())
+}
+
+#[test]
+fn identical_archives_tar_zst() -> Result<()> {
+    identical_archives(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn identical_archives_tar_gz() -> Result<()> {
+    identical_archives(ArchiveFormat::TarGz)
+}
+
+#[test]
+fn identical_archives_zip() -> Result<()> {
+    identical_archives(ArchiveFormat::Zip)
+}
@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use chrono::{Local, TimeZone};
+use game_saver::{saves::expired_autosaves, ArchiveFormat, GameConfig, RetentionPolicy, SaveFile};
+use pretty_assertions::assert_eq;
+
+/// Create a save, that has been modified at the given time.
+fn save(name: &str, (month, day, hour, minute): (u32, u32, u32, u32)) -> SaveFile {
+    let year = if month == 12 { 2023 } else { 2024 };
+    SaveFile {
+        path: PathBuf::from(format!("{name}.tar.zst")),
+        file_name: name.to_string(),
+        last_modified: Local
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap(),
+        format: ArchiveFormat::TarZst,
+    }
+}
+
+fn names(saves: &[SaveFile]) -> Vec<&str> {
+    saves.iter().map(|save| save.file_name.as_str()).collect()
+}
+
+/// Newest first, as returned by `get_archive_files`.
+/// 2024-01-10 is a Wednesday, 2024-01-05 is in the previous week.
+fn saves() -> Vec<SaveFile> {
+    vec![
+        save("a", (1, 10, 12, 30)),
+        save("b", (1, 10, 12, 10)),
+        save("c", (1, 10, 11, 50)),
+        save("d", (1, 10, 10, 0)),
+        save("e", (1, 9, 23, 0)),
+        save("f", (1, 8, 8, 0)),
+        save("g", (1, 5, 8, 0)),
+        save("h", (12, 20, 8, 0)),
+    ]
+}
+
+/// By default, only the newest saves are kept.
+#[test]
+fn count_keeps_newest() {
+    let mut saves = saves();
+    let expired = expired_autosaves(&mut saves, 3, &RetentionPolicy::default());
+
+    assert_eq!(names(&saves), ["a", "b", "c"]);
+    assert_eq!(names(&expired), ["d", "e", "f", "g", "h"]);
+}
+
+/// Each tier keeps the newest save of its most recent periods.
+#[test]
+fn tiered_thins_older_saves() {
+    let policy = RetentionPolicy::Tiered {
+        hourly: 2,
+        daily: 2,
+        weekly: 2,
+    };
+    let mut saves = saves();
+    let expired = expired_autosaves(&mut saves, 1, &policy);
+
+    assert_eq!(names(&saves), ["a", "c", "e", "g"]);
+    assert_eq!(names(&expired), ["b", "d", "f", "h"]);
+}
+
+/// The documented config syntax can be parsed.
+#[test]
+fn parse_tiered_policy() {
+    let config: GameConfig = toml::from_str(
+        r#"
+        savegame_location = "~/saves"
+        autosaves = 5
+        autosave_timeout = 0
+        ignored_files = []
+        retention = { tiered = { hourly = 24, daily = 7 } }
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.retention,
+        RetentionPolicy::Tiered {
+            hourly: 24,
+            daily: 7,
+            weekly: 0
+        }
+    );
+}
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use game_saver::{
+    files::get_archive_files,
+    manually_save_game,
+    saves::{rename_save, sanitize_save_name},
+    GameConfig,
+};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// Assert that a name is rejected with an error that contains `reason`.
+fn assert_rejected(name: &str, reason: &str) {
+    let err = sanitize_save_name(name).expect_err(&format!("{name:?} has been accepted"));
+    assert!(err.to_string().contains(reason), "{name:?}: {err}");
+}
+
+/// Names are used as file names, so path separators would create saves outside of the save
+/// directory.
+#[test]
+fn separators() {
+    for name in [
+        "nested/save",
+        "/absolute",
+        "trailing/",
+        "../escape",
+        "null\0byte",
+    ] {
+        assert_rejected(name, "cannot contain '/'");
+    }
+}
+
+/// Names with a leading dot would be hidden files. This includes `.` and `..`, which are reserved
+/// by the file system.
+#[test]
+fn leading_dots() {
+    for name in [".", "..", ".hidden", "...dots"] {
+        assert_rejected(name, "cannot start with '.'");
+    }
+}
+
+/// Leading dashes would be interpreted as flags by `tar`.
+#[test]
+fn leading_dashes() {
+    for name in ["-", "--remove-files", "-rf"] {
+        assert_rejected(name, "cannot start with '-'");
+    }
+}
+
+/// Nothing is left of names, which only consist of whitespace.
+#[test]
+fn empty_names() {
+    for name in ["", " ", "   ", "\t", "\n", " \t\r\n "] {
+        assert_rejected(name, "cannot be empty");
+    }
+}
+
+/// Everything else is kept as it is, including inner dots, dashes and whitespace.
+#[test]
+fn valid_names() -> Result<()> {
+    for name in [
+        "save",
+        "before boss",
+        "v1.2-final",
+        "a.",
+        "trailing-",
+        "ünïcödé",
+        "back\\slash",
+    ] {
+        assert_eq!(sanitize_save_name(name)?, name);
+    }
+
+    Ok(())
+}
+
+/// Invalid names are rejected before anything is written.
+#[test]
+fn invalid_manual_save() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    for name in ["../escape", ".hidden", "-rf"] {
+        assert!(manually_save_game(&fixture.config, GAME, name, &no_progress).is_err());
+    }
+    assert!(get_archive_files(&fixture.config.save_dir(GAME))?.is_empty());
+
+    Ok(())
+}
+
+/// Empty and whitespace-only names are rejected for new saves and renames alike.
+#[test]
+fn empty_manual_save_and_rename() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let save_dir = fixture.config.save_dir(GAME);
+    for name in ["", "   ", "\t\n"] {
+        assert!(manually_save_game(&fixture.config, GAME, name, &no_progress).is_err());
+    }
+    assert!(get_archive_files(&save_dir)?.is_empty());
+
+    manually_save_game(&fixture.config, GAME, "named", &no_progress)?;
+    let save = get_archive_files(&save_dir)?.remove(0);
+    for name in ["", "   ", "\t\n"] {
+        assert!(rename_save(&save, name).is_err());
+    }
+    assert!(save.path.exists());
+
+    Ok(())
+}
@@ -0,0 +1,630 @@
+use std::{
+    env,
+    fs::{create_dir_all, read, read_dir, remove_dir_all, File},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::Local;
+use game_saver::{
+    archive::{list_archive, ArchiveOptions},
+    files::{available_space, get_archive_files, init_directories},
+    manually_save_game, read_save_file, restore_save, restore_summary, save_game,
+    saves::{remove_all_children, snapshot_savegame},
+    ArchiveFormat, GameConfig, InsufficientSpace, RestoreSummary, SaveFile,
+};
+use ignore::gitignore::Gitignore;
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// Create a manual save and restore it into an emptied savegame location.
+/// The restored files must match the original ones byte for byte.
+fn round_trip(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(GameConfig {
+        archive_format: format,
+        ..Default::default()
+    });
+    let location = fixture.savegame_location();
+    let original = read_tree(&location);
+
+    let path = manually_save_game(&fixture.config, GAME, "my save", &no_progress)?;
+    assert!(path.exists(), "Save {path:?} hasn't been created");
+
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    assert_eq!(saves.len(), 1);
+    let save = &saves[0];
+    assert_eq!(save.path, path);
+    assert_eq!(save.file_name, "my save");
+    assert_eq!(save.format, format);
+
+    remove_dir_all(&location)?;
+    create_dir_all(&location)?;
+    restore_save(&fixture.config, GAME, save, &no_progress)?;
+
+    assert_eq!(read_tree(&location), original);
+
+    Ok(())
+}
+
+#[test]
+fn round_trip_tar_zst() -> Result<()> {
+    round_trip(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn round_trip_tar_gz() -> Result<()> {
+    round_trip(ArchiveFormat::TarGz)
+}
+
+#[test]
+fn round_trip_zip() -> Result<()> {
+    round_trip(ArchiveFormat::Zip)
+}
+
+/// Single files can be read from a save, without extracting the whole save.
+/// Zip archives are read at the position of the file, so the large file is never decompressed.
+fn read_single_file(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(GameConfig {
+        archive_format: format,
+        ..Default::default()
+    });
+    let large: Vec<u8> = (0..8 * 1024 * 1024_u32)
+        .map(|index| (index.wrapping_mul(2_654_435_761) >> 24) as u8)
+        .collect();
+    write_file(&fixture.savegame_location(), "large.dat", &large);
+    manually_save_game(&fixture.config, GAME, "large", &no_progress)?;
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+
+    let content = read_save_file(&saves[0], "savegames/settings.ini")?;
+    assert_eq!(content, Some(b"fullscreen=true".to_vec()));
+    assert_eq!(read_save_file(&saves[0], "savegames/missing.dat")?, None);
+
+    Ok(())
+}
+
+#[test]
+fn read_single_file_tar_zst() -> Result<()> {
+    read_single_file(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn read_single_file_tar_gz() -> Result<()> {
+    read_single_file(ArchiveFormat::TarGz)
+}
+
+#[test]
+fn read_single_file_zip() -> Result<()> {
+    read_single_file(ArchiveFormat::Zip)
+}
+
+/// Files that have been created after a save must be gone after restoring that save.
+#[test]
+fn restore_removes_stale_files() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let location = fixture.savegame_location();
+    let original = read_tree(&location);
+
+    manually_save_game(&fixture.config, GAME, "before", &no_progress)?;
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+
+    // Change an existing file and add new files and directories.
+    write_file(&location, "save.dat", b"a newer save");
+    write_file(&location, "stale.dat", b"stale");
+    write_file(&location, "slots/3/slot.dat", b"stale slot");
+    create_dir_all(location.join("empty"))?;
+
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+
+    assert_eq!(read_tree(&location), original);
+
+    Ok(())
+}
+
+/// The summary lists the files of the save and all files that currently exist.
+fn summary(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(GameConfig {
+        archive_format: format,
+        ..Default::default()
+    });
+    let location = fixture.savegame_location();
+
+    manually_save_game(&fixture.config, GAME, "summary", &no_progress)?;
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    write_file(&location, "slots/3/slot.dat", b"new slot");
+
+    let summary = restore_summary(&fixture.config, GAME, &saves[0])?;
+    assert_eq!(
+        summary,
+        RestoreSummary {
+            files: 4,
+            bytes: 45,
+            removed: 5,
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn summary_tar_zst() -> Result<()> {
+    summary(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn summary_tar_gz() -> Result<()> {
+    summary(ArchiveFormat::TarGz)
+}
+
+#[test]
+fn summary_zip() -> Result<()> {
+    summary(ArchiveFormat::Zip)
+}
+
+/// Only the content of a directory is removed, the directory itself is kept.
+#[test]
+fn remove_all_children_keeps_directory() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let location = fixture.savegame_location();
+
+    remove_all_children(&location, &Gitignore::empty())?;
+
+    assert!(location.is_dir());
+    assert!(read_tree(&location).is_empty());
+
+    Ok(())
+}
+
+/// Ignored files are neither part of the archive, nor touched by a restore.
+fn ignored_files(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(GameConfig {
+        archive_format: format,
+        ignored_files: vec!["*.ini".into(), "slots/2/".into()],
+        ..Default::default()
+    });
+    let location = fixture.savegame_location();
+
+    let path = manually_save_game(&fixture.config, GAME, "ignored", &no_progress)?;
+    let mut files: Vec<String> = list_archive(format, &path)?.into_keys().collect();
+    files.sort();
+    assert_eq!(files, ["savegames/save.dat", "savegames/slots/1/slot.dat"]);
+
+    // Only the changes to files that are part of the save are reverted.
+    write_file(&location, "slots/1/slot.dat", b"changed slot");
+    write_file(&location, "settings.ini", b"fullscreen=false");
+    let mut expected = read_tree(&location);
+    expected.insert("slots/1/slot.dat".into(), Some(b"first slot".to_vec()));
+
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+
+    assert_eq!(read_tree(&location), expected);
+
+    Ok(())
+}
+
+#[test]
+fn ignored_files_tar_zst() -> Result<()> {
+    ignored_files(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn ignored_files_zip() -> Result<()> {
+    ignored_files(ArchiveFormat::Zip)
+}
+
+/// Files that are excluded by a `.gitignore` or `.ignore` in the savegame location are treated
+/// like `ignored_files`. The `ignored_files` take precedence.
+#[test]
+fn ignore_files() -> Result<()> {
+    let fixture = setup(GameConfig {
+        ignored_files: vec!["!keep.cache".into()],
+        ..Default::default()
+    });
+    let location = fixture.savegame_location();
+    write_file(&location, ".gitignore", b"*.cache\n");
+    write_file(&location, ".ignore", b"slots/2/\n");
+    write_file(&location, "shaders.cache", b"cache");
+    write_file(&location, "keep.cache", b"keep");
+
+    let path = manually_save_game(&fixture.config, GAME, "ignore files", &no_progress)?;
+    let mut files: Vec<String> = list_archive(ArchiveFormat::TarZst, &path)?
+        .into_keys()
+        .collect();
+    files.sort();
+    let expected = [
+        "savegames/.gitignore",
+        "savegames/.ignore",
+        "savegames/keep.cache",
+        "savegames/save.dat",
+        "savegames/settings.ini",
+        "savegames/slots/1/slot.dat",
+    ];
+    assert_eq!(files, expected);
+
+    // The ignored cache survives a restore.
+    write_file(&location, "shaders.cache", b"newer cache");
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+    assert_eq!(read(location.join("shaders.cache"))?, b"newer cache");
+
+    Ok(())
+}
+
+/// A truncated save fails to extract, but the current save stays untouched.
+#[test]
+fn truncated_save_keeps_current_save() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let location = fixture.savegame_location();
+
+    let path = manually_save_game(&fixture.config, GAME, "truncated", &no_progress)?;
+    // Simulate a save that has been interrupted mid-write.
+    let file = File::options().write(true).open(&path)?;
+    file.set_len(file.metadata()?.len() / 2)?;
+
+    write_file(&location, "save.dat", b"a newer save");
+    let current = read_tree(&location);
+
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    assert!(restore_save(&fixture.config, GAME, &saves[0], &no_progress).is_err());
+    assert_eq!(read_tree(&location), current);
+
+    // The staging directory has been cleaned up.
+    let mut entries: Vec<String> = read_dir(fixture.dir.path())?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<_>>()?;
+    entries.sort();
+    assert_eq!(entries, ["backups", "savegames"]);
+
+    Ok(())
+}
+
+/// Saves are restored into the `restore_location`, the watched location stays untouched.
+#[test]
+fn restore_into_restore_location() -> Result<()> {
+    let mut fixture = setup(GameConfig::default());
+    let location = fixture.savegame_location();
+    let original = read_tree(&location);
+
+    manually_save_game(&fixture.config, GAME, "restore location", &no_progress)?;
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    write_file(&location, "save.dat", b"a newer save");
+    let current = read_tree(&location);
+
+    // The restore location may have a different name than the savegame location.
+    let restore_location = fixture.dir.path().join("canonical");
+    write_file(&restore_location, "stale.dat", b"stale");
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.restore_location = Some(restore_location.to_string_lossy().to_string());
+
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+
+    assert_eq!(read_tree(&restore_location), original);
+    assert_eq!(read_tree(&location), current);
+
+    Ok(())
+}
+
+/// The snapshot before a restore captures the restore location, as that's what gets overwritten.
+/// Restoring the snapshot afterwards brings back the previous state of the restore location.
+#[test]
+fn snapshot_restore_location() -> Result<()> {
+    let mut fixture = setup(GameConfig::default());
+    let location = fixture.savegame_location();
+    let watched = read_tree(&location);
+
+    let restore_location = fixture.dir.path().join("canonical");
+    write_file(&restore_location, "canonical.dat", b"canonical");
+    let before = read_tree(&restore_location);
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.restore_location = Some(restore_location.to_string_lossy().to_string());
+
+    let format = ArchiveFormat::default();
+    let snapshot = SaveFile {
+        path: fixture
+            .dir
+            .path()
+            .join(format!("snapshot{}", format.extension())),
+        file_name: "snapshot".into(),
+        last_modified: Local::now(),
+        format,
+    };
+    snapshot_savegame(&fixture.config, GAME, &snapshot.path)?;
+
+    write_file(&restore_location, "canonical.dat", b"overwritten");
+    restore_save(&fixture.config, GAME, &snapshot, &no_progress)?;
+
+    assert_eq!(read_tree(&restore_location), before);
+    assert_eq!(read_tree(&location), watched);
+
+    Ok(())
+}
+
+/// Saves can be restored onto a clean system, on which the restore location doesn't exist yet.
+#[test]
+fn restore_into_missing_location() -> Result<()> {
+    let mut fixture = setup(GameConfig::default());
+    let original = read_tree(&fixture.savegame_location());
+    manually_save_game(&fixture.config, GAME, "new machine", &no_progress)?;
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+
+    let restore_location = fixture.dir.path().join("new_machine/.local/share/game");
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.restore_location = Some(restore_location.to_string_lossy().to_string());
+
+    let summary = restore_summary(&fixture.config, GAME, &saves[0])?;
+    assert_eq!(summary.removed, 0);
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+    assert_eq!(read_tree(&restore_location), original);
+
+    Ok(())
+}
+
+/// Relative savegame locations are resolved against the current directory.
+#[test]
+fn relative_savegame_location() -> Result<()> {
+    let mut fixture = setup(GameConfig::default());
+    let location = fixture.savegame_location();
+    let original = read_tree(&location);
+
+    // Tests share their working directory, which therefore cannot be changed.
+    // Instead, walk up to the root and down to the savegame location.
+    let cwd = env::current_dir()?;
+    let relative: PathBuf = cwd
+        .components()
+        .skip(1)
+        .map(|_| Component::ParentDir.as_os_str())
+        .chain(location.components().skip(1).map(|part| part.as_os_str()))
+        .collect();
+    assert!(relative.is_relative());
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.savegame_location = relative.to_string_lossy().to_string();
+
+    let path = manually_save_game(&fixture.config, GAME, "relative", &no_progress)?;
+    let files = list_archive(ArchiveFormat::TarZst, &path)?;
+    assert!(files.contains_key("savegames/save.dat"), "{files:?}");
+
+    write_file(&location, "save.dat", b"a newer save");
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+    assert_eq!(read_tree(&location), original);
+
+    Ok(())
+}
+
+/// Savegame locations that end in `..` are resolved to the directory they point to.
+#[test]
+fn parent_dir_savegame_location() -> Result<()> {
+    let mut fixture = setup(GameConfig::default());
+    let location = fixture.savegame_location();
+    let original = read_tree(&location);
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.savegame_location = location.join("slots/..").to_string_lossy().to_string();
+
+    let path = manually_save_game(&fixture.config, GAME, "parent", &no_progress)?;
+    let files = list_archive(ArchiveFormat::TarZst, &path)?;
+    assert!(files.contains_key("savegames/save.dat"), "{files:?}");
+
+    write_file(&location, "save.dat", b"a newer save");
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+    assert_eq!(read_tree(&location), original);
+
+    Ok(())
+}
+
+/// The root directory doesn't have a name, which would be the top-level entry of the archive.
+#[test]
+fn root_savegame_location() {
+    let fixture = setup(GameConfig::default());
+    let dest = fixture.config.save_dir(GAME).join("root.tar.zst");
+    for location in ["/", "/tmp/.."] {
+        let result = save_game(
+            Path::new(location),
+            &dest,
+            ArchiveFormat::TarZst,
+            &ArchiveOptions::default(),
+            &no_progress,
+        );
+        assert!(result.is_err(), "{location} has been archived");
+    }
+    assert!(!dest.exists());
+}
+
+/// Restoring into the backup directory would remove the backups.
+#[test]
+fn restore_location_in_backup_directory() {
+    let mut fixture = setup(GameConfig::default());
+    let backup_dir = fixture.config.backup_directory();
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.restore_location = Some(backup_dir.join("saves").to_string_lossy().to_string());
+
+    assert_eq!(fixture.config.errors().len(), 1);
+}
+
+/// Games with their own backup directory store all of their saves in there.
+#[test]
+fn game_backup_directory() -> Result<()> {
+    let mut fixture = setup(GameConfig::default());
+    let backup_dir = fixture.dir.path().join("other_drive");
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.backup_directory = Some(backup_dir.to_string_lossy().to_string());
+    game_config.autosaves = 2;
+    init_directories(&fixture.config)?;
+
+    assert_eq!(fixture.config.save_dir(GAME), backup_dir.join(GAME));
+    assert_eq!(
+        fixture.config.autosave_dir(GAME),
+        backup_dir.join(GAME).join("autosaves")
+    );
+    assert!(fixture.config.autosave_dir(GAME).is_dir());
+
+    let path = manually_save_game(&fixture.config, GAME, "other drive", &no_progress)?;
+    assert_eq!(path, backup_dir.join(GAME).join("other drive.tar.zst"));
+    let global_saves = fixture.config.backup_directory().join(GAME);
+    assert!(get_archive_files(&global_saves)?.is_empty());
+
+    Ok(())
+}
+
+/// The backup directory of a game mustn't be inside any watched savegame location.
+#[test]
+fn game_backup_directory_in_savegame_location() {
+    let mut fixture = setup(GameConfig::default());
+    let backup_dir = fixture.savegame_location().join("backups");
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.backup_directory = Some(backup_dir.to_string_lossy().to_string());
+
+    assert_eq!(fixture.config.errors().len(), 1);
+}
+
+/// New archives are written into the configured temp directory and moved to the saves afterwards.
+#[test]
+fn temp_directory() -> Result<()> {
+    let mut fixture = setup(GameConfig::default());
+    let temp_dir = fixture.dir.path().join("temp");
+    fixture.config.temp_directory = Some(temp_dir.to_string_lossy().to_string());
+    init_directories(&fixture.config)?;
+    assert!(temp_dir.is_dir());
+
+    let path = manually_save_game(&fixture.config, GAME, "via temp", &no_progress)?;
+    assert_eq!(
+        get_archive_files(&fixture.config.save_dir(GAME))?[0].path,
+        path
+    );
+    assert_eq!(read_dir(&temp_dir)?.count(), 0);
+
+    Ok(())
+}
+
+/// Siblings are saved next to the savegame location and restored into the same layout.
+fn siblings(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(GameConfig {
+        archive_format: format,
+        include_siblings: vec!["options.cfg".into(), "profiles".into(), "missing".into()],
+        ..Default::default()
+    });
+    let root = fixture.dir.path();
+    let location = fixture.savegame_location();
+    write_file(root, "options.cfg", b"difficulty=hard");
+    write_file(root, "profiles/player.dat", b"player one");
+    let original = read_tree(&location);
+    let profiles = read_tree(&root.join("profiles"));
+
+    let path = manually_save_game(&fixture.config, GAME, "siblings", &no_progress)?;
+    let files = list_archive(format, &path)?;
+    assert!(files.contains_key("options.cfg"));
+    assert!(files.contains_key("profiles/player.dat"));
+
+    write_file(root, "options.cfg", b"difficulty=easy");
+    write_file(root, "profiles/stale.dat", b"stale");
+    write_file(&location, "save.dat", b"a newer save");
+
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    let summary = restore_summary(&fixture.config, GAME, &saves[0])?;
+    assert_eq!(summary.files, 6);
+    assert_eq!(summary.removed, 7);
+
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+    assert_eq!(read_tree(&location), original);
+    assert_eq!(read_tree(&root.join("profiles")), profiles);
+    assert_eq!(read(root.join("options.cfg"))?, b"difficulty=hard");
+
+    // Siblings that aren't part of a save stay untouched.
+    write_file(root, "missing", b"created after the save");
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+    assert!(root.join("missing").exists());
+
+    Ok(())
+}
+
+#[test]
+fn siblings_tar_zst() -> Result<()> {
+    siblings(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn siblings_zip() -> Result<()> {
+    siblings(ArchiveFormat::Zip)
+}
+
+/// Siblings have to be names of other entries next to the savegame location.
+#[test]
+fn invalid_siblings() {
+    let mut fixture = setup(GameConfig::default());
+    let game_config = fixture.config.games.get_mut(GAME).unwrap();
+    game_config.include_siblings = vec![
+        "savegames".into(),
+        "..".into(),
+        "profiles/player.dat".into(),
+        "backups".into(),
+        "options.cfg".into(),
+    ];
+
+    assert_eq!(fixture.config.errors().len(), 4);
+}
+
+/// Saves that don't fit onto the backup volume are refused without leaving anything behind.
+/// A sparse file pretends to be bigger than the free space, without using any of it.
+#[test]
+fn insufficient_space() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let save_dir = fixture.config.save_dir(GAME);
+    let available = available_space(&save_dir)?;
+    File::create(fixture.savegame_location().join("huge.dat"))?.set_len(available * 3)?;
+
+    let err = manually_save_game(&fixture.config, GAME, "huge", &no_progress).unwrap_err();
+    assert!(err.downcast_ref::<InsufficientSpace>().is_some(), "{err:?}");
+    assert!(get_archive_files(&save_dir)?.is_empty());
+
+    Ok(())
+}
+
+/// Some games only use a single save file, which is used as savegame location.
+/// Only that file is saved and restored, while its siblings are left alone.
+fn single_file_location(format: ArchiveFormat) -> Result<()> {
+    let mut fixture = setup(GameConfig {
+        archive_format: format,
+        ..Default::default()
+    });
+    let location = fixture.savegame_location();
+    let save_file = location.join("save.dat");
+    fixture
+        .config
+        .games
+        .get_mut(GAME)
+        .unwrap()
+        .savegame_location = save_file.to_string_lossy().to_string();
+
+    manually_save_game(&fixture.config, GAME, "single", &no_progress)?;
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    let files: Vec<String> = list_archive(format, &saves[0].path)?.into_keys().collect();
+    assert_eq!(files, vec!["save.dat".to_string()]);
+
+    write_file(&location, "save.dat", b"a newer save");
+    write_file(&location, "settings.ini", b"fullscreen=false");
+    let siblings = read_tree(&location);
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+
+    assert!(save_file.is_file());
+    assert_eq!(read(&save_file)?, b"the current save");
+    let mut expected = siblings;
+    expected.insert(
+        PathBuf::from("save.dat"),
+        Some(b"the current save".to_vec()),
+    );
+    assert_eq!(read_tree(&location), expected);
+
+    Ok(())
+}
+
+#[test]
+fn single_file_location_tar_zst() -> Result<()> {
+    single_file_location(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn single_file_location_zip() -> Result<()> {
+    single_file_location(ArchiveFormat::Zip)
+}
@@ -0,0 +1,141 @@
+use std::{
+    fs::{create_dir_all, read_link, remove_dir_all, symlink_metadata},
+    os::unix::fs::symlink,
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use game_saver::{
+    files::get_archive_files, manually_save_game, restore_save, ArchiveFormat, GameConfig,
+};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// Create a file and a directory outside of the savegame location and link to both of them.
+/// Returns the directory that contains the link targets.
+fn setup_links(fixture: &Fixture) -> Result<PathBuf> {
+    let shared = fixture.dir.path().join("shared");
+    write_file(&shared, "config.ini", b"shared config");
+    write_file(&shared, "cloud/sync.dat", b"synced save");
+
+    let location = fixture.savegame_location();
+    symlink(shared.join("config.ini"), location.join("config.ini"))?;
+    symlink(shared.join("cloud"), location.join("cloud"))?;
+
+    Ok(shared)
+}
+
+/// Save the savegame location and restore it into an emptied location.
+fn save_and_restore(fixture: &Fixture) -> Result<()> {
+    manually_save_game(&fixture.config, GAME, "links", &no_progress)?;
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+
+    let location = fixture.savegame_location();
+    remove_dir_all(&location)?;
+    create_dir_all(&location)?;
+    restore_save(&fixture.config, GAME, &saves[0], &no_progress)?;
+
+    Ok(())
+}
+
+/// By default, the links themselves are saved and restored.
+fn keep_links(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(GameConfig {
+        archive_format: format,
+        ..Default::default()
+    });
+    let shared = setup_links(&fixture)?;
+
+    save_and_restore(&fixture)?;
+
+    let location = fixture.savegame_location();
+    for (link, target) in [("config.ini", "config.ini"), ("cloud", "cloud")] {
+        let link = location.join(link);
+        assert!(symlink_metadata(&link)?.file_type().is_symlink());
+        assert_eq!(read_link(&link)?, shared.join(target));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn keep_links_tar_zst() -> Result<()> {
+    keep_links(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn keep_links_zip() -> Result<()> {
+    keep_links(ArchiveFormat::Zip)
+}
+
+/// With `follow_symlinks`, the link targets are saved and restored as regular files.
+/// The original link targets aren't touched by the restore.
+fn follow_links(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(GameConfig {
+        archive_format: format,
+        follow_symlinks: true,
+        ..Default::default()
+    });
+    let shared = setup_links(&fixture)?;
+    let location = fixture.savegame_location();
+    let original = read_tree(&location);
+    let shared_original = read_tree(&shared);
+
+    save_and_restore(&fixture)?;
+
+    assert_eq!(read_tree(&location), original);
+    for path in ["config.ini", "cloud", "cloud/sync.dat"] {
+        let path = location.join(path);
+        assert!(!symlink_metadata(&path)?.file_type().is_symlink());
+    }
+    assert_eq!(read_tree(&shared), shared_original);
+
+    Ok(())
+}
+
+#[test]
+fn follow_links_tar_zst() -> Result<()> {
+    follow_links(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn follow_links_zip() -> Result<()> {
+    follow_links(ArchiveFormat::Zip)
+}
+
+/// Links to a parent directory would result in endless recursion and are rejected.
+#[test]
+fn follow_links_rejects_loops() -> Result<()> {
+    let fixture = setup(GameConfig {
+        follow_symlinks: true,
+        ..Default::default()
+    });
+    let location = fixture.savegame_location();
+    symlink(&location, location.join("slots/loop"))?;
+
+    let error = manually_save_game(&fixture.config, GAME, "loop", &no_progress).unwrap_err();
+    assert!(format!("{error:#}").contains("Symlink loop"));
+
+    Ok(())
+}
+
+/// Two sibling directories that link to each other would result in endless recursion as well.
+#[test]
+fn follow_links_rejects_sibling_loops() -> Result<()> {
+    let fixture = setup(GameConfig {
+        follow_symlinks: true,
+        ..Default::default()
+    });
+    let location = fixture.savegame_location();
+    create_dir_all(location.join("first"))?;
+    create_dir_all(location.join("second"))?;
+    symlink(location.join("second"), location.join("first/second"))?;
+    symlink(location.join("first"), location.join("second/first"))?;
+
+    let error = manually_save_game(&fixture.config, GAME, "loop", &no_progress).unwrap_err();
+    assert!(format!("{error:#}").contains("Symlink loop"));
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::{
+    fs::{create_dir_all, read, remove_file, write},
+    path::Path,
+};
+
+use anyhow::Result;
+use game_saver::{
+    archive::check_archive, autosave_game, files::get_archive_files, manually_save_game,
+    restore_save, verify_saves, ArchiveFormat, BackupStrategy, GameConfig,
+};
+use pretty_assertions::assert_eq;
+
+mod common;
+use common::*;
+
+/// Overwrite a few bytes in the middle of a file.
+fn corrupt(path: &Path) -> Result<()> {
+    let mut content = read(path)?;
+    let middle = content.len() / 2;
+    for byte in &mut content[middle..middle + 4] {
+        *byte = !*byte;
+    }
+    write(path, content)?;
+
+    Ok(())
+}
+
+/// Corrupt archives are detected by their checksums, all others are reported as healthy.
+fn detect_corruption(format: ArchiveFormat) -> Result<()> {
+    let fixture = setup(GameConfig {
+        archive_format: format,
+        ..Default::default()
+    });
+    // Make sure the archives are big enough, so the corrupted bytes hit compressed data.
+    write_file(&fixture.savegame_location(), "big.dat", &[7; 64 * 1024]);
+    manually_save_game(&fixture.config, GAME, "healthy", &no_progress)?;
+    let broken = manually_save_game(&fixture.config, GAME, "broken", &no_progress)?;
+
+    let report = verify_saves(&fixture.config, GAME, &no_progress)?;
+    assert_eq!(report.healthy, 2);
+    assert!(report.corrupt.is_empty());
+
+    corrupt(&broken)?;
+    let report = verify_saves(&fixture.config, GAME, &no_progress)?;
+    assert_eq!(report.healthy, 1);
+    let corrupt: Vec<&str> = report
+        .corrupt
+        .iter()
+        .map(|(save, _)| save.file_name.as_str())
+        .collect();
+    assert_eq!(corrupt, ["broken"]);
+
+    Ok(())
+}
+
+#[test]
+fn detect_corruption_tar_zst() -> Result<()> {
+    detect_corruption(ArchiveFormat::TarZst)
+}
+
+#[test]
+fn detect_corruption_tar_gz() -> Result<()> {
+    detect_corruption(ArchiveFormat::TarGz)
+}
+
+#[test]
+fn detect_corruption_zip() -> Result<()> {
+    detect_corruption(ArchiveFormat::Zip)
+}
+
+/// Incremental autosaves cannot be restored without their base.
+#[test]
+fn missing_base_is_corrupt() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 5,
+        backup_strategy: BackupStrategy::Incremental,
+        autosave_name_template: Some("auto_{counter}".into()),
+        ..Default::default()
+    });
+    let base = autosave_game(&fixture.config, GAME, &no_progress)?;
+    write_file(&fixture.savegame_location(), "save.dat", b"changed");
+    autosave_game(&fixture.config, GAME, &no_progress)?;
+
+    remove_file(base)?;
+    let report = verify_saves(&fixture.config, GAME, &no_progress)?;
+    assert_eq!(report.healthy, 0);
+    assert_eq!(report.corrupt.len(), 1);
+    assert!(report.corrupt[0].1.contains("missing"));
+
+    Ok(())
+}
+
+/// Data that zstd cannot compress. It's stored in raw blocks, which zstd copies without any
+/// further checks, so only the content checksum detects changes to it.
+fn incompressible(len: usize) -> Vec<u8> {
+    let mut state: u32 = 0x1234_5678;
+    (0..len)
+        .map(|_| {
+            // A simple xorshift, which is good enough to defeat the compression.
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+/// Created zstd archives carry a content checksum.
+#[test]
+fn zstd_checksum_is_enabled() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let path = manually_save_game(&fixture.config, GAME, "checksum", &no_progress)?;
+
+    let content = read(&path)?;
+    assert_eq!(content[..4], [0x28, 0xb5, 0x2f, 0xfd], "Not a zstd frame");
+    // The third bit of the frame header descriptor is the content checksum flag.
+    assert!(content[4] & 0x04 != 0, "Content checksum isn't enabled");
+
+    Ok(())
+}
+
+/// A flipped byte in the stored data fails the checksum and the current save stays untouched.
+#[test]
+fn flipped_byte_fails_checksum() -> Result<()> {
+    let fixture = setup(GameConfig::default());
+    let location = fixture.savegame_location();
+    write_file(&location, "random.dat", &incompressible(256 * 1024));
+    let path = manually_save_game(&fixture.config, GAME, "flipped", &no_progress)?;
+
+    let mut content = read(&path)?;
+    let middle = content.len() / 2;
+    content[middle] ^= 0x01;
+    write(&path, content)?;
+
+    let err = format!(
+        "{:#}",
+        check_archive(ArchiveFormat::TarZst, &path).unwrap_err()
+    );
+    assert!(err.contains("checksum"), "Unexpected error: {err}");
+
+    write_file(&location, "save.dat", b"a newer save");
+    let current = read_tree(&location);
+    let saves = get_archive_files(&fixture.config.save_dir(GAME))?;
+    assert!(restore_save(&fixture.config, GAME, &saves[0], &no_progress).is_err());
+    assert_eq!(read_tree(&location), current);
+
+    Ok(())
+}
+
+/// Trashed autosaves and the safety snapshots of restores are checked as well.
+#[test]
+fn trashed_autosaves_and_snapshots() -> Result<()> {
+    let fixture = setup(GameConfig {
+        autosaves: 2,
+        ..Default::default()
+    });
+    let config = &fixture.config;
+    write_file(&fixture.savegame_location(), "big.dat", &[7; 64 * 1024]);
+    let autosave = autosave_game(config, GAME, &no_progress)?;
+    let content = read(&autosave)?;
+
+    let trashed_dir = config.trash_dir(GAME).join("autosaves");
+    let snapshot_dir = config.snapshot_dir(GAME);
+    for dir in [&trashed_dir, &snapshot_dir] {
+        create_dir_all(dir)?;
+    }
+    let trashed = trashed_dir.join("2024-01-01_00-00-00_trashed.tar.zst");
+    let snapshot = snapshot_dir.join("before_restore.tar.zst");
+    for path in [&trashed, &snapshot] {
+        write(path, &content)?;
+    }
+
+    let report = verify_saves(config, GAME, &no_progress)?;
+    assert_eq!(report.healthy, 3);
+    assert!(report.corrupt.is_empty());
+
+    corrupt(&trashed)?;
+    corrupt(&snapshot)?;
+    let report = verify_saves(config, GAME, &no_progress)?;
+    assert_eq!(report.healthy, 1);
+    let mut corrupt: Vec<_> = report.corrupt.iter().map(|(save, _)| &save.path).collect();
+    corrupt.sort();
+    assert_eq!(corrupt, [&trashed, &snapshot]);
+
+    Ok(())
+}